@@ -0,0 +1,42 @@
+//! `cargo bench --features parallel` compares streaming (always sequential)
+//! with batch inference, which splits into chunks under the feature.
+
+use aif_core::{infer_schema_from_strs, InferOptions, SchemaInferrer};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+fn samples() -> Vec<String> {
+    (0..10_000)
+        .map(|i| {
+            format!(
+                r#"{{"id":{i},"name":"user{i}","score":{}.25,"active":{},"tags":["a","b"],"meta":{{"n":{}}}}}"#,
+                i % 100,
+                i % 2 == 0,
+                i % 7
+            )
+        })
+        .collect()
+}
+
+fn bench_inference(c: &mut Criterion) {
+    let owned = samples();
+    let samples: Vec<&str> = owned.iter().map(String::as_str).collect();
+    let mut group = c.benchmark_group("infer_10k_objects");
+    group.throughput(Throughput::Elements(samples.len() as u64));
+
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            let mut inferrer = SchemaInferrer::new(InferOptions::default());
+            for s in &samples {
+                inferrer.feed(s).unwrap();
+            }
+            inferrer.finish().unwrap()
+        })
+    });
+    group.bench_function("batch", |b| {
+        b.iter(|| infer_schema_from_strs(&samples, InferOptions::default()).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_inference);
+criterion_main!(benches);
@@ -0,0 +1,224 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Human-written text attached to one inferred node.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Annotation {
+    pub title: Option<String>,
+    pub description: Option<String>,
+}
+
+pub(crate) enum Step<'a> {
+    Property(&'a str),
+    Items,
+}
+
+/// Adds `title` / `description` to the sub-schemas addressed by the keys of
+/// `annotations` and returns the keys that address nothing. A key is either a
+/// dot path with the `[]` items convention (`meta.owner`, `rows[].id`, `""`
+/// for the root) or a JSON Pointer into the schema (`/properties/meta`).
+pub(crate) fn apply_annotations(
+    doc: &mut Map<String, Value>,
+    annotations: &BTreeMap<String, Annotation>,
+) -> Vec<String> {
+    let mut unmatched = Vec::new();
+    for (path, annotation) in annotations {
+        let matched = if path.starts_with('/') {
+            match pointer_target(doc, path) {
+                Some(Value::Object(m)) => {
+                    annotate(m, annotation);
+                    true
+                }
+                _ => false,
+            }
+        } else {
+            for_each_at(doc, path, &mut |m| annotate(m, annotation))
+        };
+        if !matched {
+            unmatched.push(path.clone());
+        }
+    }
+    unmatched
+}
+
+pub(crate) fn pointer_target<'a>(
+    doc: &'a mut Map<String, Value>,
+    pointer: &str,
+) -> Option<&'a mut Value> {
+    let rest = &pointer[1..];
+    let (head, tail) = rest.split_once('/').unwrap_or((rest, ""));
+    let value = doc.get_mut(&head.replace("~1", "/").replace("~0", "~"))?;
+    if tail.is_empty() {
+        Some(value)
+    } else {
+        value.pointer_mut(&format!("/{tail}"))
+    }
+}
+
+/// Calls `f` on every object sub-schema addressed by the dot path `path`
+/// (see `apply_annotations`) and tells whether there was any.
+pub(crate) fn for_each_at(
+    doc: &mut Map<String, Value>,
+    path: &str,
+    f: &mut dyn FnMut(&mut Map<String, Value>),
+) -> bool {
+    apply_at(doc, &parse_dot_path(path), f)
+}
+
+pub(crate) fn parse_dot_path(path: &str) -> Vec<Step<'_>> {
+    let mut steps = Vec::new();
+    if path.is_empty() {
+        return steps;
+    }
+    for segment in path.split('.') {
+        let mut name = segment;
+        let mut depth = 0;
+        while let Some(rest) = name.strip_suffix("[]") {
+            name = rest;
+            depth += 1;
+        }
+        if !name.is_empty() {
+            steps.push(Step::Property(name));
+        }
+        steps.extend((0..depth).map(|_| Step::Items));
+    }
+    steps
+}
+
+/// Values of a sample at the dot path parsed by `parse_dot_path`; `[]` steps
+/// go through every element of an array.
+pub(crate) fn values_at<'a>(v: &'a Value, steps: &[Step]) -> Vec<&'a Value> {
+    let mut current = vec![v];
+    for step in steps {
+        current = current
+            .into_iter()
+            .flat_map(|v| -> Vec<&Value> {
+                match (step, v) {
+                    (Step::Property(name), Value::Object(m)) => m.get(*name).into_iter().collect(),
+                    (Step::Items, Value::Array(items)) => items.iter().collect(),
+                    _ => Vec::new(),
+                }
+            })
+            .collect();
+    }
+    current
+}
+
+// Ветви anyOf/oneOf прозрачны: путь ищется в каждой из них
+fn apply_at(
+    m: &mut Map<String, Value>,
+    steps: &[Step],
+    f: &mut dyn FnMut(&mut Map<String, Value>),
+) -> bool {
+    let Some((step, rest)) = steps.split_first() else {
+        f(m);
+        return true;
+    };
+    let mut matched = false;
+    for key in ["anyOf", "oneOf"] {
+        if let Some(Value::Array(branches)) = m.get_mut(key) {
+            for branch in branches.iter_mut().filter_map(Value::as_object_mut) {
+                matched |= apply_at(branch, steps, f);
+            }
+        }
+    }
+    let children: Vec<&mut Value> = match step {
+        Step::Property(name) => m
+            .get_mut("properties")
+            .and_then(Value::as_object_mut)
+            .and_then(|props| props.get_mut(*name))
+            .into_iter()
+            .collect(),
+        // у кортежа элементы описаны позициями: prefixItems или массив в items
+        Step::Items => {
+            let key = if m.contains_key("prefixItems") {
+                "prefixItems"
+            } else {
+                "items"
+            };
+            match m.get_mut(key) {
+                Some(Value::Array(positions)) => positions.iter_mut().collect(),
+                other => other.into_iter().collect(),
+            }
+        }
+    };
+    for child in children.into_iter().filter_map(Value::as_object_mut) {
+        matched |= apply_at(child, rest, f);
+    }
+    matched
+}
+
+fn annotate(m: &mut Map<String, Value>, annotation: &Annotation) {
+    if let Some(title) = &annotation.title {
+        m.insert("title".to_string(), Value::String(title.clone()));
+    }
+    if let Some(description) = &annotation.description {
+        m.insert(
+            "description".to_string(),
+            Value::String(description.clone()),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{infer_schema_annotated_rs, InferOptions, NullableStyle};
+    use serde_json::json;
+
+    fn described(text: &str) -> Annotation {
+        Annotation {
+            description: Some(text.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn annotations_by_dot_path_and_pointer() {
+        let samples = [
+            r#"{"id":1,"meta":{"owner":"a"},"rows":[{"n":1}],"grid":[[true]]}"#,
+            r#"{"id":2,"meta":null,"rows":[]}"#,
+        ];
+        let mut annotations = BTreeMap::new();
+        annotations.insert(
+            String::new(),
+            Annotation {
+                title: Some("Event".to_string()),
+                ..Default::default()
+            },
+        );
+        annotations.insert("meta.owner".to_string(), described("who"));
+        annotations.insert("rows[].n".to_string(), described("count"));
+        annotations.insert("grid[][]".to_string(), described("cell"));
+        annotations.insert("/properties/id".to_string(), described("key"));
+        annotations.insert("missing.x".to_string(), described("-"));
+        annotations.insert("id[]".to_string(), described("-"));
+        annotations.insert("/properties/nope".to_string(), described("-"));
+        let opts = InferOptions {
+            nullable_style: NullableStyle::AnyOf,
+            annotations,
+            ..Default::default()
+        };
+
+        let (schema, unmatched) = infer_schema_annotated_rs(&samples, opts).unwrap();
+        assert_eq!(unmatched, ["/properties/nope", "id[]", "missing.x"]);
+        let v: Value = serde_json::from_str(&schema).unwrap();
+        assert_eq!(v["title"], "Event");
+        assert_eq!(v["properties"]["id"]["description"], "key");
+        // meta обёрнут в anyOf с null — путь проходит сквозь ветви
+        assert_eq!(
+            v["properties"]["meta"]["anyOf"][0]["properties"]["owner"],
+            json!({"type": "string", "description": "who"})
+        );
+        assert_eq!(
+            v["properties"]["rows"]["items"]["properties"]["n"]["description"],
+            "count"
+        );
+        assert_eq!(
+            v["properties"]["grid"]["items"]["items"]["description"],
+            "cell"
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::{json, Map, Value};
+
+use crate::{parse_sample, root_schema, InferError, InferOptions, Node};
+
+/// Samples routed by the value of `field`; `rest` holds the ones without it.
+pub(crate) struct Partition {
+    field: String,
+    // Ключ — строка как есть или JSON-сериализация нестрокового значения
+    branches: BTreeMap<String, (Value, Node)>,
+    rest: Option<Node>,
+}
+
+/// Routes the samples by discriminator when `discriminator` is set or
+/// `detect_discriminator` finds a field; None means plain inference.
+pub(crate) fn partition<S: AsRef<str>>(
+    samples: &[S],
+    opts: &InferOptions,
+) -> Result<Option<Partition>, InferError> {
+    if opts.discriminator.is_none() && !opts.detect_discriminator {
+        return Ok(None);
+    }
+    let mut values = Vec::with_capacity(samples.len());
+    for (index, s) in samples.iter().enumerate() {
+        values.push(parse_sample(s.as_ref(), index, opts)?);
+    }
+    let field = match &opts.discriminator {
+        Some(field) => field.clone(),
+        None => match detect_field(&values, opts.discriminator_max_values) {
+            Some(field) => field,
+            None => return Ok(None),
+        },
+    };
+
+    let mut p = Partition {
+        field,
+        branches: BTreeMap::new(),
+        rest: None,
+    };
+    for v in &values {
+        match v.get(&p.field) {
+            Some(tag) => {
+                let key = tag.as_str().map_or_else(|| tag.to_string(), String::from);
+                p.branches
+                    .entry(key)
+                    .or_insert_with(|| (tag.clone(), Node::default()))
+                    .1
+                    .observe(v, opts);
+            }
+            None => p.rest.get_or_insert_with(Node::default).observe(v, opts),
+        }
+    }
+    Ok(Some(p))
+}
+
+// Строковое поле корня, которое есть в каждом образце, принимает от 2 до
+// max_values значений и разбивает образцы на группы с разным набором ключей.
+// Из нескольких кандидатов берём поле с меньшим числом значений, затем по имени.
+fn detect_field(values: &[Value], max_values: usize) -> Option<String> {
+    let first = values.first()?.as_object()?;
+    let mut best: Option<(usize, &String)> = None;
+    for (field, v) in first {
+        if !v.is_string() {
+            continue;
+        }
+        let mut shapes: BTreeMap<&str, BTreeSet<&String>> = BTreeMap::new();
+        let complete = values.iter().all(|sample| {
+            let Some(tag) = sample.get(field).and_then(Value::as_str) else {
+                return false;
+            };
+            let keys = sample.as_object().into_iter().flat_map(|o| o.keys());
+            shapes.entry(tag).or_default().extend(keys);
+            shapes.len() <= max_values
+        });
+        let distinct_shapes = shapes.values().collect::<BTreeSet<_>>().len() > 1;
+        // уникальное для каждого образца поле — идентификатор, а не тег
+        let low_cardinality = shapes.len() >= 2 && shapes.len() < values.len();
+        if complete
+            && low_cardinality
+            && distinct_shapes
+            && best.is_none_or(|(n, name)| (shapes.len(), field) < (n, name))
+        {
+            best = Some((shapes.len(), field));
+        }
+    }
+    best.map(|(_, field)| field.clone())
+}
+
+/// `oneOf` with one branch per discriminator value (plus a catch-all for
+/// samples without it) and a `discriminator` mapping values to branches.
+pub(crate) fn partition_schema(p: &Partition, opts: &InferOptions) -> Map<String, Value> {
+    let mut one_of = Vec::new();
+    let mut mapping = Map::new();
+    for (key, (tag, node)) in &p.branches {
+        let mut branch = root_schema(node, opts);
+        branch.remove("$schema");
+        if let Some(Value::Object(props)) = branch.get_mut("properties") {
+            props.insert(p.field.clone(), json!({ "const": tag }));
+        }
+        let required = branch
+            .entry("required")
+            .or_insert_with(|| Value::Array(Vec::new()));
+        if let Value::Array(required) = required {
+            let field = Value::String(p.field.clone());
+            if !required.contains(&field) {
+                required.push(field);
+                required.sort_by(|a, b| a.as_str().cmp(&b.as_str()));
+            }
+        }
+        mapping.insert(
+            key.clone(),
+            Value::String(format!("#/oneOf/{}", one_of.len())),
+        );
+        one_of.push(Value::Object(branch));
+    }
+    if let Some(rest) = &p.rest {
+        let mut branch = root_schema(rest, opts);
+        branch.remove("$schema");
+        // без этого образец с дискриминатором подошёл бы и сюда, нарушив oneOf
+        branch.insert("not".to_string(), json!({ "required": [p.field] }));
+        one_of.push(Value::Object(branch));
+    }
+    let mut out = Map::new();
+    out.insert("$schema".to_string(), Value::from(opts.draft.uri()));
+    out.insert("oneOf".to_string(), Value::Array(one_of));
+    out.insert(
+        "discriminator".to_string(),
+        json!({ "propertyName": p.field, "mapping": mapping }),
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::infer_with;
+
+    const EVENTS: &[&str] = &[
+        r#"{"type":"click","id":"e1","x":1,"y":2}"#,
+        r#"{"type":"purchase","id":"e2","amount":9.5}"#,
+        r#"{"type":"click","id":"e3","x":5,"y":0}"#,
+        r#"{"id":"e4","note":"legacy"}"#,
+    ];
+
+    #[test]
+    fn named_discriminator_splits_into_one_of() {
+        let v = infer_with(
+            EVENTS,
+            InferOptions {
+                discriminator: Some("type".to_string()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            v["discriminator"],
+            json!({"propertyName": "type", "mapping": {"click": "#/oneOf/0", "purchase": "#/oneOf/1"}})
+        );
+        let branches = v["oneOf"].as_array().unwrap();
+        assert_eq!(branches.len(), 3);
+        assert_eq!(branches[0]["properties"]["type"], json!({"const": "click"}));
+        assert_eq!(branches[0]["required"], json!(["id", "type", "x", "y"]));
+        assert_eq!(
+            branches[1]["properties"]["type"],
+            json!({"const": "purchase"})
+        );
+        assert!(branches[1]["properties"].get("x").is_none());
+        assert_eq!(branches[2]["not"], json!({"required": ["type"]}));
+        assert_eq!(branches[2]["properties"]["note"]["type"], "string");
+    }
+
+    #[test]
+    fn detection_needs_a_complete_low_cardinality_field() {
+        let opts = InferOptions {
+            detect_discriminator: true,
+            ..Default::default()
+        };
+        // у e4 нет поля type, id уникален для каждого образца — кандидатов нет
+        assert!(infer_with(EVENTS, opts.clone()).get("oneOf").is_none());
+
+        let v = infer_with(&EVENTS[..3], opts.clone());
+        assert_eq!(v["discriminator"]["propertyName"], "type");
+
+        // значения разные, но форма одинаковая — кластеризовать нечего
+        let same = [
+            r#"{"kind":"a","n":1}"#,
+            r#"{"kind":"b","n":2}"#,
+            r#"{"kind":"a","n":3}"#,
+        ];
+        assert_eq!(infer_with(&same, opts)["type"], "object");
+    }
+}
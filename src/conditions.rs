@@ -0,0 +1,134 @@
+use serde_json::{json, Map, Value};
+
+use crate::annotations::{for_each_at, parse_dot_path, values_at};
+use crate::{parse_sample, InferError, InferOptions, Node};
+
+/// Adds `if` / `then` / `else` for every rule in `conditional_rules` to the
+/// object sub-schema holding the rule's field. `then` is inferred from the
+/// objects whose field equals the value, `else` from the rest, including the
+/// objects without the field; a second rule on the same object goes to `allOf`.
+pub(crate) fn apply_conditions<S: AsRef<str>>(
+    doc: &mut Map<String, Value>,
+    samples: &[S],
+    opts: &InferOptions,
+) -> Result<(), InferError> {
+    if opts.conditional_rules.is_empty() {
+        return Ok(());
+    }
+    let mut values = Vec::with_capacity(samples.len());
+    for (index, s) in samples.iter().enumerate() {
+        values.push(parse_sample(s.as_ref(), index, opts)?);
+    }
+    for rule in &opts.conditional_rules {
+        let (parent, field) = rule.field.rsplit_once('.').unwrap_or(("", &rule.field));
+        let steps = parse_dot_path(parent);
+        let mut matching = Node::default();
+        let mut rest = Node::default();
+        for v in &values {
+            for obj in values_at(v, &steps).into_iter().filter(|v| v.is_object()) {
+                let branch = if obj.get(field) == Some(&rule.value) {
+                    &mut matching
+                } else {
+                    &mut rest
+                };
+                branch.observe(obj, opts);
+            }
+        }
+        // ни один объект не подошёл — условие ничего не уточняет
+        if matching.sample_count == 0 {
+            continue;
+        }
+        let mut condition = Map::new();
+        condition.insert(
+            "if".to_string(),
+            json!({ "properties": { field: { "const": rule.value } }, "required": [field] }),
+        );
+        condition.insert("then".to_string(), matching.to_json_schema(opts));
+        if rest.sample_count > 0 {
+            condition.insert("else".to_string(), rest.to_json_schema(opts));
+        }
+        for_each_at(doc, parent, &mut |m| {
+            if m.contains_key("if") {
+                let all_of = m.entry("allOf").or_insert_with(|| Value::Array(Vec::new()));
+                if let Value::Array(all_of) = all_of {
+                    all_of.push(Value::Object(condition.clone()));
+                }
+            } else {
+                m.extend(condition.clone());
+            }
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::infer_with;
+    use crate::{ConditionalRule, InferOptions};
+    use serde_json::{json, Value};
+
+    const PAYMENTS: &[&str] = &[
+        r#"{"id":1,"payment":{"kind":"legacy","account":"A1"}}"#,
+        r#"{"id":2,"payment":{"kind":"card","card_number":"4111","expiry":"12/30"}}"#,
+        r#"{"id":3,"payment":{"kind":"legacy","account":"A2","branch":7}}"#,
+        r#"{"id":4,"payment":{"card_number":"5500","expiry":"01/29"}}"#,
+    ];
+
+    fn rule(field: &str, value: Value) -> ConditionalRule {
+        ConditionalRule {
+            field: field.to_string(),
+            value,
+        }
+    }
+
+    #[test]
+    fn nested_rule_splits_then_and_else() {
+        let opts = InferOptions {
+            conditional_rules: vec![rule("payment.kind", json!("legacy"))],
+            ..Default::default()
+        };
+        let v = infer_with(PAYMENTS, opts);
+        let payment = &v["properties"]["payment"];
+        assert_eq!(
+            payment["if"],
+            json!({"properties": {"kind": {"const": "legacy"}}, "required": ["kind"]})
+        );
+        assert_eq!(payment["then"]["required"], json!(["account", "kind"]));
+        assert_eq!(
+            payment["then"]["properties"]["kind"],
+            json!({"const": "legacy"})
+        );
+        // образец без kind попадает в else, поэтому kind там необязателен
+        assert_eq!(
+            payment["else"]["required"],
+            json!(["card_number", "expiry"])
+        );
+        assert!(payment["else"]["properties"].get("account").is_none());
+        assert!(v.get("if").is_none());
+    }
+
+    #[test]
+    fn several_rules_and_root_fields() {
+        let samples = [
+            r#"{"v":1,"a":"x"}"#,
+            r#"{"v":2,"b":true}"#,
+            r#"{"v":2,"b":false,"c":null}"#,
+        ];
+        let opts = InferOptions {
+            conditional_rules: vec![
+                rule("v", json!(1)),
+                rule("v", json!(2)),
+                rule("v", json!(3)),
+            ],
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts);
+        assert_eq!(v["if"]["properties"]["v"], json!({"const": 1}));
+        assert_eq!(v["then"]["required"], json!(["a", "v"]));
+        let all_of = v["allOf"].as_array().unwrap();
+        // значение 3 не встретилось — правило пропущено
+        assert_eq!(all_of.len(), 1);
+        assert_eq!(all_of[0]["then"]["required"], json!(["b", "v"]));
+        assert_eq!(all_of[0]["else"]["required"], json!(["a", "v"]));
+    }
+}
@@ -0,0 +1,186 @@
+use serde_json::{Map, Value};
+
+use crate::annotations::{for_each_at, parse_dot_path, values_at};
+use crate::validate::is_valid;
+use crate::{parse_sample, InferError, InferOptions, SchemaDraft};
+
+/// Adds `contains` for every rule in `contains_rules` whose candidate matched
+/// at least one element of every array observed at the rule's path, and
+/// `minContains` when every array held more than one match. Values at the
+/// path that are not arrays are ignored; an empty array drops the rule. A
+/// second rule on the same array goes to `allOf`.
+pub(crate) fn apply_contains<S: AsRef<str>>(
+    doc: &mut Map<String, Value>,
+    samples: &[S],
+    opts: &InferOptions,
+) -> Result<(), InferError> {
+    if opts.contains_rules.is_empty() {
+        return Ok(());
+    }
+    let mut values = Vec::with_capacity(samples.len());
+    for (index, s) in samples.iter().enumerate() {
+        values.push(parse_sample(s.as_ref(), index, opts)?);
+    }
+    for rule in &opts.contains_rules {
+        let steps = parse_dot_path(&rule.path);
+        let candidate = rule.candidate.to_schema();
+        // наименьшее число совпадений в одном массиве
+        let mut guaranteed: Option<usize> = None;
+        for v in &values {
+            for items in values_at(v, &steps).into_iter().filter_map(Value::as_array) {
+                let found = items
+                    .iter()
+                    .filter(|item| is_valid(&candidate, item))
+                    .count();
+                guaranteed = Some(guaranteed.map_or(found, |g| g.min(found)));
+            }
+        }
+        let Some(min) = guaranteed.filter(|min| *min > 0) else {
+            continue;
+        };
+        let mut keywords = Map::new();
+        keywords.insert("contains".to_string(), candidate);
+        // minContains появился в 2019-09
+        let has_min_contains = matches!(
+            opts.draft,
+            SchemaDraft::Draft201909 | SchemaDraft::Draft202012
+        );
+        if min > 1 && has_min_contains {
+            keywords.insert("minContains".to_string(), Value::from(min));
+        }
+        for_each_at(doc, &rule.path, &mut |m| {
+            for schema in array_schemas(m) {
+                if schema.contains_key("contains") {
+                    let all_of = schema
+                        .entry("allOf")
+                        .or_insert_with(|| Value::Array(Vec::new()));
+                    if let Value::Array(all_of) = all_of {
+                        all_of.push(Value::Object(keywords.clone()));
+                    }
+                } else {
+                    schema.extend(keywords.clone());
+                }
+            }
+        });
+    }
+    Ok(())
+}
+
+// Схема массива или её ветви anyOf, описывающие массив (nullable_style = AnyOf)
+fn array_schemas(m: &mut Map<String, Value>) -> Vec<&mut Map<String, Value>> {
+    let is_array = |m: &Map<String, Value>| match m.get("type") {
+        Some(Value::String(t)) => t == "array",
+        Some(Value::Array(ts)) => ts.iter().any(|t| t == "array"),
+        _ => false,
+    };
+    if is_array(m) {
+        return vec![m];
+    }
+    match m.get_mut("anyOf") {
+        Some(Value::Array(branches)) => branches
+            .iter_mut()
+            .filter_map(Value::as_object_mut)
+            .filter(|b| is_array(b))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::infer_with;
+    use crate::{
+        validate_sample_rs, ContainsCandidate, ContainsRule, InferOptions, NullableStyle,
+        SchemaDraft,
+    };
+    use serde_json::{json, Value};
+
+    const EVENTS: &[&str] = &[
+        r#"{"events":[{"type":"init","at":1},{"type":"click","at":2},"noise"]}"#,
+        r#"{"events":[{"type":"click","at":3},{"type":"init","at":4},{"type":"init","at":5}]}"#,
+        r#"{"events":[{"type":"init","at":6},{"type":"init","at":7}],"batches":[[{"type":"init"}]]}"#,
+    ];
+
+    fn fields(path: &str, field: &str, value: Value) -> ContainsRule {
+        ContainsRule {
+            path: path.to_string(),
+            candidate: ContainsCandidate::Fields([(field.to_string(), value)].into()),
+        }
+    }
+
+    #[test]
+    fn field_predicate_held_in_every_array() {
+        let opts = InferOptions {
+            contains_rules: vec![
+                fields("events", "type", json!("init")),
+                fields("events", "type", json!("scroll")),
+                fields("batches[]", "type", json!("init")),
+            ],
+            ..Default::default()
+        };
+        let v = infer_with(EVENTS, opts.clone());
+        let events = &v["properties"]["events"];
+        assert_eq!(
+            events["contains"],
+            json!({
+                "type": "object",
+                "properties": {"type": {"const": "init"}},
+                "required": ["type"]
+            })
+        );
+        // в первом массиве только одно совпадение
+        assert!(events.get("minContains").is_none());
+        assert_eq!(
+            v["properties"]["batches"]["items"]["contains"]["required"],
+            json!(["type"])
+        );
+
+        // выведенная схема принимает свои образцы и отвергает массив без init
+        let schema = serde_json::to_string(&v).unwrap();
+        for sample in EVENTS {
+            assert_eq!(validate_sample_rs(&schema, sample).unwrap(), []);
+        }
+        let errors = validate_sample_rs(&schema, r#"{"events":[{"type":"click","at":1}]}"#);
+        assert_eq!(errors.unwrap()[0].path, "events");
+
+        let mut samples = EVENTS.to_vec();
+        samples.push(r#"{"events":[]}"#);
+        assert!(infer_with(&samples, opts)["properties"]["events"]
+            .get("contains")
+            .is_none());
+    }
+
+    #[test]
+    fn schema_candidate_and_min_contains() {
+        let candidate =
+            json!({"type": "object", "properties": {"at": {"minimum": 2}}, "required": ["at"]});
+        let rule = ContainsRule {
+            path: "events".to_string(),
+            candidate: ContainsCandidate::Schema(candidate.clone()),
+        };
+        let opts = InferOptions {
+            contains_rules: vec![rule, fields("events", "type", json!("init"))],
+            nullable_style: NullableStyle::AnyOf,
+            ..Default::default()
+        };
+        let mut samples = EVENTS[1..].to_vec();
+        samples.push(r#"{"events":null}"#);
+        let v = infer_with(&samples, opts.clone());
+        let branches = v["properties"]["events"]["anyOf"].as_array().unwrap();
+        assert_eq!(branches[0]["contains"], candidate);
+        assert_eq!(branches[0]["minContains"], 2);
+        assert!(branches[1].get("contains").is_none());
+        let extra = &branches[0]["allOf"][0];
+        assert_eq!(extra["contains"]["required"], json!(["type"]));
+        assert_eq!(extra["minContains"], 2);
+
+        let draft07 = InferOptions {
+            draft: SchemaDraft::Draft07,
+            ..opts
+        };
+        let v = infer_with(&samples, draft07);
+        let events = &v["properties"]["events"]["anyOf"][0];
+        assert_eq!(events["contains"], candidate);
+        assert!(events.get("minContains").is_none());
+    }
+}
@@ -0,0 +1,326 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Media types recognised from the first decoded bytes of a base64 value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MediaType {
+    Png,
+    Jpeg,
+    Gzip,
+    Pdf,
+}
+
+const MAGIC: [(MediaType, &[u8]); 4] = [
+    (MediaType::Png, b"\x89PNG\r\n\x1a\n"),
+    (MediaType::Jpeg, b"\xff\xd8\xff"),
+    (MediaType::Gzip, b"\x1f\x8b"),
+    (MediaType::Pdf, b"%PDF-"),
+];
+
+impl MediaType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MediaType::Png => "image/png",
+            MediaType::Jpeg => "image/jpeg",
+            MediaType::Gzip => "application/gzip",
+            MediaType::Pdf => "application/pdf",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<MediaType> {
+        MAGIC.iter().map(|(t, _)| *t).find(|t| t.as_str() == name)
+    }
+
+    fn sniff(bytes: &[u8]) -> Option<MediaType> {
+        MAGIC
+            .iter()
+            .find(|(_, magic)| bytes.starts_with(magic))
+            .map(|(t, _)| *t)
+    }
+}
+
+/// Every string of a node was canonical padded base64; `media_type` is kept
+/// while all of them decoded to the same kind of file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Base64 {
+    pub media_type: Option<MediaType>,
+}
+
+impl Base64 {
+    /// None unless `s` is at least `min_len` characters of valid base64.
+    pub fn detect(s: &str, min_len: usize) -> Option<Base64> {
+        let bytes = s.as_bytes();
+        if bytes.len() < min_len || bytes.is_empty() || !bytes.len().is_multiple_of(4) {
+            return None;
+        }
+        let data = s.trim_end_matches('=').as_bytes();
+        let padding = bytes.len() - data.len();
+        if padding > 2 {
+            return None;
+        }
+        let mut last = 0;
+        for &b in data {
+            last = sextet(b)?;
+        }
+        // неиспользуемые биты последнего символа должны быть нулевыми
+        let unused = [0, 0b11, 0b1111][padding];
+        if last & unused != 0 {
+            return None;
+        }
+        // для сигнатур хватает первых 12 символов (9 байт)
+        let head = &data[..data.len().min(12)];
+        Some(Base64 {
+            media_type: MediaType::sniff(&decode(head)),
+        })
+    }
+
+    pub fn merge(self, other: Base64) -> Base64 {
+        Base64 {
+            media_type: self
+                .media_type
+                .filter(|_| self.media_type == other.media_type),
+        }
+    }
+
+    pub fn insert_keywords(&self, m: &mut Map<String, Value>) {
+        m.insert(
+            "contentEncoding".to_string(),
+            Value::String("base64".to_string()),
+        );
+        if let Some(t) = self.media_type {
+            m.insert(
+                "contentMediaType".to_string(),
+                Value::String(t.as_str().to_string()),
+            );
+        }
+    }
+}
+
+/// Every string of a node spelled a JSON number; `Integer` while none of them
+/// had a fraction or an exponent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NumericString {
+    Integer,
+    Number,
+}
+
+impl NumericString {
+    /// None unless `s` is a number in JSON syntax: no sign but `-`, no leading
+    /// zeros (`"007"`), no surrounding spaces, not empty.
+    pub fn detect(s: &str) -> Option<NumericString> {
+        let b = s.strip_prefix('-').unwrap_or(s).as_bytes();
+        let int_len = b.iter().take_while(|c| c.is_ascii_digit()).count();
+        if int_len == 0 || (int_len > 1 && b[0] == b'0') {
+            return None;
+        }
+        let mut rest = &b[int_len..];
+        let mut kind = NumericString::Integer;
+        if let Some(frac) = rest.strip_prefix(b".") {
+            let n = frac.iter().take_while(|c| c.is_ascii_digit()).count();
+            if n == 0 {
+                return None;
+            }
+            rest = &frac[n..];
+            kind = NumericString::Number;
+        }
+        if let Some(exp) = rest.strip_prefix(b"e").or_else(|| rest.strip_prefix(b"E")) {
+            let exp = exp
+                .strip_prefix(b"+")
+                .or_else(|| exp.strip_prefix(b"-"))
+                .unwrap_or(exp);
+            let n = exp.iter().take_while(|c| c.is_ascii_digit()).count();
+            if n == 0 {
+                return None;
+            }
+            rest = &exp[n..];
+            kind = NumericString::Number;
+        }
+        rest.is_empty().then_some(kind)
+    }
+
+    pub fn merge(self, other: NumericString) -> NumericString {
+        if self == other {
+            self
+        } else {
+            NumericString::Number
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NumericString::Integer => "integer",
+            NumericString::Number => "number",
+        }
+    }
+
+    /// Pattern accepting exactly the strings `detect` classifies as `self`.
+    pub fn pattern(self) -> &'static str {
+        match self {
+            NumericString::Integer => r"^-?(0|[1-9][0-9]*)$",
+            NumericString::Number => r"^-?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?$",
+        }
+    }
+}
+
+/// Every string of a node was a truth word (`"true"`, `"no"`, ...) of the
+/// configured vocabulary, compared case-insensitively; `spellings` keeps the
+/// strings as they were written.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BooleanString {
+    pub true_count: u64,
+    pub false_count: u64,
+    pub spellings: BTreeSet<String>,
+}
+
+impl BooleanString {
+    /// None unless `s` is one of `true_words` or `false_words`.
+    pub fn detect(s: &str, true_words: &[String], false_words: &[String]) -> Option<BooleanString> {
+        let mut b = BooleanString {
+            true_count: 0,
+            false_count: 0,
+            spellings: BTreeSet::new(),
+        };
+        b.observe(s, true_words, false_words).then_some(b)
+    }
+
+    /// Counts `s`; false when it is outside the vocabulary.
+    pub fn observe(&mut self, s: &str, true_words: &[String], false_words: &[String]) -> bool {
+        let is = |words: &[String]| words.iter().any(|w| w.eq_ignore_ascii_case(s));
+        if is(true_words) {
+            self.true_count += 1;
+        } else if is(false_words) {
+            self.false_count += 1;
+        } else {
+            return false;
+        }
+        if !self.spellings.contains(s) {
+            self.spellings.insert(s.to_string());
+        }
+        true
+    }
+
+    pub fn merge(&mut self, other: BooleanString) {
+        self.true_count += other.true_count;
+        self.false_count += other.false_count;
+        self.spellings.extend(other.spellings);
+    }
+
+    /// Both truth values were seen, or a single one at least `min_observations`
+    /// times; one rarely seen word is more likely part of an arbitrary enum.
+    pub fn is_trusted(&self, min_observations: u64) -> bool {
+        (self.true_count > 0 && self.false_count > 0)
+            || self.true_count.max(self.false_count) >= min_observations
+    }
+
+    pub fn insert_keywords(&self, m: &mut Map<String, Value>) {
+        m.insert(
+            "x-aif-suggested-type".to_string(),
+            Value::String("boolean".to_string()),
+        );
+        m.insert(
+            "enum".to_string(),
+            self.spellings.iter().cloned().map(Value::String).collect(),
+        );
+    }
+}
+
+fn sextet(b: u8) -> Option<u8> {
+    match b {
+        b'A'..=b'Z' => Some(b - b'A'),
+        b'a'..=b'z' => Some(b - b'a' + 26),
+        b'0'..=b'9' => Some(b - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+// Входные символы уже проверены, хвост неполной группы отбрасывается
+fn decode(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() * 3 / 4);
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    for &b in data {
+        acc = (acc << 6) | u32::from(sextet(b).unwrap_or(0));
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_base64_and_magic_numbers() {
+        let media = |s: &str| Base64::detect(s, 16).map(|b| b.media_type);
+        assert_eq!(
+            media("iVBORw0KGgoAAAANSUhEUg=="),
+            Some(Some(MediaType::Png))
+        );
+        assert_eq!(media("/9j/4AAQSkZJRg=="), Some(Some(MediaType::Jpeg)));
+        assert_eq!(
+            media("H4sIALGDz2oC/8tIzcnJVyjPL8pJAQCFEUoNCwAAAA=="),
+            Some(Some(MediaType::Gzip))
+        );
+        assert_eq!(media("JVBERi0xLjQKJWFiYw=="), Some(Some(MediaType::Pdf)));
+        assert_eq!(media("cGxhaW4gdGV4dCBwYXlsb2FkIQ=="), Some(None));
+    }
+
+    #[test]
+    fn boolean_strings() {
+        let words = |w: &[&str]| w.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let (yes, no) = (words(&["true", "yes"]), words(&["false", "no"]));
+        let mut b = BooleanString::detect("TRUE", &yes, &no).unwrap();
+        assert!(!b.is_trusted(3));
+        assert!(b.observe("Yes", &yes, &no));
+        assert!(b.observe("TRUE", &yes, &no));
+        assert!(b.is_trusted(3));
+        assert!(!b.observe("maybe", &yes, &no));
+        assert!(b.observe("no", &yes, &no));
+        assert_eq!((b.true_count, b.false_count), (3, 1));
+        assert!(b.is_trusted(100));
+        assert_eq!(
+            b.spellings,
+            BTreeSet::from(["TRUE".into(), "Yes".into(), "no".into()])
+        );
+        assert_eq!(BooleanString::detect("1", &yes, &no), None);
+        assert!(BooleanString::detect("1", &words(&["1"]), &words(&["0"])).is_some());
+    }
+
+    #[test]
+    fn numeric_strings() {
+        let kind = NumericString::detect;
+        assert_eq!(kind("42"), Some(NumericString::Integer));
+        assert_eq!(kind("-0"), Some(NumericString::Integer));
+        assert_eq!(kind("3.14"), Some(NumericString::Number));
+        assert_eq!(kind("1e-5"), Some(NumericString::Number));
+        for s in [
+            "", "007", "+1", " 1", "1.", ".5", "1e", "-", "0x1f", "1_000",
+        ] {
+            assert_eq!(kind(s), None, "{s:?}");
+        }
+        assert_eq!(
+            NumericString::Integer.merge(NumericString::Number),
+            NumericString::Number
+        );
+    }
+
+    #[test]
+    fn rejects_short_or_malformed_values() {
+        assert!(Base64::detect("abcd", 16).is_none());
+        assert!(Base64::detect("abcd", 4).is_some());
+        // неверная длина, лишнее выравнивание, чужой алфавит, ненулевые хвостовые биты
+        assert!(Base64::detect("cGxhaW4gdGV4dCBwYXlsb2FkIQ=", 4).is_none());
+        assert!(Base64::detect("cGxhaW4gdGV4d===", 4).is_none());
+        assert!(Base64::detect("cGxhaW4-dGV4dCBw", 4).is_none());
+        assert!(Base64::detect("cGxhaW4gdGV4dCBwYXlsb2FkIR==", 4).is_none());
+        assert!(Base64::detect("", 0).is_none());
+    }
+}
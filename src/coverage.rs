@@ -0,0 +1,123 @@
+use ahash::AHashSet;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::diff::parse_schema;
+use crate::visitor::{items_path, property_path};
+use crate::{collect_paths_with_options, CollectPathsOptions, InferError};
+
+/// Which dot paths of a schema (`a.b`, `tags[]`) a sample set exercised.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SchemaCoverage {
+    pub covered: Vec<String>,
+    pub uncovered: Vec<String>,
+    /// `covered` as a fraction of all paths; 1.0 for a schema without paths.
+    pub coverage: f64,
+}
+
+/// Fraction of the property and items paths of `schema` that hold a non-null
+/// value in at least one of `samples`.
+pub fn schema_coverage_rs<S: AsRef<str>>(schema: &str, samples: &[S]) -> Result<f64, InferError> {
+    Ok(schema_coverage_report_rs(schema, samples)?.coverage)
+}
+
+/// `schema_coverage_rs` with the covered and uncovered paths, sorted.
+pub fn schema_coverage_report_rs<S: AsRef<str>>(
+    schema: &str,
+    samples: &[S],
+) -> Result<SchemaCoverage, InferError> {
+    let schema = parse_schema(schema, "schema")?;
+    let paths = collect_paths_with_options(&schema, &CollectPathsOptions::default());
+    let mut exercised = AHashSet::default();
+    for (index, s) in samples.iter().enumerate() {
+        let sample: Value = serde_json::from_str(s.as_ref())
+            .map_err(|source| InferError::InvalidJson { index, source })?;
+        collect_present(&sample, "", &mut exercised);
+    }
+    let mut report = SchemaCoverage::default();
+    for path in paths {
+        if exercised.contains(&path) {
+            report.covered.push(path);
+        } else {
+            report.uncovered.push(path);
+        }
+    }
+    report.covered.sort();
+    report.uncovered.sort();
+    let total = report.covered.len() + report.uncovered.len();
+    report.coverage = if total == 0 {
+        1.0
+    } else {
+        report.covered.len() as f64 / total as f64
+    };
+    Ok(report)
+}
+
+/// `schema_coverage_report_rs` rendered as pretty-printed JSON.
+pub fn schema_coverage_json_rs<S: AsRef<str>>(
+    schema: &str,
+    samples: &[S],
+) -> Result<String, InferError> {
+    serde_json::to_string_pretty(&schema_coverage_report_rs(schema, samples)?)
+        .map_err(InferError::SerializeError)
+}
+
+// Пути непустых значений образца в записи collect_paths_with_options
+fn collect_present(v: &Value, prefix: &str, acc: &mut AHashSet<String>) {
+    match v {
+        Value::Object(m) => {
+            for (k, child) in m.iter().filter(|(_, child)| !child.is_null()) {
+                let path = property_path(prefix, k);
+                collect_present(child, &path, acc);
+                acc.insert(path);
+            }
+        }
+        Value::Array(items) => {
+            let path = items_path(prefix);
+            for item in items.iter().filter(|item| !item.is_null()) {
+                collect_present(item, &path, acc);
+                acc.insert(path.clone());
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{infer_schema_from_strs, InferOptions};
+
+    #[test]
+    fn fixtures_cover_part_of_the_schema() {
+        let schema = infer_schema_from_strs(
+            &[
+                r#"{"id":1,"name":"a","tags":["x"],"address":{"city":"c","zip":"1"}}"#,
+                r#"{"id":2,"name":null,"tags":[],"address":{"city":"d"}}"#,
+            ],
+            InferOptions::default(),
+        )
+        .unwrap();
+        let fixtures = [
+            r#"{"id":3,"name":null,"tags":[null],"address":{"city":"e"}}"#.to_string(),
+            r#"{"id":4,"extra":true}"#.to_string(),
+        ];
+        let report = schema_coverage_report_rs(&schema, &fixtures).unwrap();
+        assert_eq!(report.covered, ["address", "address.city", "id", "tags"]);
+        assert_eq!(report.uncovered, ["address.zip", "name", "tags[]"]);
+        assert_eq!(report.coverage, 4.0 / 7.0);
+        assert_eq!(schema_coverage_rs(&schema, &fixtures).unwrap(), 4.0 / 7.0);
+
+        let json: Value =
+            serde_json::from_str(&schema_coverage_json_rs(&schema, &fixtures).unwrap()).unwrap();
+        assert_eq!(json["uncovered"][0], "address.zip");
+
+        assert_eq!(schema_coverage_rs::<String>(&schema, &[]).unwrap(), 0.0);
+        assert_eq!(
+            schema_coverage_rs(r#"{"type":"string"}"#, &["1"]).unwrap(),
+            1.0
+        );
+        let err = schema_coverage_rs(&schema, &["{}", "{"]).unwrap_err();
+        assert!(matches!(err, InferError::InvalidJson { index: 1, .. }));
+    }
+}
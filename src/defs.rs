@@ -0,0 +1,308 @@
+use std::collections::BTreeMap;
+
+use serde_json::{json, Map, Value};
+
+use crate::extensions::strip_keywords;
+use crate::fingerprint::canonicalize;
+
+// Значения этих ключевых слов — данные, а не подсхемы
+pub(crate) const DATA_KEYWORDS: [&str; 4] = ["const", "enum", "default", "examples"];
+
+struct Occurrence {
+    pointer: String,
+    // имя свойства, под которым встретилась подсхема
+    key: Option<String>,
+}
+
+/// Hoists object sub-schemas with at least `min_properties` properties that
+/// occur at two or more paths into `keyword` (`$defs` or draft-07
+/// `definitions`), leaving a `$ref` at each place. Larger shapes go first, so
+/// a repeated shape nested in another one ends up as a `$ref` inside its
+/// definition.
+pub(crate) fn extract_defs(doc: &mut Map<String, Value>, min_properties: usize, keyword: &str) {
+    let mut defs = match doc.remove(keyword) {
+        Some(Value::Object(defs)) => defs,
+        _ => Map::new(),
+    };
+    loop {
+        let mut shapes: BTreeMap<String, Vec<Occurrence>> = BTreeMap::new();
+        collect_children(doc, "", None, min_properties, &mut shapes);
+        for (name, def) in &defs {
+            if let Value::Object(def) = def {
+                let pointer = format!("/{keyword}/{}", escape(name));
+                collect_children(def, &pointer, None, min_properties, &mut shapes);
+            }
+        }
+        // самая крупная повторяющаяся форма, при равенстве — по канонической строке
+        let Some((_, occurrences)) = shapes
+            .into_iter()
+            .filter(|(_, found)| found.len() >= 2)
+            .max_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then_with(|| b.cmp(a)))
+        else {
+            break;
+        };
+
+        let name = unique_name(&def_name(&occurrences), &defs);
+        let reference = json!({ "$ref": format!("#/{keyword}/{}", escape(&name)) });
+        let mut bodies = Vec::new();
+        for occurrence in &occurrences {
+            let pointer = occurrence.pointer.strip_prefix('/').unwrap_or_default();
+            if let Some(slot) = lookup(doc, &mut defs, keyword, pointer) {
+                bodies.push(std::mem::replace(slot, reference.clone()));
+            }
+        }
+        if let Some(mut body) = bodies.pop() {
+            // заголовок одного из полей определению не подходит, если у других он другой
+            for key in ["title", "description"] {
+                if bodies.iter().any(|other| other.get(key) != body.get(key)) {
+                    if let Value::Object(m) = &mut body {
+                        m.remove(key);
+                    }
+                }
+            }
+            defs.insert(name, body);
+        }
+    }
+    if !defs.is_empty() {
+        defs.sort_keys();
+        doc.insert(keyword.to_string(), Value::Object(defs));
+    }
+}
+
+fn collect(
+    v: &Value,
+    pointer: &str,
+    key: Option<&str>,
+    min_properties: usize,
+    shapes: &mut BTreeMap<String, Vec<Occurrence>>,
+) {
+    match v {
+        Value::Object(m) => {
+            let props = m.get("properties").and_then(Value::as_object);
+            if props.is_some_and(|p| p.len() >= min_properties) {
+                // форму задают ограничения: заголовки по именам полей и x-aif- не мешают совпадению
+                let mut canonical = v.clone();
+                strip_keywords(&mut canonical, is_annotation);
+                canonicalize(&mut canonical);
+                shapes
+                    .entry(canonical.to_string())
+                    .or_default()
+                    .push(Occurrence {
+                        pointer: pointer.to_string(),
+                        key: key.map(String::from),
+                    });
+            }
+            collect_children(m, pointer, key, min_properties, shapes);
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                collect(item, &format!("{pointer}/{i}"), key, min_properties, shapes);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn is_annotation(keyword: &str) -> bool {
+    matches!(keyword, "title" | "description") || keyword.starts_with("x-aif-")
+}
+
+fn collect_children(
+    m: &Map<String, Value>,
+    pointer: &str,
+    key: Option<&str>,
+    min_properties: usize,
+    shapes: &mut BTreeMap<String, Vec<Occurrence>>,
+) {
+    for (k, child) in m {
+        if DATA_KEYWORDS.contains(&k.as_str()) {
+            continue;
+        }
+        let child_pointer = format!("{pointer}/{}", escape(k));
+        match (k.as_str(), child) {
+            ("properties", Value::Object(props)) => {
+                for (name, sub) in props {
+                    let sub_pointer = format!("{child_pointer}/{}", escape(name));
+                    collect(sub, &sub_pointer, Some(name), min_properties, shapes);
+                }
+            }
+            _ => collect(child, &child_pointer, key, min_properties, shapes),
+        }
+    }
+}
+
+fn lookup<'a>(
+    doc: &'a mut Map<String, Value>,
+    defs: &'a mut Map<String, Value>,
+    keyword: &str,
+    pointer: &str,
+) -> Option<&'a mut Value> {
+    let (head, tail) = pointer.split_once('/').unwrap_or((pointer, ""));
+    // определения хранятся отдельно от документа, следующий сегмент — имя
+    let (container, head, tail) = if head == keyword {
+        let (name, tail) = tail.split_once('/').unwrap_or((tail, ""));
+        (defs, name, tail)
+    } else {
+        (doc, head, tail)
+    };
+    let value = container.get_mut(&unescape(head))?;
+    if tail.is_empty() {
+        Some(value)
+    } else {
+        value.pointer_mut(&format!("/{tail}"))
+    }
+}
+
+// Самый частый ключ среди мест вхождения; при равенстве — меньший по алфавиту
+fn def_name(occurrences: &[Occurrence]) -> String {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for key in occurrences.iter().filter_map(|o| o.key.as_deref()) {
+        *counts.entry(key).or_default() += 1;
+    }
+    let key = counts
+        .iter()
+        .max_by(|(a, x), (b, y)| x.cmp(y).then_with(|| b.cmp(a)))
+        .map_or("def", |(key, _)| key);
+    sanitize_name(key)
+}
+
+// Ключ свойства как имя определения: всё, кроме [A-Za-z0-9_-], заменяется на _
+pub(crate) fn sanitize_name(key: &str) -> String {
+    let name: String = key
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.is_empty() {
+        "def".to_string()
+    } else {
+        name
+    }
+}
+
+pub(crate) fn unique_name(base: &str, defs: &Map<String, Value>) -> String {
+    if !defs.contains_key(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{base}_{n}"))
+        .find(|name| !defs.contains_key(name))
+        .unwrap_or_default()
+}
+
+pub(crate) fn escape(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+fn unescape(segment: &str) -> String {
+    segment.replace("~1", "/").replace("~0", "~")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::infer_with;
+    use crate::InferOptions;
+
+    #[test]
+    fn repeated_objects_move_to_defs() {
+        let samples = [
+            r#"{"billing":{"city":"a","zip":"1"},"shipping":{"city":"b","zip":"2"},
+                "history":[{"city":"c","zip":"3"}],"geo":{"lat":1.5,"lon":2.5}}"#,
+        ];
+        let opts = InferOptions {
+            extract_defs: true,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts.clone());
+        let reference = json!({"$ref": "#/$defs/billing"});
+        assert_eq!(v["properties"]["billing"], reference);
+        assert_eq!(v["properties"]["shipping"], reference);
+        assert_eq!(v["properties"]["history"]["items"], reference);
+        assert_eq!(v["$defs"]["billing"]["required"], json!(["city", "zip"]));
+        // форма встречается один раз — остаётся на месте
+        assert_eq!(v["properties"]["geo"]["type"], "object");
+        assert_eq!(v["$defs"].as_object().unwrap().len(), 1);
+
+        assert!(infer_with(&samples, InferOptions::default())
+            .get("$defs")
+            .is_none());
+        let strict = InferOptions {
+            defs_min_properties: 3,
+            ..opts
+        };
+        assert!(infer_with(&samples, strict).get("$defs").is_none());
+    }
+
+    #[test]
+    fn titles_do_not_keep_shapes_apart() {
+        let samples = [r#"{"billing":{"city":"a","zip":"1"},"shipping":{"city":"b","zip":"2"}}"#];
+        let opts = InferOptions {
+            extract_defs: true,
+            generate_titles: true,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts);
+        let reference = json!({"$ref": "#/$defs/billing"});
+        assert_eq!(v["properties"]["billing"], reference);
+        assert_eq!(v["properties"]["shipping"], reference);
+        let def = &v["$defs"]["billing"];
+        assert!(def.get("title").is_none());
+        assert_eq!(def["properties"]["city"]["title"], "City");
+    }
+
+    #[test]
+    fn two_properties_sharing_a_point_shape() {
+        let samples = [r#"{"start":{"x":1,"y":2},"end":{"x":3,"y":4},"label":"a"}"#];
+        let opts = InferOptions {
+            extract_defs: true,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts);
+        let reference = json!({"$ref": "#/$defs/end"});
+        assert_eq!(v["properties"]["start"], reference);
+        assert_eq!(v["properties"]["end"], reference);
+        assert_eq!(
+            v["$defs"]["end"]["properties"],
+            json!({"x": {"type": "integer"}, "y": {"type": "integer"}})
+        );
+        assert_eq!(v["properties"]["label"], json!({"type": "string"}));
+    }
+
+    #[test]
+    fn nested_shapes_and_name_collisions() {
+        let pq = json!({"properties": {"p": {}, "q": {}}});
+        let rs = json!({"properties": {"r": {}, "s": {}}});
+        let outer = json!({"properties": {"inner": pq, "t": {}}});
+        let mut doc = json!({
+            "type": "object",
+            "properties": {
+                "o1": outer, "o2": outer, "u": pq,
+                "x": {"properties": {"a": pq, "m": {}}},
+                "y": {"properties": {"a": pq, "n": {}}},
+                "z": {"properties": {"a": rs, "m": {}}},
+                "w": {"properties": {"a": rs, "n": {}}},
+                "k": {"enum": [pq]}
+            }
+        });
+        extract_defs(doc.as_object_mut().unwrap(), 1, "$defs");
+        let reference = |name: &str| json!({"$ref": format!("#/$defs/{name}")});
+        // сначала крупная форма o1/o2, затем pq уже и внутри её определения
+        assert_eq!(doc["properties"]["o2"], reference("o1"));
+        assert_eq!(doc["$defs"]["o1"]["properties"]["inner"], reference("a"));
+        assert_eq!(doc["properties"]["u"], reference("a"));
+        assert_eq!(doc["properties"]["y"]["properties"]["a"], reference("a"));
+        // та же подсказка a для другой формы получает суффикс
+        assert_eq!(doc["properties"]["w"]["properties"]["a"], reference("a_2"));
+        assert_eq!(doc["$defs"]["a_2"], rs);
+        let names: Vec<&String> = doc["$defs"].as_object().unwrap().keys().collect();
+        assert_eq!(names, ["a", "a_2", "o1"]);
+        // значения enum не трогаем
+        assert_eq!(doc["properties"]["k"]["enum"][0], pq);
+    }
+}
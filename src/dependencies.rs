@@ -0,0 +1,163 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// For each pair of keys of an object node, how many observed objects held
+/// both of them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct CoOccurrence {
+    pairs: BTreeMap<String, BTreeMap<String, u64>>,
+}
+
+impl CoOccurrence {
+    pub(crate) fn observe<'a>(&mut self, keys: impl Iterator<Item = &'a String> + Clone) {
+        for a in keys.clone() {
+            let partners = match self.pairs.get_mut(a) {
+                Some(partners) => partners,
+                None => self.pairs.entry(a.clone()).or_default(),
+            };
+            for b in keys.clone().filter(|b| *b != a) {
+                match partners.get_mut(b) {
+                    Some(count) => *count += 1,
+                    None => {
+                        partners.insert(b.clone(), 1);
+                    }
+                }
+            }
+        }
+    }
+
+    pub(crate) fn merge(&mut self, other: CoOccurrence) {
+        for (a, partners) in other.pairs {
+            let mine = self.pairs.entry(a).or_default();
+            for (b, count) in partners {
+                *mine.entry(b).or_default() += count;
+            }
+        }
+    }
+
+    /// Records `key` as always appearing together with `dependents`, as if
+    /// they had been seen together `count` times.
+    pub(crate) fn insert(&mut self, key: &str, dependents: &[&str], count: u64) {
+        let partners = self.pairs.entry(key.to_string()).or_default();
+        for b in dependents.iter().filter(|b| **b != key) {
+            partners.insert(b.to_string(), count);
+        }
+    }
+
+    /// Keys present in at least `min_support` but not all of the `total`
+    /// objects, mapped to the optional keys that accompanied every one of
+    /// their occurrences. `seen` is how many objects held a key.
+    pub(crate) fn dependent_required(
+        &self,
+        seen: impl Fn(&str) -> u64,
+        total: u64,
+        min_support: u64,
+    ) -> BTreeMap<String, Vec<String>> {
+        let mut out = BTreeMap::new();
+        for (a, partners) in &self.pairs {
+            let support = seen(a);
+            // обязательный ключ уже описан в required
+            if support < min_support.max(1) || support >= total {
+                continue;
+            }
+            let dependents: Vec<String> = partners
+                .iter()
+                .filter(|(b, count)| **count == support && seen(b) < total)
+                .map(|(b, _)| b.clone())
+                .collect();
+            if !dependents.is_empty() {
+                out.insert(a.clone(), dependents);
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::infer_with;
+    use crate::{merge_schemas_rs, InferOptions, SchemaDraft};
+    use serde_json::{json, Value};
+
+    fn payments() -> Vec<String> {
+        (0..12)
+            .map(|i| match i % 3 {
+                0 => format!(r#"{{"id":{i},"card_number":"4{i}","card_expiry":"12/3{i}"}}"#),
+                1 => format!(r#"{{"id":{i},"card_expiry":"01/2{i}","iban":"DE{i}"}}"#),
+                _ => format!(r#"{{"id":{i},"iban":"DE{i}","bic":"B{i}","note":"n{i}"}}"#),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn dependencies_that_always_held() {
+        let opts = InferOptions {
+            detect_dependent_required: true,
+            ..Default::default()
+        };
+        let v = infer_with(&payments(), opts.clone());
+        assert_eq!(
+            v["dependentRequired"],
+            json!({
+                "bic": ["iban", "note"],
+                "card_number": ["card_expiry"],
+                "note": ["bic", "iban"]
+            })
+        );
+        assert_eq!(v["required"], json!(["id"]));
+
+        assert!(infer_with(&payments(), InferOptions::default())
+            .get("dependentRequired")
+            .is_none());
+        // четыре вхождения card_number — меньше порога
+        let strict = InferOptions {
+            dependent_required_min_support: 5,
+            ..opts.clone()
+        };
+        assert!(infer_with(&payments(), strict)
+            .get("dependentRequired")
+            .is_none());
+        // шесть разных ключей больше лимита — узел не отслеживается
+        let bounded = InferOptions {
+            dependent_required_max_properties: 5,
+            ..opts.clone()
+        };
+        assert!(infer_with(&payments(), bounded)
+            .get("dependentRequired")
+            .is_none());
+
+        let draft07 = InferOptions {
+            draft: SchemaDraft::Draft07,
+            ..opts
+        };
+        let v = infer_with(&payments(), draft07);
+        assert_eq!(v["dependencies"]["card_number"], json!(["card_expiry"]));
+        assert!(v.get("dependentRequired").is_none());
+
+        // зависимость переживает восстановление из схемы
+        let schema = serde_json::to_string(&v).unwrap();
+        let merged: Value =
+            serde_json::from_str(&merge_schemas_rs(&schema, &schema).unwrap()).unwrap();
+        assert_eq!(
+            merged["dependentRequired"]["card_number"],
+            json!(["card_expiry"])
+        );
+    }
+
+    #[test]
+    fn merge_adds_pair_counts() {
+        let keys = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        let mut a = CoOccurrence::default();
+        a.observe(keys(&["x", "y"]).iter());
+        let mut b = CoOccurrence::default();
+        b.observe(keys(&["x", "y"]).iter());
+        b.observe(keys(&["y"]).iter());
+        a.merge(b);
+        let seen = |k: &str| if k == "x" { 2 } else { 3 };
+        let deps = a.dependent_required(seen, 4, 1);
+        assert_eq!(deps.len(), 1);
+        assert_eq!(deps["x"], ["y"]);
+    }
+}
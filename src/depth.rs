@@ -0,0 +1,180 @@
+use crate::InferError;
+
+enum Level<'a> {
+    Array,
+    // последний прочитанный ключ и ждём ли следующий
+    Object {
+        key: Option<&'a str>,
+        expect_key: bool,
+    },
+}
+
+/// Fails with `DepthLimitExceeded` when `text` opens more than `limit` nested
+/// objects and arrays. The scan neither recurses nor builds values, so it is
+/// safe on inputs too deep for the JSON parser; malformed input is left for the
+/// parser to report.
+pub(crate) fn check_depth(text: &str, limit: usize) -> Result<(), InferError> {
+    let bytes = text.as_bytes();
+    let mut levels: Vec<Level> = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                let start = i + 1;
+                i = start;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                if let Some(Level::Object { key, expect_key }) = levels.last_mut() {
+                    if *expect_key {
+                        *key = text.get(start..i);
+                        *expect_key = false;
+                    }
+                }
+            }
+            open @ (b'{' | b'[') => {
+                if levels.len() >= limit {
+                    return Err(InferError::DepthLimitExceeded {
+                        path: dot_path(&levels),
+                    });
+                }
+                levels.push(if open == b'{' {
+                    Level::Object {
+                        key: None,
+                        expect_key: true,
+                    }
+                } else {
+                    Level::Array
+                });
+            }
+            b'}' | b']' => {
+                levels.pop();
+            }
+            b',' => {
+                if let Some(Level::Object { expect_key, .. }) = levels.last_mut() {
+                    *expect_key = true;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    Ok(())
+}
+
+// Путь в соглашении get_at_path: rows[].id
+fn dot_path(levels: &[Level]) -> String {
+    let mut path = String::new();
+    for level in levels {
+        match level {
+            Level::Array => path.push_str("[]"),
+            Level::Object { key: Some(key), .. } => {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+            }
+            Level::Object { key: None, .. } => {}
+        }
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{infer_schema_rs, InferOptions, Node};
+    use serde_json::{json, Map, Value};
+
+    fn depth_error(text: &str, limit: usize) -> Option<String> {
+        match check_depth(text, limit) {
+            Err(InferError::DepthLimitExceeded { path }) => Some(path),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn depth_scan_reports_the_path() {
+        let doc = r#"{"a":1,"rows":[{"id":{"x":[]}}],"s":"{[{["}"#;
+        assert_eq!(depth_error(doc, 5), None);
+        assert_eq!(depth_error(doc, 4).as_deref(), Some("rows[].id.x"));
+        assert_eq!(depth_error(doc, 2).as_deref(), Some("rows[]"));
+        assert_eq!(
+            depth_error(r#"{"k\"{":{"v":{}}}"#, 2).as_deref(),
+            Some("k\\\"{.v")
+        );
+        assert_eq!(depth_error("[[1]]", 1).as_deref(), Some("[]"));
+        assert_eq!(depth_error("1", 0), None);
+    }
+
+    #[test]
+    fn five_thousand_levels_of_nesting() {
+        let levels = 5000;
+        let deep = format!("{}1{}", r#"{"a":"#.repeat(levels), "}".repeat(levels));
+
+        // без лимита глубину ограничивает разборщик JSON — ошибка, а не переполнение стека
+        match infer_schema_rs(&[deep.as_str()], InferOptions::default()) {
+            Ok(_) | Err(InferError::InvalidJson { .. }) => {}
+            Err(other) => panic!("unexpected error: {other}"),
+        }
+
+        let opts = InferOptions {
+            depth_limit: Some(64),
+            ..Default::default()
+        };
+        match infer_schema_rs(&[deep.as_str()], opts) {
+            Err(InferError::DepthLimitExceeded { path }) => {
+                assert_eq!(path, vec!["a"; 64].join("."));
+            }
+            other => panic!("expected a depth error, got {other:?}"),
+        }
+
+        // сам вывод не рекурсивен: значение, собранное в обход разборщика
+        let mut v = json!(1);
+        for i in 0..levels {
+            v = if i % 2 == 0 {
+                Value::Object(Map::from_iter([("a".to_string(), v)]))
+            } else {
+                Value::Array(vec![v])
+            };
+        }
+        let mut node = Node::default();
+        let opts = InferOptions::default();
+        node.observe(&v, &opts);
+        let rendered = node.to_json_schema(&opts);
+        let mut schema = &rendered;
+        let mut depth = 0;
+        while let Some(next) = schema
+            .pointer("/properties/a")
+            .or_else(|| schema.get("items"))
+        {
+            schema = next;
+            depth += 1;
+        }
+        assert_eq!(depth, levels);
+        assert_eq!(schema, &json!({"type": "integer"}));
+        dismantle_values(vec![v, rendered]);
+        dismantle_node(node);
+    }
+
+    // Сгенерированная drop-логика рекурсивна — разбираем глубокие структуры вручную
+    fn dismantle_values(mut stack: Vec<Value>) {
+        while let Some(v) = stack.pop() {
+            match v {
+                Value::Array(items) => stack.extend(items),
+                Value::Object(m) => stack.extend(m.into_iter().map(|(_, v)| v)),
+                _ => {}
+            }
+        }
+    }
+
+    fn dismantle_node(node: Node) {
+        let mut stack = vec![node];
+        while let Some(mut n) = stack.pop() {
+            stack.extend(n.properties.drain(..).map(|(_, child)| child));
+            stack.extend(n.items.take().map(|items| *items));
+            stack.extend(n.map_values.take().map(|values| *values));
+            stack.extend(n.tuple.take().into_iter().flatten());
+        }
+    }
+}
@@ -0,0 +1,713 @@
+use ahash::{AHashMap, AHashSet};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::extensions::is_extension;
+use crate::visitor::{items_path, property_path};
+use crate::{InferError, TypeTag};
+
+/// Path-level difference between two schemas. Paths use dot notation with
+/// `[]` for array items (`a.b`, `tags[]`); every list is sorted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SchemaDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub common: Vec<String>,
+    /// Paths present on both sides whose type differs; they are not in `common`.
+    pub changed: Vec<PathChange>,
+    pub modified: Vec<KeywordChange>,
+    /// Schema B with the sub-schemas of `removed` paths copied back from
+    /// schema A and marked `"deprecated": true`; only with
+    /// `DiffOptions::mark_removed_as_deprecated`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated_annotated_schema: Option<String>,
+}
+
+/// A path whose `type` differs between the two schemas.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PathChange {
+    pub path: String,
+    pub old_type: Value,
+    pub new_type: Value,
+}
+
+/// An annotation keyword (e.g. `format`) that differs on a common path.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeywordChange {
+    pub path: String,
+    pub keyword: String,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+// Аннотации, изменение которых на общем пути попадает в "modified"
+const MODIFIED_KEYWORDS: &[&str] = &["format", "x-aif-semantic"];
+
+// Ключевые слова, которыми помечается объект, всегда бывший {}
+const EMPTY_OBJECT_KEYWORDS: [&str; 2] = ["maxProperties", "x-aif-empty"];
+
+fn is_empty_object(schema: &Value) -> bool {
+    schema.get("maxProperties") == Some(&Value::from(0))
+        || schema.get("x-aif-empty") == Some(&Value::Bool(true))
+}
+
+/// How diff paths are spelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PathFormat {
+    /// `a.b`, `tags[]`
+    #[default]
+    Dot,
+    /// RFC 6901 pointers into the schema: `/properties/a/properties/b`,
+    /// `/properties/tags/items`
+    JsonPointer,
+}
+
+/// Knobs for `diff_schemas_rs`.
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+    pub path_format: PathFormat,
+    /// Also report changed `x-` extension keywords in `modified`; they are
+    /// ignored by default.
+    pub include_extensions: bool,
+    /// Fill `SchemaDiff::deprecated_annotated_schema`, a transitional schema
+    /// that still accepts the removed fields.
+    pub mark_removed_as_deprecated: bool,
+}
+
+/// Knobs for `collect_paths_with_options`.
+#[derive(Debug, Clone, Default)]
+pub struct CollectPathsOptions {
+    /// Deepest level collected: every property and every step into array
+    /// items is one level, so `Some(1)` keeps only the top-level properties.
+    pub max_depth: Option<usize>,
+    /// Only paths starting with this string, spelled in `path_format`.
+    pub prefix_filter: Option<String>,
+    pub path_format: PathFormat,
+}
+
+fn collect_paths<'a>(schema: &'a Value, format: PathFormat, acc: &mut AHashMap<String, &'a Value>) {
+    let options = CollectPathsOptions {
+        path_format: format,
+        ..Default::default()
+    };
+    walk_paths(schema, schema, "", 1, &options, &mut Vec::new(), acc);
+}
+
+fn walk_paths<'a>(
+    root: &'a Value,
+    schema: &'a Value,
+    prefix: &str,
+    depth: usize,
+    options: &CollectPathsOptions,
+    refs: &mut Vec<&'a str>,
+    acc: &mut AHashMap<String, &'a Value>,
+) {
+    if options.max_depth.is_some_and(|max| depth > max) {
+        return;
+    }
+    let Some(obj) = schema.as_object() else {
+        return;
+    };
+    // ветви anyOf / oneOf и цель локального $ref описывают то же место: их пути сливаются
+    for key in ["anyOf", "oneOf"] {
+        let branches = obj.get(key).and_then(Value::as_array).into_iter().flatten();
+        for (i, branch) in branches.enumerate() {
+            let next = match options.path_format {
+                PathFormat::Dot => prefix.to_string(),
+                PathFormat::JsonPointer => format!("{prefix}/{key}/{i}"),
+            };
+            walk_paths(root, branch, &next, depth, options, refs, acc);
+        }
+    }
+    if let Some(reference) = obj.get("$ref").and_then(Value::as_str) {
+        let pointer = reference.strip_prefix('#');
+        // рекурсивная схема: по одной и той же ссылке внутри неё не спускаемся
+        if let Some(target) = pointer
+            .and_then(|p| root.pointer(p))
+            .filter(|_| !refs.contains(&reference))
+        {
+            let next = match options.path_format {
+                PathFormat::Dot => prefix.to_string(),
+                PathFormat::JsonPointer => pointer.unwrap_or_default().to_string(),
+            };
+            refs.push(reference);
+            walk_paths(root, target, &next, depth, options, refs, acc);
+            refs.pop();
+        }
+    }
+    let mut children: Vec<(String, &Value)> = Vec::new();
+    if let Some(props) = obj.get("properties").and_then(|p| p.as_object()) {
+        for (k, v) in props {
+            let next = match options.path_format {
+                PathFormat::Dot => property_path(prefix, k),
+                PathFormat::JsonPointer => {
+                    // RFC 6901: сначала ~, потом /
+                    format!(
+                        "{prefix}/properties/{}",
+                        k.replace('~', "~0").replace('/', "~1")
+                    )
+                }
+            };
+            children.push((next, v));
+        }
+    }
+    if let Some(items) = obj.get("items") {
+        let next = match options.path_format {
+            PathFormat::Dot => items_path(prefix),
+            PathFormat::JsonPointer => format!("{prefix}/items"),
+        };
+        children.push((next, items));
+    }
+    for (next, v) in children {
+        // спускаемся и через предков подходящих путей: user ведёт к user.address
+        let (matches, leads_to_match) = match &options.prefix_filter {
+            Some(filter) => (next.starts_with(filter.as_str()), filter.starts_with(&next)),
+            None => (true, false),
+        };
+        if !matches && !leads_to_match {
+            continue;
+        }
+        walk_paths(root, v, &next, depth + 1, options, refs, acc);
+        if matches {
+            acc.insert(next, v);
+        }
+    }
+}
+
+/// Paths to the property and items sub-schemas of `schema`, limited by
+/// `options`. `anyOf` / `oneOf` branches and local `$ref` targets add their
+/// paths at the place they describe.
+pub fn collect_paths_with_options(
+    schema: &Value,
+    options: &CollectPathsOptions,
+) -> AHashSet<String> {
+    let mut acc = AHashMap::default();
+    walk_paths(schema, schema, "", 1, options, &mut Vec::new(), &mut acc);
+    acc.into_keys().collect()
+}
+
+/// JSON Pointers to every property and items sub-schema of `schema`, usable
+/// with `Value::pointer` on the schema itself.
+pub fn collect_json_pointers(schema: &Value) -> AHashSet<String> {
+    let options = CollectPathsOptions {
+        path_format: PathFormat::JsonPointer,
+        ..Default::default()
+    };
+    collect_paths_with_options(schema, &options)
+}
+
+// Тип по ключевому слову type, а при его отсутствии — по const/enum/anyOf.
+// Результат нормализован: строка для одного типа, отсортированный массив для нескольких.
+pub(crate) fn effective_type(schema: &Value) -> Value {
+    let mut names: Vec<String> = Vec::new();
+    match schema.get("type") {
+        Some(Value::String(t)) => names.push(t.clone()),
+        Some(Value::Array(ts)) => {
+            names.extend(ts.iter().filter_map(|t| t.as_str().map(String::from)))
+        }
+        _ => {
+            if let Some(c) = schema.get("const") {
+                names.push(TypeTag::of(c).to_string());
+            } else if let Some(Value::Array(values)) = schema.get("enum") {
+                names.extend(values.iter().map(|v| TypeTag::of(v).to_string()));
+            } else if let Some(Value::Array(branches)) = schema.get("anyOf") {
+                for b in branches {
+                    match effective_type(b) {
+                        Value::String(t) => names.push(t),
+                        Value::Array(ts) => names
+                            .extend(ts.into_iter().filter_map(|t| t.as_str().map(String::from))),
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+    names.sort();
+    names.dedup();
+    match names.len() {
+        0 => Value::Null,
+        1 => Value::String(names.remove(0)),
+        _ => Value::Array(names.into_iter().map(Value::String).collect()),
+    }
+}
+
+pub(crate) fn parse_schema(s: &str, name: &'static str) -> Result<Value, InferError> {
+    serde_json::from_str(s).map_err(|source| InferError::InvalidSchema { name, source })
+}
+
+pub fn diff_schemas_rs(a: &str, b: &str, options: DiffOptions) -> Result<SchemaDiff, InferError> {
+    let va = parse_schema(a, "schema A")?;
+    let vb = parse_schema(b, "schema B")?;
+
+    let mut ka = AHashMap::default();
+    let mut kb = AHashMap::default();
+    collect_paths(&va, options.path_format, &mut ka);
+    collect_paths(&vb, options.path_format, &mut kb);
+
+    let mut diff = SchemaDiff::default();
+    for path in kb.keys() {
+        if !ka.contains_key(path) {
+            diff.added.push(path.clone());
+        }
+    }
+    for (path, old_schema) in &ka {
+        let Some(new_schema) = kb.get(path) else {
+            diff.removed.push(path.clone());
+            continue;
+        };
+        let old_type = effective_type(old_schema);
+        let new_type = effective_type(new_schema);
+        if old_type == new_type {
+            diff.common.push(path.clone());
+        } else {
+            diff.changed.push(PathChange {
+                path: path.clone(),
+                old_type,
+                new_type,
+            });
+        }
+        let mut keywords: Vec<&str> = MODIFIED_KEYWORDS.to_vec();
+        if options.include_extensions {
+            let mut extensions: Vec<&str> = Vec::new();
+            for side in [old_schema, new_schema] {
+                let keys = side.as_object().into_iter().flat_map(|m| m.keys());
+                extensions.extend(
+                    keys.map(String::as_str)
+                        .filter(|k| is_extension(k) && !MODIFIED_KEYWORDS.contains(k)),
+                );
+            }
+            extensions.sort();
+            extensions.dedup();
+            keywords.extend(extensions);
+        }
+        // пустой объект стал объектом со свойствами (или наоборот)
+        if is_empty_object(old_schema) != is_empty_object(new_schema) {
+            for kw in EMPTY_OBJECT_KEYWORDS {
+                if !keywords.contains(&kw) {
+                    keywords.push(kw);
+                }
+            }
+        }
+        for kw in keywords {
+            let old = old_schema.get(kw);
+            let new = new_schema.get(kw);
+            if old != new {
+                diff.modified.push(KeywordChange {
+                    path: path.clone(),
+                    keyword: kw.to_string(),
+                    old: old.cloned(),
+                    new: new.cloned(),
+                });
+            }
+        }
+    }
+
+    diff.added.sort();
+    diff.removed.sort();
+    diff.common.sort();
+    diff.changed.sort_by(|x, y| x.path.cmp(&y.path));
+    diff.modified
+        .sort_by(|x, y| (&x.path, &x.keyword).cmp(&(&y.path, &y.keyword)));
+    if options.mark_removed_as_deprecated {
+        let mut transitional = vb.clone();
+        restore_deprecated(&va, &mut transitional);
+        let schema =
+            serde_json::to_string_pretty(&transitional).map_err(InferError::SerializeError)?;
+        diff.deprecated_annotated_schema = Some(schema);
+    }
+    Ok(diff)
+}
+
+// Возвращает в b недостающие в нём свойства и items из a; помечается только
+// внешний удалённый путь, вложенные в него приходят вместе с ним
+fn restore_deprecated(a: &Value, b: &mut Value) {
+    let (Some(a), Some(b)) = (a.as_object(), b.as_object_mut()) else {
+        return;
+    };
+    let deprecated = |schema: &Value| {
+        let mut schema = schema.clone();
+        if let Value::Object(m) = &mut schema {
+            m.insert("deprecated".to_string(), Value::Bool(true));
+        }
+        schema
+    };
+    if let Some(props) = a
+        .get("properties")
+        .and_then(Value::as_object)
+        .filter(|props| !props.is_empty())
+    {
+        let target = b
+            .entry("properties")
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Value::Object(target) = target {
+            for (k, old) in props {
+                match target.get_mut(k) {
+                    Some(new) => restore_deprecated(old, new),
+                    None => {
+                        target.insert(k.clone(), deprecated(old));
+                    }
+                }
+            }
+        }
+    }
+    if let Some(old) = a.get("items") {
+        match b.get_mut("items") {
+            Some(new) => restore_deprecated(old, new),
+            None => {
+                b.insert("items".to_string(), deprecated(old));
+            }
+        }
+    }
+}
+
+/// `diff_schemas_rs` rendered as pretty-printed JSON.
+pub fn diff_schemas_json_rs(a: &str, b: &str, options: DiffOptions) -> Result<String, InferError> {
+    serde_json::to_string_pretty(&diff_schemas_rs(a, b, options)?)
+        .map_err(InferError::SerializeError)
+}
+
+/// Jaccard similarity of the path sets of two schemas, `|A ∩ B| / |A ∪ B|`:
+/// 1.0 for the same structure (including two schemas without paths), 0.0
+/// when no path is shared.
+pub fn schema_similarity_rs(a: &str, b: &str) -> Result<f64, InferError> {
+    similarity(a, b, |_, _| 1.0)
+}
+
+/// `schema_similarity_rs` where a shared path counts fully only if both sides
+/// have the same type there and half otherwise; still in `[0, 1]`.
+pub fn schema_similarity_typed_rs(a: &str, b: &str) -> Result<f64, InferError> {
+    similarity(a, b, |x, y| {
+        if effective_type(x) == effective_type(y) {
+            1.0
+        } else {
+            0.5
+        }
+    })
+}
+
+// Сумма весов общих путей, делённая на размер объединения
+fn similarity(a: &str, b: &str, weight: impl Fn(&Value, &Value) -> f64) -> Result<f64, InferError> {
+    let va = parse_schema(a, "schema A")?;
+    let vb = parse_schema(b, "schema B")?;
+    let mut ka = AHashMap::default();
+    let mut kb = AHashMap::default();
+    collect_paths(&va, PathFormat::Dot, &mut ka);
+    collect_paths(&vb, PathFormat::Dot, &mut kb);
+
+    let mut shared = 0.0;
+    let mut common = 0;
+    for (path, x) in &ka {
+        if let Some(y) = kb.get(path) {
+            shared += weight(x, y);
+            common += 1;
+        }
+    }
+    let union = ka.len() + kb.len() - common;
+    if union == 0 {
+        return Ok(1.0);
+    }
+    Ok(shared / union as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn type_change_goes_to_changed_not_common() {
+        let a = r#"{"properties":{"id":{"type":"integer"},"n":{"type":["null","string"]}}}"#;
+        let b = r#"{"properties":{"id":{"type":"string"},"n":{"type":["string","null"]}}}"#;
+        let d = diff_schemas_rs(a, b, DiffOptions::default()).unwrap();
+
+        assert_eq!(d.common, vec!["n"]);
+        assert_eq!(
+            d.changed,
+            vec![PathChange {
+                path: "id".to_string(),
+                old_type: Value::from("integer"),
+                new_type: Value::from("string"),
+            }]
+        );
+    }
+
+    #[test]
+    fn similarity_of_path_sets() {
+        let a = r#"{"properties":{"id":{"type":"integer"},"name":{"type":"string"},"tags":{"type":"array","items":{"type":"string"}}}}"#;
+        let b = r#"{"properties":{"id":{"type":"string"},"name":{"type":"string"},"age":{"type":"integer"}}}"#;
+        assert_eq!(schema_similarity_rs(a, a).unwrap(), 1.0);
+        // общие id и name из пяти путей: id, name, tags, tags[], age
+        assert_eq!(schema_similarity_rs(a, b).unwrap(), 0.4);
+        // у id разные типы — он весит вдвое меньше
+        assert_eq!(schema_similarity_typed_rs(a, b).unwrap(), 0.3);
+        assert_eq!(schema_similarity_typed_rs(b, b).unwrap(), 1.0);
+        let c = r#"{"properties":{"other":{"type":"null"}}}"#;
+        assert_eq!(schema_similarity_rs(a, c).unwrap(), 0.0);
+        assert_eq!(schema_similarity_rs("{}", "true").unwrap(), 1.0);
+        assert!(schema_similarity_typed_rs(a, "{").is_err());
+    }
+
+    #[test]
+    fn json_pointer_paths() {
+        let a = r#"{"properties":{"id":{"type":"integer"},"a/b":{"type":"string"}}}"#;
+        let b = r#"{"properties":{"id":{"type":"string"},
+            "tags":{"type":"array","items":{"type":"object","properties":{"x~":{}}}}}}"#;
+        let options = DiffOptions {
+            path_format: PathFormat::JsonPointer,
+            ..Default::default()
+        };
+        let d = diff_schemas_rs(a, b, options).unwrap();
+
+        assert_eq!(d.changed[0].path, "/properties/id");
+        assert_eq!(d.removed, vec!["/properties/a~1b"]);
+        assert_eq!(
+            d.added,
+            vec![
+                "/properties/tags",
+                "/properties/tags/items",
+                "/properties/tags/items/properties/x~0",
+            ]
+        );
+
+        let schema: Value = serde_json::from_str(b).unwrap();
+        for pointer in collect_json_pointers(&schema) {
+            assert!(schema.pointer(&pointer).is_some(), "{pointer}");
+        }
+    }
+
+    #[test]
+    fn paths_inside_branches_and_local_refs() {
+        let opts = || crate::InferOptions {
+            items_any_of: true,
+            ..Default::default()
+        };
+        let a = crate::infer_schema_rs(&[r#"{"l":[1,{"k":"x"}]}"#], opts()).unwrap();
+        let b = crate::infer_schema_rs(&[r#"{"l":[1,{"z":"x"}]}"#], opts()).unwrap();
+        let d = diff_schemas_rs(&a, &b, DiffOptions::default()).unwrap();
+        assert_eq!(d.added, vec!["l[].z"]);
+        assert_eq!(d.removed, vec!["l[].k"]);
+
+        let schema: Value = serde_json::from_str(
+            r##"{"properties":{
+                "owner":{"$ref":"#/$defs/user"},
+                "children":{"items":{"$ref":"#"}},
+                "v":{"oneOf":[{"type":"string"},{"properties":{"n":{}}}]}},
+                "$defs":{"user":{"properties":{"id":{}}}}}"##,
+        )
+        .unwrap();
+        let mut paths: Vec<String> =
+            collect_paths_with_options(&schema, &CollectPathsOptions::default())
+                .into_iter()
+                .collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            [
+                "children",
+                "children[]",
+                "children[].children",
+                "children[].children[]",
+                "children[].owner",
+                "children[].owner.id",
+                "children[].v",
+                "children[].v.n",
+                "owner",
+                "owner.id",
+                "v",
+                "v.n"
+            ]
+        );
+        for pointer in collect_json_pointers(&schema) {
+            assert!(schema.pointer(&pointer).is_some(), "{pointer}");
+        }
+    }
+
+    #[test]
+    fn collect_paths_by_depth_and_prefix() {
+        let schema: Value = serde_json::from_str(
+            r#"{"properties":{
+                "user":{"properties":{"address":{"properties":{"city":{}}},"name":{}}},
+                "username":{},
+                "tags":{"items":{"properties":{"k":{}}}}}}"#,
+        )
+        .unwrap();
+        let paths = |options: CollectPathsOptions| {
+            let mut paths: Vec<String> = collect_paths_with_options(&schema, &options)
+                .into_iter()
+                .collect();
+            paths.sort();
+            paths
+        };
+        assert_eq!(paths(CollectPathsOptions::default()).len(), 8);
+        assert_eq!(
+            paths(CollectPathsOptions {
+                max_depth: Some(2),
+                ..Default::default()
+            }),
+            [
+                "tags",
+                "tags[]",
+                "user",
+                "user.address",
+                "user.name",
+                "username"
+            ]
+        );
+        assert_eq!(
+            paths(CollectPathsOptions {
+                prefix_filter: Some("user.".to_string()),
+                ..Default::default()
+            }),
+            ["user.address", "user.address.city", "user.name"]
+        );
+        assert_eq!(
+            paths(CollectPathsOptions {
+                max_depth: Some(2),
+                prefix_filter: Some("/properties/user/properties/a".to_string()),
+                path_format: PathFormat::JsonPointer,
+            }),
+            ["/properties/user/properties/address"]
+        );
+    }
+
+    #[test]
+    fn schema_paths_match_node_paths() {
+        let samples = [r#"{"id":1,"rows":[{"n":1}],"grid":[[true]],"meta":{"x.y":"a"}}"#];
+        let root = crate::parse_samples(&samples, &crate::InferOptions::default()).unwrap();
+        let schema: Value =
+            serde_json::from_str(&crate::infer_schema_rs(&samples, Default::default()).unwrap())
+                .unwrap();
+        let mut from_schema: Vec<String> =
+            collect_paths_with_options(&schema, &CollectPathsOptions::default())
+                .into_iter()
+                .collect();
+        let mut from_node = root.collect_paths();
+        from_schema.sort();
+        from_node.sort();
+        assert_eq!(from_schema, from_node);
+    }
+
+    #[test]
+    fn effective_type_without_type_keyword() {
+        let t = |s: &str| effective_type(&serde_json::from_str(s).unwrap());
+        assert_eq!(t(r#"{"const":"event"}"#), Value::from("string"));
+        assert_eq!(t(r#"{"enum":[1,2]}"#), Value::from("integer"));
+        assert_eq!(
+            t(r#"{"anyOf":[{"type":"string"},{"type":"null"}]}"#),
+            serde_json::json!(["null", "string"])
+        );
+        assert_eq!(t("{}"), Value::Null);
+    }
+
+    #[test]
+    fn empty_object_gaining_properties_is_modified() {
+        let a = r#"{"properties":{"e":{"type":"object","maxProperties":0},
+            "f":{"type":"object","x-aif-empty":true}}}"#;
+        let b = r#"{"properties":{"e":{"type":"object","properties":{"k":{"type":"string"}}},
+            "f":{"type":"object","properties":{"k":{"type":"string"}}}}}"#;
+        let d = diff_schemas_rs(a, b, DiffOptions::default()).unwrap();
+        assert_eq!(d.common, vec!["e", "f"]);
+        assert_eq!(d.added, vec!["e.k", "f.k"]);
+        let modified: Vec<(&str, &str)> = d
+            .modified
+            .iter()
+            .map(|m| (m.path.as_str(), m.keyword.as_str()))
+            .collect();
+        assert_eq!(modified, [("e", "maxProperties"), ("f", "x-aif-empty")]);
+        assert_eq!(d.modified[0].old, Some(Value::from(0)));
+        assert_eq!(d.modified[0].new, None);
+
+        let d = diff_schemas_rs(b, b, DiffOptions::default()).unwrap();
+        assert!(d.modified.is_empty());
+    }
+
+    #[test]
+    fn removed_paths_come_back_deprecated() {
+        let a = r#"{"type":"object","properties":{
+            "id":{"type":"integer"},
+            "legacy":{"type":"object","properties":{"code":{"type":"string"}}},
+            "user":{"type":"object","properties":{"name":{"type":"string"},"fax":{"type":"string"}}},
+            "tags":{"type":"array","items":{"type":"string"}}},
+            "required":["id","legacy"]}"#;
+        let b = r#"{"type":"object","properties":{
+            "id":{"type":"integer"},
+            "user":{"type":"object","properties":{"name":{"type":"string"}}},
+            "tags":{"type":"array"}},
+            "required":["id"]}"#;
+        let d = diff_schemas_rs(a, b, DiffOptions::default()).unwrap();
+        assert_eq!(d.removed, ["legacy", "legacy.code", "tags[]", "user.fax"]);
+        assert!(d.deprecated_annotated_schema.is_none());
+
+        let options = DiffOptions {
+            mark_removed_as_deprecated: true,
+            ..Default::default()
+        };
+        let d = diff_schemas_rs(a, b, options).unwrap();
+        let v: Value =
+            serde_json::from_str(d.deprecated_annotated_schema.as_ref().unwrap()).unwrap();
+        let p = &v["properties"];
+        assert_eq!(p["legacy"]["deprecated"], true);
+        assert_eq!(
+            p["legacy"]["properties"]["code"],
+            serde_json::json!({"type": "string"})
+        );
+        assert_eq!(
+            p["user"]["properties"]["fax"],
+            serde_json::json!({"type": "string", "deprecated": true})
+        );
+        assert_eq!(p["tags"]["items"]["deprecated"], true);
+        assert!(p["id"].get("deprecated").is_none());
+        // удалённые поля не становятся обязательными
+        assert_eq!(v["required"], serde_json::json!(["id"]));
+
+        let same = diff_schemas_rs(
+            b,
+            &d.deprecated_annotated_schema.unwrap(),
+            DiffOptions::default(),
+        );
+        assert!(same.unwrap().removed.is_empty());
+    }
+
+    #[test]
+    fn semantic_annotation_changes_are_modified() {
+        let a =
+            r#"{"properties":{"ts":{"type":"integer","x-aif-semantic":"unix-timestamp-seconds"}}}"#;
+        let b = r#"{"properties":{"ts":{"type":"integer"}}}"#;
+        for options in [
+            DiffOptions::default(),
+            DiffOptions {
+                include_extensions: true,
+                ..Default::default()
+            },
+        ] {
+            let d = diff_schemas_rs(a, b, options).unwrap();
+            assert_eq!(
+                d.modified,
+                vec![KeywordChange {
+                    path: "ts".to_string(),
+                    keyword: "x-aif-semantic".to_string(),
+                    old: Some(Value::from("unix-timestamp-seconds")),
+                    new: None,
+                }]
+            );
+        }
+    }
+
+    #[test]
+    fn extension_keywords_ignored_unless_requested() {
+        let a =
+            r#"{"properties":{"id":{"type":"integer","x-aif-frequency":{"count":3,"total":3}}}}"#;
+        let b =
+            r#"{"properties":{"id":{"type":"integer","x-aif-frequency":{"count":5,"total":9}}}}"#;
+        let d = diff_schemas_rs(a, b, DiffOptions::default()).unwrap();
+        assert!(d.modified.is_empty());
+        assert_eq!(d.common, vec!["id"]);
+
+        let options = DiffOptions {
+            include_extensions: true,
+            ..Default::default()
+        };
+        let d = diff_schemas_rs(a, b, options).unwrap();
+        assert_eq!(d.modified.len(), 1);
+        assert_eq!(d.modified[0].keyword, "x-aif-frequency");
+    }
+}
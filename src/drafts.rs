@@ -0,0 +1,180 @@
+use std::cmp::Ordering;
+
+use serde_json::{Map, Value};
+
+use crate::defs::DATA_KEYWORDS;
+use crate::extensions::{is_extension, NAME_MAPS};
+
+// Появились после draft-04 и не имеют замены в нём
+const NEWER_KEYWORDS: [&str; 13] = [
+    "contains",
+    "minContains",
+    "maxContains",
+    "propertyNames",
+    "if",
+    "then",
+    "else",
+    "dependentRequired",
+    "dependentSchemas",
+    "unevaluatedItems",
+    "unevaluatedProperties",
+    "contentEncoding",
+    "contentMediaType",
+];
+
+/// Rewrites a rendered document for draft-04 consumers at any depth: `const`
+/// becomes a one-value `enum`, numeric `exclusiveMinimum` / `exclusiveMaximum`
+/// become the boolean flag next to `minimum` / `maximum`, and keywords draft-04
+/// does not know are removed and named in `x-aif-dropped-keywords`.
+pub(crate) fn downgrade_to_draft04(doc: &mut Map<String, Value>) {
+    downgrade_object(doc);
+}
+
+fn downgrade(v: &mut Value) {
+    match v {
+        Value::Object(m) => downgrade_object(m),
+        Value::Array(items) => items.iter_mut().for_each(downgrade),
+        _ => {}
+    }
+}
+
+fn downgrade_object(m: &mut Map<String, Value>) {
+    if let Some(c) = m.remove("const") {
+        m.insert("enum".to_string(), Value::Array(vec![c]));
+    }
+    exclusive_flag(m, "minimum", "exclusiveMinimum", Ordering::Greater);
+    exclusive_flag(m, "maximum", "exclusiveMaximum", Ordering::Less);
+
+    let dropped: Vec<Value> = NEWER_KEYWORDS
+        .iter()
+        .filter(|k| m.remove(**k).is_some())
+        .map(|k| Value::from(*k))
+        .collect();
+    if !dropped.is_empty() {
+        m.insert("x-aif-dropped-keywords".to_string(), Value::Array(dropped));
+    }
+
+    for (k, child) in m.iter_mut() {
+        if DATA_KEYWORDS.contains(&k.as_str()) || is_extension(k) {
+            continue;
+        }
+        // в draft-07 dependencies — тоже словарь по именам свойств
+        match (
+            NAME_MAPS.contains(&k.as_str()) || k == "dependencies",
+            child,
+        ) {
+            (true, Value::Object(named)) => named.values_mut().for_each(downgrade),
+            (_, child) => downgrade(child),
+        }
+    }
+}
+
+// Числовая исключающая граница становится флагом; из двух границ
+// остаётся более строгая, при равенстве — исключающая
+fn exclusive_flag(m: &mut Map<String, Value>, bound: &str, exclusive: &str, stricter: Ordering) {
+    let Some(Value::Number(limit)) = m.get(exclusive).cloned() else {
+        return;
+    };
+    let inclusive = m.get(bound).and_then(Value::as_f64);
+    let keep_inclusive = inclusive.is_some_and(|b| {
+        limit
+            .as_f64()
+            .and_then(|l| b.partial_cmp(&l))
+            .is_some_and(|o| o == stricter)
+    });
+    if keep_inclusive {
+        m.remove(exclusive);
+    } else {
+        m.insert(bound.to_string(), Value::Number(limit));
+        m.insert(exclusive.to_string(), Value::Bool(true));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::infer_with;
+    use crate::{
+        validate_sample_rs, ConditionalRule, ContainsCandidate, ContainsRule, InferOptions,
+        NullableStyle, SchemaDraft,
+    };
+    use serde_json::{json, Value};
+
+    const SAMPLES: &[&str] = &[
+        r#"{"kind":"a","pt":["x",1],"amount":5,"note":null,"tags":["t1"]}"#,
+        r#"{"kind":"a","pt":["y",2],"amount":7,"note":"n","tags":["t1","t2"]}"#,
+    ];
+
+    fn infer(draft: SchemaDraft) -> Value {
+        let opts = InferOptions {
+            draft,
+            detect_tuples: true,
+            exclusive_zero_bounds: true,
+            exclusive_bounds_min_observations: 1,
+            nullable_style: NullableStyle::Nullable,
+            conditional_rules: vec![ConditionalRule {
+                field: "kind".to_string(),
+                value: json!("a"),
+            }],
+            contains_rules: vec![ContainsRule {
+                path: "tags".to_string(),
+                candidate: ContainsCandidate::Schema(json!({"const": "t1"})),
+            }],
+            ..Default::default()
+        };
+        infer_with(SAMPLES, opts)
+    }
+
+    #[test]
+    fn same_samples_under_every_draft() {
+        let v = infer(SchemaDraft::Draft04);
+        let p = &v["properties"];
+        assert_eq!(v["$schema"], "http://json-schema.org/draft-04/schema#");
+        assert_eq!(p["kind"], json!({"enum": ["a"]}));
+        assert_eq!(p["pt"]["items"][0]["type"], "string");
+        assert_eq!(p["pt"]["additionalItems"], false);
+        assert_eq!(p["amount"]["minimum"], 0);
+        assert_eq!(p["amount"]["exclusiveMinimum"], true);
+        assert_eq!(p["note"]["type"], json!(["null", "string"]));
+        assert!(p["note"].get("nullable").is_none());
+        assert!(p["tags"].get("contains").is_none());
+        assert_eq!(p["tags"]["x-aif-dropped-keywords"], json!(["contains"]));
+        assert!(v.get("if").is_none());
+        assert_eq!(v["x-aif-dropped-keywords"], json!(["if", "then"]));
+        // булев exclusiveMinimum делает minimum строгим
+        let schema = serde_json::to_string(&v).unwrap();
+        for sample in SAMPLES {
+            assert_eq!(validate_sample_rs(&schema, sample).unwrap(), []);
+        }
+        let zero = SAMPLES[0].replace(r#""amount":5"#, r#""amount":0"#);
+        let errors = validate_sample_rs(&schema, &zero).unwrap();
+        assert_eq!(errors[0].path, "amount");
+        assert_eq!(errors[0].message, "0 is not above exclusive minimum 0");
+
+        let v = infer(SchemaDraft::Draft07);
+        let p = &v["properties"];
+        assert_eq!(v["$schema"], "http://json-schema.org/draft-07/schema#");
+        assert_eq!(p["kind"]["const"], "a");
+        assert_eq!(p["pt"]["additionalItems"], false);
+        assert_eq!(p["amount"]["exclusiveMinimum"], 0);
+        assert!(p["amount"].get("minimum").is_none());
+        assert_eq!(p["note"], json!({"type": "string", "nullable": true}));
+        assert_eq!(p["tags"]["contains"], json!({"const": "t1"}));
+        assert!(p["tags"].get("minContains").is_none());
+        assert_eq!(v["if"]["properties"]["kind"]["const"], "a");
+
+        let v = infer(SchemaDraft::Draft201909);
+        let p = &v["properties"];
+        assert_eq!(v["$schema"], "https://json-schema.org/draft/2019-09/schema");
+        assert_eq!(p["pt"]["additionalItems"], false);
+        assert_eq!(p["tags"]["contains"], json!({"const": "t1"}));
+        assert!(v.get("x-aif-dropped-keywords").is_none());
+
+        let v = infer(SchemaDraft::Draft202012);
+        let p = &v["properties"];
+        assert_eq!(v["$schema"], "https://json-schema.org/draft/2020-12/schema");
+        assert_eq!(p["pt"]["prefixItems"][1]["type"], "integer");
+        assert_eq!(p["pt"]["items"], false);
+        assert_eq!(p["amount"]["exclusiveMinimum"], 0);
+        assert_eq!(p["note"]["nullable"], true);
+    }
+}
@@ -0,0 +1,43 @@
+use thiserror::Error;
+
+/// Errors returned by the inference API.
+#[derive(Debug, Error)]
+pub enum InferError {
+    /// Sample number `index` (0-based) is not valid JSON.
+    #[error("invalid JSON in sample {index}: {source}")]
+    InvalidJson {
+        index: usize,
+        source: serde_json::Error,
+    },
+    /// A schema passed to a diff/merge/... function is not valid JSON.
+    #[error("invalid {name}: {source}")]
+    InvalidSchema {
+        name: &'static str,
+        source: serde_json::Error,
+    },
+    /// A schema uses a construct that cannot be turned back into an inference node.
+    #[error("unsupported schema at {path}: {reason}")]
+    UnsupportedSchema { path: String, reason: String },
+    /// A type name that is not one of the seven JSON types.
+    #[error("unknown JSON type: {0}")]
+    UnknownType(String),
+    /// A JSON Patch could not be applied.
+    #[error("invalid patch: {reason}")]
+    InvalidPatch { reason: String },
+    /// A checkpoint written by `SchemaInferrer::save` could not be read back.
+    #[error("invalid checkpoint {path}: {source}")]
+    InvalidCheckpoint {
+        path: String,
+        source: serde_json::Error,
+    },
+    /// A sample skipped under `ErrorMode::Lenient` before a checkpoint was
+    /// saved; a checkpoint keeps the original error as text only.
+    #[error("{message}")]
+    SkippedSample { index: usize, message: String },
+    #[error("serialize error: {0}")]
+    SerializeError(serde_json::Error),
+    #[error("depth limit exceeded at {path}")]
+    DepthLimitExceeded { path: String },
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Fixed-size uniform sample of the distinct scalar values seen at a node
+/// (Algorithm R with a deterministic generator, so output is reproducible).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reservoir {
+    values: Vec<Value>,
+    cap: usize,
+    seen: u64,
+    rng: u64,
+}
+
+impl Reservoir {
+    pub fn new(cap: usize) -> Reservoir {
+        Reservoir {
+            values: Vec::with_capacity(cap),
+            cap,
+            seen: 0,
+            rng: 0x9E37_79B9_7F4A_7C15,
+        }
+    }
+
+    /// Reservoir holding exactly `values` (deduplicated).
+    pub fn from_values(values: &[Value]) -> Reservoir {
+        let mut r = Reservoir::new(values.len());
+        for v in values {
+            r.offer(v.clone());
+        }
+        r
+    }
+
+    /// Offers an already truncated value; duplicates are not stored twice.
+    pub fn offer(&mut self, v: Value) {
+        if self.cap == 0 || self.values.contains(&v) {
+            return;
+        }
+        self.seen += 1;
+        if self.values.len() < self.cap {
+            self.values.push(v);
+            return;
+        }
+        let j = self.next() % self.seen;
+        if let Ok(j) = usize::try_from(j) {
+            if j < self.cap {
+                self.values[j] = v;
+            }
+        }
+    }
+
+    pub fn merge(&mut self, other: Reservoir) {
+        self.cap = self.cap.max(other.cap);
+        for v in other.values {
+            self.offer(v);
+        }
+    }
+
+    pub fn values(&self) -> &[Value] {
+        &self.values
+    }
+
+    // splitmix64
+    fn next(&mut self) -> u64 {
+        self.rng = self.rng.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+/// Strings longer than `max_len` characters are cut and marked with `…`.
+pub fn truncate_example(s: &str, max_len: usize) -> Value {
+    match s.char_indices().nth(max_len) {
+        Some((end, _)) => Value::String(format!("{}…", &s[..end])),
+        None => Value::String(s.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reservoir_is_capped_and_deduplicated() {
+        let mut r = Reservoir::new(3);
+        for i in 0..1_000_000u64 {
+            r.offer(Value::from(i % 5000));
+        }
+        assert_eq!(r.values().len(), 3);
+
+        let mut r = Reservoir::new(3);
+        for _ in 0..10 {
+            r.offer(Value::from("same"));
+        }
+        assert_eq!(r.values(), [Value::from("same")]);
+    }
+
+    #[test]
+    fn truncation_respects_char_boundaries() {
+        assert_eq!(truncate_example("привет", 3), Value::from("при…"));
+        assert_eq!(truncate_example("abc", 3), Value::from("abc"));
+    }
+}
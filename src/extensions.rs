@@ -0,0 +1,93 @@
+use serde_json::{Map, Value};
+
+use crate::defs::DATA_KEYWORDS;
+use crate::diff::parse_schema;
+use crate::InferError;
+
+// Ключи этих ключевых слов — имена, а не ключевые слова схемы
+pub(crate) const NAME_MAPS: [&str; 5] = [
+    "properties",
+    "patternProperties",
+    "$defs",
+    "definitions",
+    "dependentSchemas",
+];
+
+pub(crate) fn is_extension(keyword: &str) -> bool {
+    keyword.starts_with("x-")
+}
+
+/// `schema` without vendor extension keywords (`x-...`, such as
+/// `x-aif-frequency`) at any depth. Property names starting with `x-` are kept.
+pub fn strip_extensions_rs(schema: &str) -> Result<String, InferError> {
+    let mut v = parse_schema(schema, "schema")?;
+    strip_keywords(&mut v, is_extension);
+    serde_json::to_string_pretty(&v).map_err(InferError::SerializeError)
+}
+
+// Убирает ключевые слова, для которых drop истинно, на любой глубине схемы
+pub(crate) fn strip_keywords(v: &mut Value, drop: fn(&str) -> bool) {
+    match v {
+        Value::Object(m) => strip_object(m, drop),
+        Value::Array(items) => items.iter_mut().for_each(|item| strip_keywords(item, drop)),
+        _ => {}
+    }
+}
+
+fn strip_object(m: &mut Map<String, Value>, drop: fn(&str) -> bool) {
+    m.retain(|k, _| !drop(k));
+    for (k, child) in m.iter_mut() {
+        if DATA_KEYWORDS.contains(&k.as_str()) {
+            continue;
+        }
+        match (NAME_MAPS.contains(&k.as_str()), child) {
+            (true, Value::Object(named)) => {
+                named.values_mut().for_each(|sub| strip_keywords(sub, drop))
+            }
+            (_, child) => strip_keywords(child, drop),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{infer_schema_from_strs, InferOptions};
+    use serde_json::json;
+
+    #[test]
+    fn frequency_is_annotated_and_stripped() {
+        let samples = [
+            r#"{"id":1,"x-y":{"a":1},"tags":[{"k":1}]}"#,
+            r#"{"id":2,"tags":[{"k":2},{}]}"#,
+            r#"{"id":3,"tags":[]}"#,
+        ];
+        let opts = InferOptions {
+            annotate_frequency: true,
+            ..Default::default()
+        };
+        let schema = infer_schema_from_strs(&samples, opts).unwrap();
+        let v: Value = serde_json::from_str(&schema).unwrap();
+        let frequency = |p: &str| v.pointer(&format!("{p}/x-aif-frequency")).cloned();
+        assert_eq!(
+            frequency("/properties/id"),
+            Some(json!({"count": 3, "total": 3}))
+        );
+        assert_eq!(
+            frequency("/properties/x-y"),
+            Some(json!({"count": 1, "total": 3}))
+        );
+        assert_eq!(
+            frequency("/properties/tags/items/properties/k"),
+            Some(json!({"count": 2, "total": 3}))
+        );
+
+        let stripped: Value = serde_json::from_str(&strip_extensions_rs(&schema).unwrap()).unwrap();
+        let plain: Value = serde_json::from_str(
+            &infer_schema_from_strs(&samples, InferOptions::default()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(stripped, plain);
+        assert!(stripped["properties"].get("x-y").is_some());
+    }
+}
@@ -0,0 +1,635 @@
+use serde::{Deserialize, Serialize};
+
+struct Detector {
+    format: FormatSet,
+    name: &'static str,
+    check: fn(&str) -> bool,
+    // Сколько строк нужно увидеть, прежде чем доверять формату
+    min_observations: u64,
+}
+
+/// Set of string formats that are still possible for a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct FormatSet(u16);
+
+impl FormatSet {
+    pub const DATE_TIME: FormatSet = FormatSet(1 << 0);
+    pub const EMAIL: FormatSet = FormatSet(1 << 1);
+    pub const UUID: FormatSet = FormatSet(1 << 2);
+    pub const URI: FormatSet = FormatSet(1 << 3);
+    pub const IPV4: FormatSet = FormatSet(1 << 4);
+    pub const IPV6: FormatSet = FormatSet(1 << 5);
+    // Каждое значение — ipv4 или ipv6 (в выводе отдельного формата нет)
+    const IP_MIXED: FormatSet = FormatSet(1 << 6);
+    pub const DATE: FormatSet = FormatSet(1 << 7);
+    pub const TIME: FormatSet = FormatSet(1 << 8);
+    pub const DURATION: FormatSet = FormatSet(1 << 9);
+    pub const HOSTNAME: FormatSet = FormatSet(1 << 10);
+    pub const JSON_POINTER: FormatSet = FormatSet(1 << 11);
+    // Все указатели пока пустые: "" слишком частое обычное значение
+    const EMPTY_POINTERS: FormatSet = FormatSet(1 << 12);
+    pub const ALL: FormatSet = FormatSet(
+        Self::DATE_TIME.0
+            | Self::DATE.0
+            | Self::TIME.0
+            | Self::DURATION.0
+            | Self::EMAIL.0
+            | Self::UUID.0
+            | Self::URI.0
+            | Self::HOSTNAME.0
+            | Self::JSON_POINTER.0
+            | Self::EMPTY_POINTERS.0
+            | Self::IPV4.0
+            | Self::IPV6.0
+            | Self::IP_MIXED.0,
+    );
+
+    // Порядок важен: при выводе берётся первый уцелевший формат
+    const ORDERED: &'static [Detector] = &[
+        Detector {
+            format: FormatSet::DATE_TIME,
+            name: "date-time",
+            check: is_date_time,
+            min_observations: 1,
+        },
+        Detector {
+            format: FormatSet::DATE,
+            name: "date",
+            check: is_date,
+            min_observations: 1,
+        },
+        Detector {
+            format: FormatSet::TIME,
+            name: "time",
+            check: is_time,
+            min_observations: 1,
+        },
+        Detector {
+            format: FormatSet::DURATION,
+            name: "duration",
+            check: is_duration,
+            min_observations: 1,
+        },
+        Detector {
+            format: FormatSet::UUID,
+            name: "uuid",
+            check: is_uuid,
+            min_observations: 1,
+        },
+        Detector {
+            format: FormatSet::IPV4,
+            name: "ipv4",
+            check: is_ipv4,
+            min_observations: 1,
+        },
+        Detector {
+            format: FormatSet::IPV6,
+            name: "ipv6",
+            check: is_ipv6,
+            min_observations: 1,
+        },
+        Detector {
+            format: FormatSet::EMAIL,
+            name: "email",
+            check: is_email,
+            min_observations: 3,
+        },
+        Detector {
+            format: FormatSet::URI,
+            name: "uri",
+            check: is_uri,
+            min_observations: 1,
+        },
+        Detector {
+            format: FormatSet::HOSTNAME,
+            name: "hostname",
+            check: is_hostname,
+            min_observations: 1,
+        },
+        Detector {
+            format: FormatSet::JSON_POINTER,
+            name: "json-pointer",
+            check: is_json_pointer,
+            min_observations: 1,
+        },
+    ];
+
+    pub fn contains(self, other: FormatSet) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Drops every format that `s` does not conform to.
+    pub fn retain_matching(self, s: &str) -> FormatSet {
+        let mut out = self;
+        for d in Self::ORDERED {
+            if out.contains(d.format) && !(d.check)(s) {
+                out.0 &= !d.format.0;
+            }
+        }
+        if out.contains(Self::IP_MIXED) && !(is_ipv4(s) || is_ipv6(s)) {
+            out.0 &= !Self::IP_MIXED.0;
+        }
+        if !s.is_empty() {
+            out.0 &= !Self::EMPTY_POINTERS.0;
+        }
+        out
+    }
+
+    /// The set a field declared with `format: name` stands for; unknown
+    /// names give the empty set.
+    pub fn from_name(name: &str) -> FormatSet {
+        let Some(d) = Self::ORDERED.iter().find(|d| d.name == name) else {
+            return FormatSet::default();
+        };
+        if d.format == Self::IPV4 || d.format == Self::IPV6 {
+            FormatSet(d.format.0 | Self::IP_MIXED.0)
+        } else {
+            d.format
+        }
+    }
+
+    /// Formats satisfied by the values behind both sets.
+    pub fn intersect(self, other: FormatSet) -> FormatSet {
+        FormatSet(self.0 & other.0)
+    }
+
+    /// Every value was an IP address, but both families were seen.
+    pub fn is_mixed_ip(self) -> bool {
+        self.contains(Self::IP_MIXED) && !self.contains(Self::IPV4) && !self.contains(Self::IPV6)
+    }
+
+    /// JSON Schema `format` name of the highest-priority format in the set
+    /// that has seen enough observations to be trusted.
+    pub fn name(self, observed: u64) -> Option<&'static str> {
+        Self::ORDERED
+            .iter()
+            .find(|d| {
+                self.contains(d.format)
+                    && observed >= d.min_observations
+                    && (d.format != Self::JSON_POINTER || !self.contains(Self::EMPTY_POINTERS))
+            })
+            .map(|d| d.name)
+    }
+}
+
+fn digits(b: &[u8]) -> Option<u32> {
+    if b.is_empty() || !b.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    Some(b.iter().fold(0, |acc, d| acc * 10 + u32::from(d - b'0')))
+}
+
+fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        _ => 31,
+    }
+}
+
+/// RFC 3339 `full-date`: `YYYY-MM-DD`.
+fn is_date(s: &str) -> bool {
+    let b = s.as_bytes();
+    if b.len() != 10 || b[4] != b'-' || b[7] != b'-' {
+        return false;
+    }
+    match (digits(&b[0..4]), digits(&b[5..7]), digits(&b[8..10])) {
+        (Some(y), Some(m), Some(d)) => (1..=12).contains(&m) && d >= 1 && d <= days_in_month(y, m),
+        _ => false,
+    }
+}
+
+/// RFC 3339 `full-time`: `HH:MM:SS[.frac](Z|+HH:MM|-HH:MM)`.
+fn is_time(s: &str) -> bool {
+    let b = s.as_bytes();
+    if b.len() < 9 || b[2] != b':' || b[5] != b':' {
+        return false;
+    }
+    match (digits(&b[0..2]), digits(&b[3..5]), digits(&b[6..8])) {
+        // секунда 60 допустима для високосной секунды
+        (Some(h), Some(m), Some(sec)) if h < 24 && m < 60 && sec <= 60 => {}
+        _ => return false,
+    }
+    let mut rest = &b[8..];
+    if let Some(frac) = rest.strip_prefix(b".") {
+        let n = frac.iter().take_while(|c| c.is_ascii_digit()).count();
+        if n == 0 {
+            return false;
+        }
+        rest = &frac[n..];
+    }
+    match rest {
+        [b'Z' | b'z'] => true,
+        [b'+' | b'-', h1, h2, b':', m1, m2] => matches!(
+            (digits(&[*h1, *h2]), digits(&[*m1, *m2])),
+            (Some(h), Some(m)) if h < 24 && m < 60
+        ),
+        _ => false,
+    }
+}
+
+/// RFC 3339 `date-time`.
+fn is_date_time(s: &str) -> bool {
+    s.len() > 11
+        && matches!(s.as_bytes()[10], b'T' | b't')
+        && s.is_char_boundary(10)
+        && is_date(&s[..10])
+        && is_time(&s[11..])
+}
+
+// ISO 8601: PnYnMnDTnHnMnS с любым непустым набором компонентов по порядку или PnW
+fn is_duration(s: &str) -> bool {
+    let Some(rest) = s.strip_prefix('P') else {
+        return false;
+    };
+    if let Some(weeks) = rest.strip_suffix('W') {
+        return !weeks.is_empty() && weeks.bytes().all(|b| b.is_ascii_digit());
+    }
+    let (date, time) = match rest.split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (rest, None),
+    };
+    let Some((date_parts, date_fraction)) = duration_parts(date, b"YMD") else {
+        return false;
+    };
+    match time {
+        // дробной может быть только последняя компонента всей длительности
+        Some(time) => !date_fraction && duration_parts(time, b"HMS").is_some_and(|(n, _)| n > 0),
+        None => date_parts > 0,
+    }
+}
+
+// Компоненты вида 12Y или 0.5S с обозначениями в заданном порядке, дробь — только
+// у последней. Возвращает число компонент и была ли дробь.
+fn duration_parts(s: &str, designators: &[u8]) -> Option<(usize, bool)> {
+    let b = s.as_bytes();
+    let (mut i, mut next, mut count, mut fraction) = (0, 0, 0, false);
+    while i < b.len() {
+        if fraction {
+            return None;
+        }
+        let start = i;
+        while i < b.len() && b[i].is_ascii_digit() {
+            i += 1;
+        }
+        if i == start {
+            return None;
+        }
+        if b[i..].starts_with(b".") || b[i..].starts_with(b",") {
+            i += 1;
+            let frac_start = i;
+            while i < b.len() && b[i].is_ascii_digit() {
+                i += 1;
+            }
+            if i == frac_start {
+                return None;
+            }
+            fraction = true;
+        }
+        let d = *b.get(i)?;
+        next += designators[next..].iter().position(|x| *x == d)? + 1;
+        i += 1;
+        count += 1;
+    }
+    Some((count, fraction))
+}
+
+// Метка доменного имени: буквы, цифры и дефис, но не по краям
+fn is_dns_label(label: &str) -> bool {
+    let b = label.as_bytes();
+    !b.is_empty()
+        && b.len() <= 63
+        && b.iter().all(|c| c.is_ascii_alphanumeric() || *c == b'-')
+        && b[0] != b'-'
+        && b[b.len() - 1] != b'-'
+}
+
+/// RFC 1123 hostname with at least two labels; a numeric last label is left
+/// to ipv4 (`10.0.0.1`) and version-like strings (`1.2.3`).
+fn is_hostname(s: &str) -> bool {
+    // без точки под формат подходило бы любое слово
+    let Some((_, last)) = s.rsplit_once('.') else {
+        return false;
+    };
+    s.len() <= 253 && !last.bytes().all(|c| c.is_ascii_digit()) && s.split('.').all(is_dns_label)
+}
+
+/// RFC 6901: empty, or `/`-prefixed reference tokens where `~` only appears
+/// as the `~0` / `~1` escapes.
+fn is_json_pointer(s: &str) -> bool {
+    if s.is_empty() {
+        return true;
+    }
+    let b = s.as_bytes();
+    b[0] == b'/'
+        && b.iter()
+            .enumerate()
+            .all(|(i, c)| *c != b'~' || matches!(b.get(i + 1), Some(b'0' | b'1')))
+}
+
+/// Conservative `local@domain` check: prefers false negatives to false positives.
+fn is_email(s: &str) -> bool {
+    let Some((local, domain)) = s.split_once('@') else {
+        return false;
+    };
+    let local_ok = !local.is_empty()
+        && local.len() <= 64
+        && !local.starts_with('.')
+        && !local.ends_with('.')
+        && !local.contains("..")
+        && local
+            .bytes()
+            .all(|c| c.is_ascii_alphanumeric() || b".!#$%&'*+/=?^_`{|}~-".contains(&c));
+    local_ok && domain.len() <= 253 && domain.contains('.') && domain.split('.').all(is_dns_label)
+}
+
+/// Hyphenated RFC 4122 UUID of any version, hex digits in either case.
+pub(crate) fn is_uuid(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() == 36
+        && b.iter().enumerate().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => *c == b'-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
+
+// unreserved / reserved по RFC 3986 (без '%', он проверяется отдельно)
+fn is_uri_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || b"-._~:/?#[]@!$&'()*+,;=".contains(&c)
+}
+
+/// RFC 3986 absolute URI: `scheme ":" ("//" authority path | path) ["?" query] ["#" fragment]`.
+fn is_uri(s: &str) -> bool {
+    let Some((scheme, rest)) = s.split_once(':') else {
+        return false;
+    };
+    let sb = scheme.as_bytes();
+    if sb.is_empty()
+        || !sb[0].is_ascii_alphabetic()
+        || !sb
+            .iter()
+            .all(|c| c.is_ascii_alphanumeric() || b"+-.".contains(c))
+    {
+        return false;
+    }
+
+    let b = rest.as_bytes();
+    let mut i = 0;
+    while i < b.len() {
+        match b[i] {
+            b'%' => {
+                if !(b.get(i + 1).is_some_and(u8::is_ascii_hexdigit)
+                    && b.get(i + 2).is_some_and(u8::is_ascii_hexdigit))
+                {
+                    return false;
+                }
+                i += 3;
+            }
+            c if is_uri_char(c) => i += 1,
+            _ => return false,
+        }
+    }
+    if rest.matches('#').count() > 1 {
+        return false;
+    }
+
+    let hier = rest.split(['?', '#']).next().unwrap_or("");
+    match hier.strip_prefix("//") {
+        Some(after) => {
+            let (authority, path) = after.split_at(after.find('/').unwrap_or(after.len()));
+            // квадратные скобки допустимы только вокруг IP-литерала в authority
+            let host = authority.rsplit('@').next().unwrap_or("");
+            let brackets_ok = !path.contains(['[', ']'])
+                && (!host.contains(['[', ']']) || (host.starts_with('[') && host.contains(']')));
+            brackets_ok && !(authority.is_empty() && path.is_empty())
+        }
+        None => !hier.is_empty() && !hier.contains(['[', ']']),
+    }
+}
+
+fn is_ipv4(s: &str) -> bool {
+    s.parse::<std::net::Ipv4Addr>().is_ok()
+}
+
+fn is_ipv6(s: &str) -> bool {
+    s.parse::<std::net::Ipv6Addr>().is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn date_time_grammar() {
+        assert!(is_date_time("2024-06-01T13:45:00Z"));
+        assert!(is_date_time("2024-02-29t00:00:00.123456+05:30"));
+        assert!(is_date_time("2016-12-31T23:59:60-08:00"));
+        assert!(!is_date_time("2023-02-29T00:00:00Z"));
+        assert!(!is_date_time("2024-06-01 13:45:00Z"));
+        assert!(!is_date_time("2024-06-01T13:45:00"));
+        assert!(!is_date_time("2024-06-01T24:00:00Z"));
+        assert!(!is_date_time("2024-06-01T13:45:00.Z"));
+        assert!(!is_date_time("2024-13-01T13:45:00+01:60"));
+        assert!(!is_date_time("2024-06-01T13:45:00Zjunk"));
+        assert!(!is_date_time("2024-06-01Тé"));
+    }
+
+    #[test]
+    fn date_and_time_are_separate_from_date_time() {
+        assert!(is_date("2024-02-29"));
+        assert!(is_date("2000-02-29"));
+        assert!(!is_date("1900-02-29"));
+        assert!(!is_date("2024-06-31"));
+        assert!(!is_date("2024-6-01"));
+        assert!(is_time("13:45:00Z"));
+        assert!(is_time("23:59:59.999999-07:00"));
+        assert!(is_time("00:00:00+14:00"));
+        assert!(!is_time("13:45:00"));
+        assert!(!is_time("13:45Z"));
+
+        let date_time = FormatSet::ALL.retain_matching("2024-02-29T13:45:00.25+01:00");
+        assert_eq!(date_time.name(1), Some("date-time"));
+        let date = FormatSet::ALL.retain_matching("2024-02-29");
+        assert_eq!(date.name(1), Some("date"));
+        let time = FormatSet::ALL.retain_matching("13:45:00.5Z");
+        assert_eq!(time.name(1), Some("time"));
+        // дата и полная метка времени вместе не дают ни одного формата
+        assert_eq!(date.retain_matching("2024-02-29T13:45:00Z").name(2), None);
+    }
+
+    #[test]
+    fn duration_grammar() {
+        for s in [
+            "PT5M",
+            "P1DT2H",
+            "P2W",
+            "PT0.5S",
+            "PT1,5S",
+            "P1Y2M3DT4H5M6S",
+            "P1Y1D",
+            "PT36H",
+            "P0D",
+        ] {
+            assert!(is_duration(s), "{s}");
+        }
+        for s in [
+            "P", "PT", "P1DT", "pt5m", "PT5m", "p1D", "P1M1Y", "PT1S1M", "P1Y2W", "P2W1D",
+            "P1.5DT2H", "PT0.5M1S", "PT.5S", "PT5.S", "P-1D", "1D", "P1DT2HZ", "P1D ",
+        ] {
+            assert!(!is_duration(s), "{s}");
+        }
+        let set = FormatSet::ALL
+            .retain_matching("PT5M")
+            .retain_matching("P2W");
+        assert_eq!(set.name(2), Some("duration"));
+        assert_eq!(set.retain_matching("5 minutes").name(3), None);
+        assert_eq!(FormatSet::from_name("duration"), FormatSet::DURATION);
+    }
+
+    #[test]
+    fn hostname_labels() {
+        for s in [
+            "db-replica-3.internal.example.com",
+            "xn--bcher-kva.example",
+            "api.xn--80akhbyknj4f.xn--p1ai",
+            "Example.COM",
+            "a.b2",
+        ] {
+            assert!(is_hostname(s), "{s}");
+        }
+        let long_label = format!("{}.com", "a".repeat(64));
+        let long_name = vec!["a".repeat(63); 4].join(".") + ".com";
+        for s in [
+            "localhost",
+            "-db.example.com",
+            "db-.example.com",
+            "db..example.com",
+            "db.example.com.",
+            "db_1.example.com",
+            "http://example.com",
+            "example.com/path",
+            "my host.example.com",
+            "10.0.0.1",
+            "1.2.3",
+            long_label.as_str(),
+            long_name.as_str(),
+        ] {
+            assert!(!is_hostname(s), "{s}");
+        }
+        let ip = FormatSet::ALL.retain_matching("10.0.0.1");
+        assert_eq!(ip.name(1), Some("ipv4"));
+        assert!(!ip.contains(FormatSet::HOSTNAME));
+        let set = FormatSet::ALL
+            .retain_matching("xn--bcher-kva.example")
+            .retain_matching("db-replica-3.internal.example.com");
+        assert_eq!(set.name(2), Some("hostname"));
+        assert_eq!(set.retain_matching("not a host").name(3), None);
+    }
+
+    #[test]
+    fn json_pointer_grammar() {
+        for s in [
+            "",
+            "/",
+            "/spec/replicas",
+            "/a~1b/~0c",
+            "/items/0",
+            "//x",
+            "/ü ñ",
+        ] {
+            assert!(is_json_pointer(s), "{s:?}");
+        }
+        for s in ["spec/replicas", "#/spec", "/a~2b", "/a~", "/~/x"] {
+            assert!(!is_json_pointer(s), "{s:?}");
+        }
+        // одни пустые строки формата не дают, один непустой указатель — даёт
+        let empty = FormatSet::ALL.retain_matching("").retain_matching("");
+        assert!(empty.contains(FormatSet::JSON_POINTER));
+        assert_eq!(empty.name(2), None);
+        let set = empty.retain_matching("/spec/replicas");
+        assert_eq!(set.name(3), Some("json-pointer"));
+        assert_eq!(set.intersect(empty).name(5), Some("json-pointer"));
+        assert_eq!(set.retain_matching("spec").name(4), None);
+        assert_eq!(
+            FormatSet::from_name("json-pointer").name(1),
+            Some("json-pointer")
+        );
+    }
+
+    #[test]
+    fn email_shape() {
+        assert!(is_email("alice@example.com"));
+        assert!(is_email("first.last+tag@mail.co.uk"));
+        assert!(!is_email("alice@localhost"));
+        assert!(!is_email("alice@@example.com"));
+        assert!(!is_email("a@b@example.com"));
+        assert!(!is_email("@example.com"));
+        assert!(!is_email("alice.@example.com"));
+        assert!(!is_email("alice@-example.com"));
+        assert!(!is_email("alice @example.com"));
+        assert!(!is_email("alice@example..com"));
+    }
+
+    #[test]
+    fn uuid_shape() {
+        assert!(is_uuid("0f8fad5b-d9cb-469f-a165-70867728950e"));
+        assert!(is_uuid("7C9E6679-7425-40DE-944B-E07FC1F90AE7"));
+        assert!(is_uuid("00000000-0000-0000-0000-000000000000"));
+        assert!(!is_uuid("0f8fad5bd9cb469fa16570867728950e"));
+        assert!(!is_uuid("0f8fad5b-d9cb-469f-a165-70867728950"));
+        assert!(!is_uuid("0f8fad5b-d9cb-469f-a165-70867728950g"));
+        assert!(!is_uuid("{0f8fad5b-d9cb-469f-a165-70867728950e}"));
+    }
+
+    #[test]
+    fn uri_grammar() {
+        assert!(is_uri("https://example.com/x?y=1"));
+        assert!(is_uri("s3://bucket/key/with%20space"));
+        assert!(is_uri("file:///etc/hosts"));
+        assert!(is_uri("mailto:alice@example.com"));
+        assert!(is_uri("urn:isbn:0451450523"));
+        assert!(is_uri("http://[::1]:8080/"));
+        assert!(!is_uri("example.com/x"));
+        assert!(!is_uri("/relative/path"));
+        assert!(!is_uri("https://exa mple.com"));
+        assert!(!is_uri("https://"));
+        assert!(!is_uri("http://x/%zz"));
+        assert!(!is_uri("http://x/a#b#c"));
+        assert!(!is_uri("1http://x"));
+        assert!(!is_uri("mailto:"));
+    }
+
+    #[test]
+    fn ip_addresses() {
+        assert!(is_ipv4("10.0.0.255"));
+        assert!(!is_ipv4("999.1.1.1"));
+        assert!(!is_ipv4("1.2.3"));
+        assert!(is_ipv6("2001:db8::1"));
+        assert!(is_ipv6("::ffff:192.0.2.1"));
+        assert!(!is_ipv6("10.0.0.1"));
+
+        let mixed = FormatSet::ALL
+            .retain_matching("10.0.0.1")
+            .retain_matching("2001:db8::1");
+        assert_eq!(mixed.name(2), None);
+        assert!(mixed.is_mixed_ip());
+
+        let v4 = FormatSet::ALL.retain_matching("10.0.0.1");
+        assert_eq!(v4.name(1), Some("ipv4"));
+        assert!(!v4.is_mixed_ip());
+    }
+
+    #[test]
+    fn email_needs_three_observations() {
+        let set = FormatSet::ALL.retain_matching("a@example.com");
+        assert_eq!(set.name(2), None);
+        assert_eq!(set.name(3), Some("email"));
+    }
+}
@@ -0,0 +1,269 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{parse_sample, render_schema, ErrorMode, InferError, InferOptions, Node};
+
+/// A schema together with the errors of the samples that were skipped under
+/// `ErrorMode::Lenient`.
+#[derive(Debug)]
+pub struct InferResult {
+    pub schema: String,
+    pub errors: Vec<InferError>,
+}
+
+/// Incremental inference: feed samples one at a time, render the schema at any point.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SchemaInferrer {
+    root: Node,
+    options: InferOptions,
+    // Номер следующего образца — для InferError::InvalidJson
+    fed: usize,
+    // Ошибки пропущенных образцов в мягком режиме; в контрольной точке — номер и текст
+    #[serde(
+        default,
+        serialize_with = "save_errors",
+        deserialize_with = "load_errors"
+    )]
+    errors: Vec<(usize, InferError)>,
+}
+
+impl SchemaInferrer {
+    pub fn new(options: InferOptions) -> Self {
+        Self {
+            root: Node::default(),
+            options,
+            fed: 0,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Parses one JSON sample and merges it into the accumulated schema. Under
+    /// `ErrorMode::Lenient` a bad sample is recorded for `finish_with_errors`
+    /// and skipped instead of returning the error.
+    pub fn feed(&mut self, sample: &str) -> Result<(), InferError> {
+        let index = self.fed;
+        self.fed += 1;
+        let v = match parse_sample(sample, index, &self.options) {
+            Ok(v) => v,
+            Err(e) if self.options.error_mode == ErrorMode::Lenient => {
+                self.errors.push((index, e));
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        };
+        self.root.observe(&v, &self.options);
+        Ok(())
+    }
+
+    /// The node accumulated from every sample fed so far.
+    pub fn root(&self) -> &Node {
+        &self.root
+    }
+
+    pub fn options(&self) -> &InferOptions {
+        &self.options
+    }
+
+    /// Writes the accumulated state (options included) to `path` as JSON. Errors
+    /// not yet taken by `finish_with_errors` are kept as
+    /// `InferError::SkippedSample`.
+    pub fn save(&self, path: &str) -> Result<(), InferError> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(writer, self).map_err(InferError::SerializeError)
+    }
+
+    /// Restores an inferrer written by `save`; feeding can continue from there.
+    pub fn load(path: &str) -> Result<Self, InferError> {
+        let reader = BufReader::new(File::open(path)?);
+        serde_json::from_reader(reader).map_err(|source| InferError::InvalidCheckpoint {
+            path: path.to_string(),
+            source,
+        })
+    }
+
+    /// Renders the schema for everything fed so far.
+    pub fn finish(&self) -> Result<String, InferError> {
+        render_schema(&self.root, &self.options)
+    }
+
+    /// `finish` plus the errors recorded since the last call; the errors are
+    /// handed over, the accumulated state is kept.
+    pub fn finish_with_errors(&mut self) -> Result<InferResult, InferError> {
+        Ok(InferResult {
+            schema: self.finish()?,
+            errors: std::mem::take(&mut self.errors)
+                .into_iter()
+                .map(|(_, e)| e)
+                .collect(),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedError {
+    index: usize,
+    message: String,
+}
+
+fn save_errors<S: Serializer>(errors: &[(usize, InferError)], ser: S) -> Result<S::Ok, S::Error> {
+    ser.collect_seq(errors.iter().map(|(index, e)| SavedError {
+        index: *index,
+        message: e.to_string(),
+    }))
+}
+
+fn load_errors<'de, D: Deserializer<'de>>(de: D) -> Result<Vec<(usize, InferError)>, D::Error> {
+    let saved = Vec::<SavedError>::deserialize(de)?;
+    Ok(saved
+        .into_iter()
+        .map(|SavedError { index, message }| (index, InferError::SkippedSample { index, message }))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infer_schema_rs;
+
+    #[test]
+    fn streaming_matches_batch_inference() {
+        let samples = vec![
+            r#"{"id":1,"tags":["a"]}"#.to_string(),
+            r#"{"id":2,"name":"x"}"#.to_string(),
+        ];
+        let mut inferrer = SchemaInferrer::new(InferOptions::default());
+        for s in &samples {
+            inferrer.feed(s).unwrap();
+        }
+
+        assert_eq!(
+            inferrer.finish().unwrap(),
+            infer_schema_rs(&samples, InferOptions::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn checkpoint_resumes_streaming() {
+        let samples = [
+            r#"{"id":1,"tags":["a"],"e":"x@example.com","m":{"k":1.5}}"#,
+            r#"{"id":2,"name":null,"e":"y@example.com"}"#,
+            r#"{"id":3,"tags":[],"e":"z@example.com","m":{"k":2}}"#,
+            r#"{"id":4,"name":"n","e":"w@example.com"}"#,
+        ];
+        let options = InferOptions {
+            emit_numeric_bounds: true,
+            emit_string_bounds: true,
+            detect_formats: true,
+            detect_patterns: true,
+            include_examples: true,
+            examples_per_field: 2,
+            ..Default::default()
+        };
+        let path = std::env::temp_dir().join(format!("aif-checkpoint-{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let mut first = SchemaInferrer::new(options.clone());
+        for s in &samples[..2] {
+            first.feed(s).unwrap();
+        }
+        first.save(path).unwrap();
+        let mut resumed = SchemaInferrer::load(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        for s in &samples[2..] {
+            resumed.feed(s).unwrap();
+        }
+
+        let mut single = SchemaInferrer::new(options);
+        for s in &samples {
+            single.feed(s).unwrap();
+        }
+        assert_eq!(resumed.finish().unwrap(), single.finish().unwrap());
+    }
+
+    #[test]
+    fn load_rejects_garbage() {
+        let path = std::env::temp_dir().join(format!("aif-garbage-{}.json", std::process::id()));
+        std::fs::write(&path, "{").unwrap();
+        let err = SchemaInferrer::load(path.to_str().unwrap()).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(err, InferError::InvalidCheckpoint { .. }));
+    }
+
+    #[test]
+    fn feed_reports_sample_index() {
+        let mut inferrer = SchemaInferrer::default();
+        inferrer.feed("{}").unwrap();
+        let err = inferrer.feed("{").unwrap_err();
+
+        assert!(matches!(err, InferError::InvalidJson { index: 1, .. }));
+        // ошибка не портит накопленное состояние
+        inferrer.feed(r#"{"a":1}"#).unwrap();
+        assert!(inferrer.finish().unwrap().contains("\"a\""));
+    }
+
+    #[test]
+    fn lenient_mode_skips_bad_samples() {
+        let samples = [
+            r#"{"id":1}"#,
+            r#"{"id":2,"name":"x"}"#,
+            r#"{"id":3,"broken":"#,
+            r#"{"id":4,"tags":["a"]}"#,
+            r#"{"id":5,"name":"y"}"#,
+        ];
+        let options = InferOptions {
+            error_mode: ErrorMode::Lenient,
+            ..Default::default()
+        };
+        let mut inferrer = SchemaInferrer::new(options.clone());
+        for s in &samples {
+            inferrer.feed(s).unwrap();
+        }
+        let result = inferrer.finish_with_errors().unwrap();
+        let schema: serde_json::Value = serde_json::from_str(&result.schema).unwrap();
+        let mut keys: Vec<&String> = schema["properties"].as_object().unwrap().keys().collect();
+        keys.sort();
+        assert_eq!(keys, ["id", "name", "tags"]);
+        assert_eq!(result.errors.len(), 1);
+        assert!(matches!(
+            result.errors[0],
+            InferError::InvalidJson { index: 2, .. }
+        ));
+        // ошибки отдаются один раз
+        assert!(inferrer.finish_with_errors().unwrap().errors.is_empty());
+
+        // контрольная точка сохраняет ошибки номером и текстом
+        let path = std::env::temp_dir().join(format!("aif-lenient-{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        let mut first = SchemaInferrer::new(options.clone());
+        for s in &samples[..3] {
+            first.feed(s).unwrap();
+        }
+        first.save(path).unwrap();
+        let mut resumed = SchemaInferrer::load(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        for s in &samples[3..] {
+            resumed.feed(s).unwrap();
+        }
+        let restored = resumed.finish_with_errors().unwrap();
+        assert_eq!(restored.schema, result.schema);
+        assert!(matches!(
+            &restored.errors[..],
+            [InferError::SkippedSample { index: 2, message }]
+                if message.starts_with("invalid JSON in sample 2")
+        ));
+
+        let batch = crate::infer_schema_with_errors_rs(&samples, options).unwrap();
+        assert_eq!(batch.schema, result.schema);
+        assert!(matches!(
+            batch.errors[..],
+            [InferError::InvalidJson { index: 2, .. }]
+        ));
+        let strict = crate::infer_schema_with_errors_rs(&samples, InferOptions::default());
+        assert!(matches!(
+            strict,
+            Err(InferError::InvalidJson { index: 2, .. })
+        ));
+    }
+}
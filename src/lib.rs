@@ -1,11 +1,81 @@
 use ahash::{AHashMap, AHashSet};
-use pyo3::exceptions::PyValueError;
-use pyo3::prelude::*;
-use serde_json::{json, Map, Value};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Number, Value};
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-enum TypeTag {
+mod annotations;
+mod cluster;
+mod conditions;
+mod contains;
+mod content;
+mod coverage;
+mod defs;
+mod dependencies;
+mod depth;
+mod diff;
+mod drafts;
+mod error;
+mod examples;
+mod extensions;
+mod fingerprint;
+mod formats;
+mod inferrer;
+mod merge;
+mod node_diff;
+mod options;
+mod patch;
+mod patterns;
+#[cfg(not(target_arch = "wasm32"))]
+mod python;
+mod reconstruct;
+mod recursion;
+mod sql;
+mod stats;
+mod typescript;
+mod validate;
+mod visitor;
+#[cfg(feature = "wasm")]
+mod wasm;
+
+pub use annotations::Annotation;
+use content::{Base64, BooleanString, NumericString};
+pub use coverage::{
+    schema_coverage_json_rs, schema_coverage_report_rs, schema_coverage_rs, SchemaCoverage,
+};
+use dependencies::CoOccurrence;
+pub use diff::{
+    collect_json_pointers, collect_paths_with_options, diff_schemas_json_rs, diff_schemas_rs,
+    schema_similarity_rs, schema_similarity_typed_rs, CollectPathsOptions, DiffOptions,
+    KeywordChange, PathChange, PathFormat, SchemaDiff,
+};
+pub use error::InferError;
+use examples::{truncate_example, Reservoir};
+pub use extensions::strip_extensions_rs;
+pub use fingerprint::schema_fingerprint_rs;
+use formats::FormatSet;
+pub use inferrer::{InferResult, SchemaInferrer};
+pub use merge::{merge_nodes, merge_schemas_rs};
+pub use node_diff::{diff_nodes, diff_schema_nodes_json_rs, NodeDiff};
+pub use options::{
+    AdditionalProperties, ArrayMode, ConditionalRule, ConflictStrategy, ContainsCandidate,
+    ContainsRule, ErrorMode, InferOptions, NullOnlyFields, NullableStyle, SchemaDraft,
+};
+pub use patch::{apply_schema_patch_rs, schema_patch_rs};
+use patterns::{KeyPatterns, KeyShape, PatternTracker};
+pub use sql::schema_to_sql_ddl_rs;
+pub use stats::schema_stats_rs;
+pub use typescript::schema_to_typescript_rs;
+pub use validate::{validate_sample_rs, ValidationError};
+pub use visitor::{walk_node, NodeVisitor, PathCollectingVisitor};
+
+/// A JSON type observed at a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TypeTag {
     Null,
     Boolean,
     Integer,
@@ -16,7 +86,30 @@ enum TypeTag {
 }
 
 impl TypeTag {
-    fn as_str(&self) -> &'static str {
+    pub const ALL: [TypeTag; 7] = [
+        TypeTag::Null,
+        TypeTag::Boolean,
+        TypeTag::Integer,
+        TypeTag::Number,
+        TypeTag::String,
+        TypeTag::Object,
+        TypeTag::Array,
+    ];
+
+    /// The tag of a JSON value; numbers without a fraction part are integers.
+    pub fn of(v: &Value) -> TypeTag {
+        match v {
+            Value::Null => TypeTag::Null,
+            Value::Bool(_) => TypeTag::Boolean,
+            Value::Number(n) if n.is_i64() || n.is_u64() => TypeTag::Integer,
+            Value::Number(_) => TypeTag::Number,
+            Value::String(_) => TypeTag::String,
+            Value::Object(_) => TypeTag::Object,
+            Value::Array(_) => TypeTag::Array,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
         match self {
             TypeTag::Null => "null",
             TypeTag::Boolean => "boolean",
@@ -27,20 +120,259 @@ impl TypeTag {
             TypeTag::Array => "array",
         }
     }
+
+    /// Every tag, in declaration order.
+    pub fn all() -> impl Iterator<Item = TypeTag> {
+        Self::ALL.into_iter()
+    }
+}
+
+impl std::fmt::Display for TypeTag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for TypeTag {
+    type Err = InferError;
+
+    /// Parses a JSON Schema `type` name (`"integer"`, `"null"`, ...).
+    fn from_str(s: &str) -> Result<TypeTag, InferError> {
+        TypeTag::all()
+            .find(|t| t.as_str() == s)
+            .ok_or_else(|| InferError::UnknownType(s.to_string()))
+    }
+}
+
+/// Exact number of values of each type observed at a node.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TypeCounts([u64; 7]);
+
+impl TypeCounts {
+    pub fn get(&self, tag: TypeTag) -> u64 {
+        self.0[tag as usize]
+    }
+
+    fn add(&mut self, tag: TypeTag, n: u64) {
+        self.0[tag as usize] += n;
+    }
+
+    fn set(&mut self, tag: TypeTag, n: u64) {
+        self.0[tag as usize] = n;
+    }
+
+    fn merge(&mut self, other: &TypeCounts) {
+        for (count, extra) in self.0.iter_mut().zip(other.0) {
+            *count += extra;
+        }
+    }
+
+    /// Observed types with their counts, most frequent first (ties by name).
+    pub fn histogram(&self) -> Vec<(TypeTag, u64)> {
+        let mut seen: Vec<(TypeTag, u64)> = TypeTag::ALL
+            .iter()
+            .map(|t| (*t, self.get(*t)))
+            .filter(|(_, n)| *n > 0)
+            .collect();
+        seen.sort_by(|(a, x), (b, y)| y.cmp(x).then_with(|| a.as_str().cmp(b.as_str())));
+        seen
+    }
+
+    /// The most frequent type, ties broken by name.
+    pub fn dominant(&self) -> Option<TypeTag> {
+        self.histogram().first().map(|(t, _)| *t)
+    }
 }
 
-#[derive(Debug, Clone, Default)]
-struct Node {
+/// Statistics accumulated for one position in the documents (the root, a
+/// property, or the items of an array).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Node {
     // Наблюдаемые типы на этом уровне
     types: AHashSet<TypeTag>,
-    // Для объектов
-    properties: AHashMap<String, Node>,
+    // Сколько значений каждого типа видели, точно — без выборки
+    type_counts: TypeCounts,
+    // Сколько раз значение встречалось в этом узле (для свойства — сколько раз был ключ)
+    seen_count: u64,
+    // Сколько раз узел наблюдался как объект
+    sample_count: u64,
+    // В скольких образцах встретился узел: повторы внутри одного образца не считаются
+    doc_count: u64,
+    // Номер последнего образца, учтённого в doc_count; номера выдаёт корень обхода
+    last_doc: u64,
+    // Для объектов, в порядке первого появления ключа
+    properties: IndexMap<String, Node, ahash::RandomState>,
+    // Объект-словарь: ключи — данные, все значения сведены в один узел
+    map_values: Option<Box<Node>>,
+    // Общая форма ключей словаря (propertyNames)
+    map_keys: KeyShape,
+    // Кандидаты propertyNames обычного объекта; None — не отслеживаются или не сложились
+    key_patterns: Option<KeyPatterns>,
+    // Совместная встречаемость ключей; None — не отслеживается или ключей слишком много
+    co_occurrence: Option<CoOccurrence>,
     // Для массивов
     items: Option<Box<Node>>,
+    // Узлы по позициям, пока все массивы одной длины (не больше tuple_max_len)
+    tuple: Option<Vec<Node>>,
+    // Диапазон наблюдаемых чисел
+    num_min: Option<Number>,
+    num_max: Option<Number>,
+    // Длины строк в символах
+    str_min_len: Option<usize>,
+    str_max_len: Option<usize>,
+    // Сколько строковых значений видели
+    string_count: u64,
+    // Различные строковые значения; None — не отслеживаются (превышен лимит)
+    string_values: Option<AHashSet<String>>,
+    // Форматы, которым соответствовали все строки
+    formats: FormatSet,
+    // Общая форма строк для pattern; None — не отслеживается или не сложилась
+    patterns: Option<PatternTracker>,
+    // Все строки были base64; None — не отслеживается или нашлась не-base64
+    base64: Option<Base64>,
+    // Все строки записывали число; None — не отслеживается или нашлась другая строка
+    numeric_strings: Option<NumericString>,
+    // Все строки были словами истинности; None — не отслеживается или нашлась другая
+    boolean_strings: Option<BooleanString>,
+    // Число ключей у наблюдавшихся объектов
+    obj_min_props: Option<usize>,
+    obj_max_props: Option<usize>,
+    // Длины массивов
+    arr_min_items: Option<usize>,
+    arr_max_items: Option<usize>,
+    // Ни в одном массиве не было повторяющихся элементов
+    unique_items_candidate: bool,
+    // Сколько чисел видели и различные целые значения для enum; дробное значение
+    // отключает отслеживание
+    number_count: u64,
+    int_values: Option<AHashSet<i64>>,
+    // НОД модулей целых значений и уцелевшие дробные шаги (биты FLOAT_STEPS)
+    int_gcd: u64,
+    float_steps: u8,
+    // Наибольшее число знаков после запятой в записи дробных значений
+    decimal_places: u32,
+    // Выборка наблюдавшихся скалярных значений для examples
+    examples: Option<Reservoir>,
+    // Частоты скалярных значений (ключ — JSON-сериализация) для default;
+    // None — не отслеживаются или превышен enum_cap
+    value_counts: Option<AHashMap<String, u64>>,
+    // Единственное наблюдавшееся скалярное значение
+    const_candidate: Option<Value>,
+    is_const: bool,
+}
+
+/// Structural equality: the same types and, recursively, the same
+/// properties (in any order), items, map values and tuple positions.
+/// Counters, bounds and other statistics are not compared.
+impl PartialEq for Node {
+    fn eq(&self, other: &Node) -> bool {
+        self.types == other.types
+            && self.properties == other.properties
+            && self.items == other.items
+            && self.map_values == other.map_values
+            && self.tuple == other.tuple
+    }
+}
+
+impl Eq for Node {}
+
+// Где в родительском узле лежит дочерний
+enum Slot {
+    // индекс в properties: до конца обхода объекта ключи не переставляются
+    Property(usize),
+    Tuple(usize),
+    Items,
+    MapValues,
+}
+
+// Вложенные значения узла с местом, куда уходит каждое
+type Children<'v> = Vec<(Slot, &'v Value)>;
+
+// Объект или массив, чьи вложенные значения ещё обходятся
+struct Frame<'v> {
+    slot: Slot,
+    children: std::vec::IntoIter<(Slot, &'v Value)>,
+    // число свойств до этого объекта — для проверки на словарь после обхода
+    properties_before: Option<usize>,
 }
 
 impl Node {
-    fn observe(&mut self, v: &Value) {
+    fn observe(&mut self, v: &Value, opts: &InferOptions) {
+        // Обход с явным стеком: глубина документа не упирается в стек потока.
+        // Узел кадра каждый раз находится заново от корня по местам в стеке
+        // номер образца больше любого last_doc в дереве: у потомков он не превышает корневой
+        let doc = self.last_doc + 1;
+        let (children, properties_before) = self.observe_here(v, doc, opts);
+        let mut root = children.into_iter();
+        let mut stack: Vec<Frame> = Vec::new();
+        loop {
+            let next = match stack.last_mut() {
+                Some(top) => top.children.next(),
+                None => root.next(),
+            };
+            let Some((slot, value)) = next else {
+                let Some(done) = stack.pop() else {
+                    break;
+                };
+                if let (Some(before), Some(node)) = (
+                    done.properties_before,
+                    self.descend(&stack, Some(&done.slot)),
+                ) {
+                    node.maybe_collapse(before, opts);
+                }
+                continue;
+            };
+            let Some(child) = self.descend(&stack, Some(&slot)) else {
+                continue;
+            };
+            let (children, properties_before) = child.observe_here(value, doc, opts);
+            if matches!(value, Value::Array(_) | Value::Object(_)) {
+                stack.push(Frame {
+                    slot,
+                    children: children.into_iter(),
+                    properties_before,
+                });
+            }
+        }
+        if let Some(before) = properties_before {
+            self.maybe_collapse(before, opts);
+        }
+    }
+
+    fn descend(&mut self, stack: &[Frame], last: Option<&Slot>) -> Option<&mut Node> {
+        let mut node = self;
+        for slot in stack.iter().map(|f| &f.slot).chain(last) {
+            node = node.slot_mut(slot)?;
+        }
+        Some(node)
+    }
+
+    fn slot_mut(&mut self, slot: &Slot) -> Option<&mut Node> {
+        match slot {
+            Slot::Property(i) => self.properties.get_index_mut(*i).map(|(_, n)| n),
+            Slot::Tuple(i) => self.tuple.as_mut()?.get_mut(*i),
+            Slot::Items => self.items.as_deref_mut(),
+            Slot::MapValues => self.map_values.as_deref_mut(),
+        }
+    }
+
+    // Статистика самого узла; вложенные значения возвращаются для обхода
+    fn observe_here<'v>(
+        &mut self,
+        v: &'v Value,
+        doc: u64,
+        opts: &InferOptions,
+    ) -> (Children<'v>, Option<usize>) {
+        let mut children = Vec::new();
+        self.seen_count += 1;
+        if self.last_doc != doc {
+            self.last_doc = doc;
+            self.doc_count += 1;
+        }
+        self.type_counts.add(TypeTag::of(v), 1);
+        self.track_const(v);
+        self.track_example(v, opts);
+        self.track_value_count(v, opts);
         match v {
             Value::Null => {
                 self.types.insert(TypeTag::Null);
@@ -54,33 +386,848 @@ impl Node {
                 } else {
                     self.types.insert(TypeTag::Number);
                 }
+                if self
+                    .num_min
+                    .as_ref()
+                    .is_none_or(|m| cmp_numbers(n, m) == Ordering::Less)
+                {
+                    self.num_min = Some(n.clone());
+                }
+                if self
+                    .num_max
+                    .as_ref()
+                    .is_none_or(|m| cmp_numbers(n, m) == Ordering::Greater)
+                {
+                    self.num_max = Some(n.clone());
+                }
+
+                if self.number_count == 0 {
+                    self.int_values = Some(AHashSet::default());
+                    self.float_steps = u8::MAX;
+                }
+                self.number_count += 1;
+                if let Some(i) = n.as_i64() {
+                    self.int_gcd = gcd(self.int_gcd, i.unsigned_abs());
+                } else if let Some(u) = n.as_u64() {
+                    self.int_gcd = gcd(self.int_gcd, u);
+                } else if let Some(x) = n.as_f64() {
+                    self.decimal_places = self.decimal_places.max(decimal_places(n));
+                    for (bit, step) in FLOAT_STEPS.iter().enumerate() {
+                        if !is_multiple_of(x, *step) {
+                            self.float_steps &= !(1 << bit);
+                        }
+                    }
+                }
+                match n.as_i64() {
+                    Some(i) => track_distinct(&mut self.int_values, i, opts),
+                    None => self.int_values = None,
+                }
             }
-            Value::String(_) => {
+            Value::String(s) => {
                 self.types.insert(TypeTag::String);
+                let len = s.chars().count();
+                self.str_min_len = Some(self.str_min_len.map_or(len, |m| m.min(len)));
+                self.str_max_len = Some(self.str_max_len.map_or(len, |m| m.max(len)));
+
+                if self.string_count == 0 {
+                    self.string_values = Some(AHashSet::default());
+                    if opts.detect_formats {
+                        self.formats = FormatSet::ALL;
+                    }
+                    if opts.detect_patterns {
+                        self.patterns = Some(PatternTracker::new(s));
+                    }
+                    if opts.detect_base64 {
+                        self.base64 = Base64::detect(s, opts.base64_min_len);
+                    }
+                    if opts.detect_numeric_strings {
+                        self.numeric_strings = NumericString::detect(s);
+                    }
+                    if opts.detect_boolean_strings {
+                        self.boolean_strings = BooleanString::detect(
+                            s,
+                            &opts.boolean_true_strings,
+                            &opts.boolean_false_strings,
+                        );
+                    }
+                } else {
+                    if let Some(p) = &mut self.patterns {
+                        p.observe(s);
+                        if p.is_empty() {
+                            self.patterns = None;
+                        }
+                    }
+                    if let Some(b) = self.base64 {
+                        self.base64 = Base64::detect(s, opts.base64_min_len).map(|n| b.merge(n));
+                    }
+                    if let Some(k) = self.numeric_strings {
+                        self.numeric_strings = NumericString::detect(s).map(|n| k.merge(n));
+                    }
+                    if let Some(b) = &mut self.boolean_strings {
+                        if !b.observe(s, &opts.boolean_true_strings, &opts.boolean_false_strings) {
+                            self.boolean_strings = None;
+                        }
+                    }
+                }
+                self.string_count += 1;
+                // непроверенной строке формат не доверяется: сверх format_sample_size он снимается
+                if self.string_count > opts.format_sample_size {
+                    self.formats = FormatSet::default();
+                } else if !self.formats.is_empty() {
+                    self.formats = self.formats.retain_matching(s);
+                }
+                if !self
+                    .string_values
+                    .as_ref()
+                    .is_some_and(|values| values.contains(s.as_str()))
+                {
+                    track_distinct(&mut self.string_values, s.clone(), opts);
+                }
             }
             Value::Array(arr) => {
                 self.types.insert(TypeTag::Array);
-                let items_node = self.items.get_or_insert_with(|| Box::new(Node::default()));
-                for el in arr {
-                    items_node.observe(el);
+                if self.arr_min_items.is_none() {
+                    self.unique_items_candidate = true;
+                    if opts.tracks_positions() && arr.len() <= opts.tuple_max_len {
+                        self.tuple = Some(vec![Node::default(); arr.len()]);
+                    }
                 }
+                if let Some(positions) = &self.tuple {
+                    if positions.len() == arr.len() {
+                        children.extend(arr.iter().enumerate().map(|(i, el)| (Slot::Tuple(i), el)));
+                    } else {
+                        self.tuple = None;
+                    }
+                }
+                if self.unique_items_candidate
+                    && (!opts.detect_unique_items
+                        || arr.len() > opts.unique_items_max_len
+                        || !all_distinct(arr))
+                {
+                    self.unique_items_candidate = false;
+                }
+                let len = arr.len();
+                self.arr_min_items = Some(self.arr_min_items.map_or(len, |m| m.min(len)));
+                self.arr_max_items = Some(self.arr_max_items.map_or(len, |m| m.max(len)));
+                // узел элементов появляется с первым элементом: у всегда пустого массива items нет
+                if !arr.is_empty() {
+                    self.items.get_or_insert_with(Box::default);
+                }
+                children.extend(arr.iter().map(|el| (Slot::Items, el)));
             }
             Value::Object(obj) => {
                 self.types.insert(TypeTag::Object);
+                self.sample_count += 1;
+                let len = obj.len();
+                self.obj_min_props = Some(self.obj_min_props.map_or(len, |m| m.min(len)));
+                self.obj_max_props = Some(self.obj_max_props.map_or(len, |m| m.max(len)));
+                if self.map_values.is_some() {
+                    for (k, vv) in obj {
+                        self.map_keys.observe(k);
+                        children.push((Slot::MapValues, vv));
+                    }
+                    return (children, None);
+                }
+                if opts.infer_property_names && self.sample_count == 1 {
+                    self.key_patterns = Some(KeyPatterns::default());
+                }
+                if let Some(keys) = &mut self.key_patterns {
+                    if !obj.keys().all(|k| keys.observe(k)) {
+                        self.key_patterns = None;
+                    }
+                }
+                let before = self.properties.len();
                 for (k, vv) in obj {
-                    self.properties
-                        .entry(k.to_string())
-                        .or_default()
-                        .observe(vv);
+                    let index = match self.properties.get_index_of(k.as_str()) {
+                        Some(index) => index,
+                        None => self.properties.insert_full(k.clone(), Node::default()).0,
+                    };
+                    children.push((Slot::Property(index), vv));
+                }
+                if opts.detect_dependent_required && self.sample_count == 1 {
+                    self.co_occurrence = Some(CoOccurrence::default());
+                }
+                if self.properties.len() > opts.dependent_required_max_properties {
+                    self.co_occurrence = None;
+                }
+                if let Some(co) = &mut self.co_occurrence {
+                    co.observe(obj.keys());
+                }
+                return (children, Some(before));
+            }
+        }
+        (children, None)
+    }
+
+    // Сворачиваем сразу после объекта, пока ключи не съели память
+    fn maybe_collapse(&mut self, before: usize, opts: &InferOptions) {
+        if opts.detect_maps
+            && self.properties.len() > before
+            && self.properties.len() > opts.map_key_threshold
+            && (!opts.map_keys_look_like_ids || self.properties.keys().all(|k| looks_like_id(k)))
+        {
+            self.collapse_to_map();
+            // значения разных ключей одного образца сложились — образцов не больше, чем у словаря
+            if let Some(values) = &mut self.map_values {
+                values.clamp_doc_count(self.doc_count);
+            }
+        }
+    }
+
+    fn clamp_doc_count(&mut self, limit: u64) {
+        self.doc_count = self.doc_count.min(limit);
+        let limit = self.doc_count;
+        for child in self
+            .properties
+            .values_mut()
+            .chain(self.tuple.iter_mut().flatten())
+            .chain(self.items.as_deref_mut())
+            .chain(self.map_values.as_deref_mut())
+        {
+            child.clamp_doc_count(limit);
+        }
+    }
+
+    fn track_const(&mut self, v: &Value) {
+        if self.seen_count == 1 {
+            if matches!(v, Value::Bool(_) | Value::Number(_) | Value::String(_)) {
+                self.const_candidate = Some(v.clone());
+                self.is_const = true;
+            }
+        } else if self.is_const && self.const_candidate.as_ref() != Some(v) {
+            self.is_const = false;
+            self.const_candidate = None;
+        }
+    }
+
+    // Значения всех свойств сливаются в узел значений словаря
+    fn collapse_to_map(&mut self) {
+        let values = self.map_values.get_or_insert_with(Box::default);
+        for (k, child) in self.properties.drain(..) {
+            self.map_keys.observe(&k);
+            values.merge_with(child);
+        }
+    }
+
+    /// Keys of the object properties seen at this node, sorted.
+    pub fn property_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.properties.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+
+    pub fn has_property(&self, key: &str) -> bool {
+        self.properties.contains_key(key)
+    }
+
+    /// The node describing array elements, once an element was seen.
+    pub fn items_node(&self) -> Option<&Node> {
+        self.items.as_deref()
+    }
+
+    /// Types seen at this node, sorted in `TypeTag` order.
+    pub fn observed_types(&self) -> Vec<TypeTag> {
+        let mut types: Vec<TypeTag> = self.types.iter().copied().collect();
+        types.sort();
+        types
+    }
+
+    /// Descends along a dot path (`a.b`), where a trailing `[]` on a segment
+    /// (`arr[]`, `arr[].id`, `[]`) steps into the array items node.
+    pub fn get_at_path<'a>(&'a self, path: &str) -> Option<&'a Node> {
+        if path.is_empty() {
+            return Some(self);
+        }
+        let mut node = self;
+        for segment in path.split('.') {
+            let mut name = segment;
+            let mut depth = 0;
+            while let Some(rest) = name.strip_suffix("[]") {
+                name = rest;
+                depth += 1;
+            }
+            if !name.is_empty() {
+                node = node.properties.get(name)?;
+            }
+            for _ in 0..depth {
+                node = node.items.as_deref()?;
+            }
+        }
+        Some(node)
+    }
+
+    fn track_example(&mut self, v: &Value, opts: &InferOptions) {
+        if !opts.include_examples || opts.examples_per_field == 0 {
+            return;
+        }
+        let example = match v {
+            Value::String(s) => truncate_example(s, opts.example_max_len),
+            Value::Bool(_) | Value::Number(_) => v.clone(),
+            _ => return,
+        };
+        self.examples
+            .get_or_insert_with(|| Reservoir::new(opts.examples_per_field))
+            .offer(example);
+    }
+
+    fn track_value_count(&mut self, v: &Value, opts: &InferOptions) {
+        if !opts.emit_default {
+            return;
+        }
+        if self.seen_count == 1 {
+            self.value_counts = Some(AHashMap::default());
+        }
+        if matches!(v, Value::Object(_) | Value::Array(_)) {
+            return;
+        }
+        if let Some(counts) = &mut self.value_counts {
+            *counts.entry(v.to_string()).or_insert(0) += 1;
+            if counts.len() > opts.enum_cap {
+                self.value_counts = None;
+            }
+        }
+    }
+
+    // Самое частое значение, если его доля не меньше default_min_share;
+    // при равенстве частот побеждает меньшая сериализация
+    fn default_value(&self, opts: &InferOptions) -> Option<Value> {
+        if !opts.emit_default || self.seen_count == 0 {
+            return None;
+        }
+        let (key, count) = self
+            .value_counts
+            .as_ref()?
+            .iter()
+            .max_by(|(ka, ca), (kb, cb)| ca.cmp(cb).then_with(|| kb.cmp(ka)))?;
+        if (*count as f64) < opts.default_min_share * self.seen_count as f64 {
+            return None;
+        }
+        serde_json::from_str(key).ok()
+    }
+
+    // Доля наблюдений объекта, в которых был этот ключ
+    fn presence(&self, total: u64) -> f64 {
+        if self.seen_count >= total {
+            1.0
+        } else {
+            self.seen_count as f64 / total as f64
+        }
+    }
+
+    // Ключи, которые встречались в каждом наблюдении объекта (при required_threshold
+    // меньше 1.0 — хотя бы в такой доле наблюдений)
+    fn required_keys(&self, opts: &InferOptions) -> Vec<String> {
+        let drop_null_only = opts.null_only_fields != NullOnlyFields::Keep;
+        let mut keys: Vec<String> = self
+            .properties
+            .iter()
+            .filter(|(_, n)| n.presence(self.sample_count) >= opts.required_threshold)
+            .filter(|(_, n)| !(drop_null_only && n.is_null_only()))
+            .map(|(k, _)| k.clone())
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    // Небольшой закрытый набор строк или целых у поля с единственным типом
+    fn enum_values(&self, opts: &InferOptions) -> Option<Vec<Value>> {
+        if self.types.len() != 1 {
+            return None;
+        }
+        if self.types.contains(&TypeTag::String) {
+            if self.string_count < opts.enum_min_observations {
+                return None;
+            }
+            let values = self
+                .string_values
+                .as_ref()
+                .filter(|v| !v.is_empty() && v.len() < opts.enum_threshold)?;
+            let mut values: Vec<&String> = values.iter().collect();
+            values.sort();
+            Some(
+                values
+                    .into_iter()
+                    .map(|s| Value::String(s.clone()))
+                    .collect(),
+            )
+        } else if self.types.contains(&TypeTag::Integer) {
+            if self.number_count < opts.enum_min_observations {
+                return None;
+            }
+            let values = self
+                .int_values
+                .as_ref()
+                .filter(|v| !v.is_empty() && v.len() < opts.enum_threshold)?;
+            let mut values: Vec<i64> = values.iter().copied().collect();
+            values.sort();
+            Some(values.into_iter().map(Value::from).collect())
+        } else {
+            None
+        }
+    }
+
+    fn multiple_of(&self) -> Option<Value> {
+        if self.types.contains(&TypeTag::Number) {
+            // целые кратны любому из шагов, решают дробные значения
+            FLOAT_STEPS
+                .iter()
+                .enumerate()
+                .find(|(bit, _)| self.float_steps & (1 << bit) != 0)
+                .map(|(_, step)| Value::from(*step))
+        } else if self.types.contains(&TypeTag::Integer) && self.int_gcd > 1 {
+            Some(Value::from(self.int_gcd))
+        } else {
+            None
+        }
+    }
+
+    fn decimal_step(&self, opts: &InferOptions) -> Option<Value> {
+        let places = self.decimal_places;
+        if !opts.detect_decimal_places
+            || !self.types.contains(&TypeTag::Number)
+            || places == 0
+            || places > opts.decimal_max_places
+        {
+            return None;
+        }
+        // через запись 1e-n: 10f64.powi(-n) даёт 0.010000000000000002
+        format!("1e-{places}").parse::<f64>().ok().map(Value::from)
+    }
+
+    // Все целые значения — метки времени одного масштаба внутри окна
+    fn epoch_unit(&self, opts: &InferOptions) -> Option<&'static str> {
+        if !opts.detect_epoch_timestamps
+            || !self.types.contains(&TypeTag::Integer)
+            || self.types.contains(&TypeTag::Number)
+            || self.number_count < opts.epoch_min_observations
+        {
+            return None;
+        }
+        let min = self.num_min.as_ref()?.as_i64()?;
+        let max = self.num_max.as_ref()?.as_i64()?;
+        let (lo, hi) = opts.epoch_window;
+        [
+            ("unix-timestamp-seconds", 1),
+            ("unix-timestamp-milliseconds", 1000),
+        ]
+        .into_iter()
+        .find(|(_, scale)| lo.saturating_mul(*scale) <= min && max <= hi.saturating_mul(*scale))
+        .map(|(unit, _)| unit)
+    }
+
+    fn insert_numeric_bounds(&self, m: &mut Map<String, Value>, opts: &InferOptions) {
+        if !self.types.contains(&TypeTag::Integer) && !self.types.contains(&TypeTag::Number) {
+            return;
+        }
+        let zero = Number::from(0);
+        // Строго положительное/отрицательное поле обобщаем до границы по нулю
+        let exclusive = opts.exclusive_zero_bounds
+            && self.number_count >= opts.exclusive_bounds_min_observations;
+
+        // Number хранит исходное представление: целые остаются целыми
+        if let Some(min) = &self.num_min {
+            if exclusive && cmp_numbers(min, &zero) == Ordering::Greater {
+                m.insert("exclusiveMinimum".to_string(), Value::from(0));
+            } else if opts.emit_numeric_bounds {
+                m.insert("minimum".to_string(), Value::Number(min.clone()));
+            }
+        }
+        if let Some(max) = &self.num_max {
+            if exclusive && cmp_numbers(max, &zero) == Ordering::Less {
+                m.insert("exclusiveMaximum".to_string(), Value::from(0));
+            } else if opts.emit_numeric_bounds {
+                m.insert("maximum".to_string(), Value::Number(max.clone()));
+            }
+        }
+    }
+
+    // Ключевые слова, относящиеся к значениям одного типа
+    fn insert_type_keywords(
+        &self,
+        tag: TypeTag,
+        m: &mut Map<String, Value>,
+        opts: &InferOptions,
+        rendered: &mut Rendered,
+    ) {
+        match tag {
+            TypeTag::Integer | TypeTag::Number => {
+                self.insert_numeric_bounds(m, opts);
+                let step = self
+                    .decimal_step(opts)
+                    .or_else(|| self.multiple_of().filter(|_| opts.detect_multiple_of));
+                if let Some(step) = step {
+                    m.insert("multipleOf".to_string(), step);
+                }
+                if let Some(unit) = self.epoch_unit(opts) {
+                    m.insert("x-aif-semantic".to_string(), Value::from(unit));
+                }
+            }
+            TypeTag::String => {
+                if let (true, Some(min), Some(max)) =
+                    (opts.emit_string_bounds, self.str_min_len, self.str_max_len)
+                {
+                    m.insert("minLength".to_string(), Value::from(min));
+                    m.insert("maxLength".to_string(), Value::from(max));
+                }
+                // после слияния строк может оказаться больше, чем проверялось в одном узле
+                let formats = if self.string_count > opts.format_sample_size {
+                    FormatSet::default()
+                } else {
+                    self.formats
+                };
+                if let Some(format) = formats
+                    .name(self.string_count)
+                    .filter(|_| opts.detect_formats)
+                {
+                    m.insert("format".to_string(), Value::String(format.to_string()));
+                } else if opts.detect_formats && opts.ip_any_of && formats.is_mixed_ip() {
+                    m.insert(
+                        "anyOf".to_string(),
+                        json!([{ "format": "ipv4" }, { "format": "ipv6" }]),
+                    );
+                }
+                if let Some(pattern) = self
+                    .patterns
+                    .as_ref()
+                    .filter(|_| opts.detect_patterns && !m.contains_key("format"))
+                    .and_then(PatternTracker::pattern)
+                {
+                    m.insert("pattern".to_string(), Value::String(pattern));
+                }
+                if let Some(b) = self.base64.filter(|_| opts.detect_base64) {
+                    b.insert_keywords(m);
+                }
+                if let Some(k) = self.numeric_strings.filter(|_| opts.detect_numeric_strings) {
+                    if !opts.numeric_string_pattern {
+                        m.insert("x-aif-suggested-type".to_string(), Value::from(k.as_str()));
+                    } else {
+                        // тип остаётся строкой, а форму числа закрепляет pattern
+                        if m.get("type") == Some(&Value::from("string")) {
+                            m.insert("type".to_string(), json!(["string"]));
+                        }
+                        m.entry("pattern")
+                            .or_insert_with(|| Value::from(k.pattern()));
+                    }
+                }
+                if let Some(b) = self.boolean_strings.as_ref().filter(|b| {
+                    opts.detect_boolean_strings
+                        && b.is_trusted(opts.boolean_string_min_observations)
+                }) {
+                    b.insert_keywords(m);
+                }
+            }
+            TypeTag::Object => {
+                if let (true, Some(min), Some(max)) = (
+                    opts.emit_property_counts,
+                    self.obj_min_props,
+                    self.obj_max_props,
+                ) {
+                    m.insert("minProperties".to_string(), Value::from(min));
+                    m.insert("maxProperties".to_string(), Value::from(max));
+                }
+                if let Some(values) = &self.map_values {
+                    // словарь: значения описывает additionalProperties, Deny к нему не относится
+                    m.insert(
+                        "additionalProperties".to_string(),
+                        take_schema(rendered, values),
+                    );
+                    if let Some(names) = self.map_keys.property_names() {
+                        m.insert("propertyNames".to_string(), names);
+                    }
+                    return;
+                }
+                if self.properties.is_empty() {
+                    // ни одного ключа за все наблюдения — объект структурно пуст, а не непрозрачен
+                    if self.obj_max_props == Some(0) {
+                        if opts.annotate_empty_objects {
+                            m.insert("x-aif-empty".to_string(), Value::Bool(true));
+                        } else {
+                            m.entry("maxProperties").or_insert(Value::from(0));
+                        }
+                    }
+                    return;
+                }
+                let mut keys: Vec<&String> = self.properties.keys().collect();
+                if !opts.preserve_insertion_order {
+                    keys.sort();
+                }
+                let mut props = Map::new();
+                for k in keys {
+                    let child = &self.properties[k];
+                    let mut schema = take_schema(rendered, child);
+                    if let (true, Value::Object(s)) = (opts.annotate_frequency, &mut schema) {
+                        s.insert(
+                            "x-aif-frequency".to_string(),
+                            json!({ "count": child.seen_count, "total": self.sample_count }),
+                        );
+                    }
+                    if let (true, Value::Object(s)) = (opts.annotate_presence, &mut schema) {
+                        s.insert(
+                            "x-aif-presence".to_string(),
+                            Value::from(child.presence(self.sample_count)),
+                        );
+                    }
+                    if let (true, Value::Object(s)) = (opts.generate_titles, &mut schema) {
+                        s.insert("title".to_string(), Value::String(key_to_title(k)));
+                    }
+                    props.insert(k.clone(), schema);
+                }
+                m.insert("properties".to_string(), Value::Object(props));
+                if let Some(pattern) = self
+                    .key_patterns
+                    .as_ref()
+                    .filter(|_| opts.infer_property_names)
+                    .and_then(KeyPatterns::pattern)
+                {
+                    m.insert("propertyNames".to_string(), json!({ "pattern": pattern }));
+                }
+
+                let required = self.required_keys(opts);
+                if !required.is_empty() {
+                    m.insert(
+                        "required".to_string(),
+                        Value::Array(required.into_iter().map(Value::String).collect()),
+                    );
+                }
+                if let Some(co) = self
+                    .co_occurrence
+                    .as_ref()
+                    .filter(|_| opts.detect_dependent_required)
+                    .filter(|_| self.properties.len() <= opts.dependent_required_max_properties)
+                {
+                    let seen = |k: &str| self.properties.get(k).map_or(0, |n| n.seen_count);
+                    let dependencies = co.dependent_required(
+                        seen,
+                        self.sample_count,
+                        opts.dependent_required_min_support,
+                    );
+                    if !dependencies.is_empty() {
+                        // до 2019-09 зависимости ключей записывались в dependencies
+                        let keyword = match opts.draft {
+                            SchemaDraft::Draft04 | SchemaDraft::Draft07 => "dependencies",
+                            SchemaDraft::Draft201909 | SchemaDraft::Draft202012 => {
+                                "dependentRequired"
+                            }
+                        };
+                        m.insert(keyword.to_string(), json!(dependencies));
+                    }
+                }
+
+                if opts.additional_properties == AdditionalProperties::Deny {
+                    m.insert("additionalProperties".to_string(), Value::Bool(false));
+                }
+            }
+            TypeTag::Array => {
+                if let (true, Some(min), Some(max)) = (
+                    opts.emit_array_bounds,
+                    self.arr_min_items,
+                    self.arr_max_items,
+                ) {
+                    m.insert("minItems".to_string(), Value::from(min));
+                    m.insert("maxItems".to_string(), Value::from(max));
+                }
+                if opts.detect_unique_items && self.unique_items_candidate {
+                    m.insert("uniqueItems".to_string(), Value::Bool(true));
+                }
+                if let Some(positions) = self.tuple_positions(opts) {
+                    let prefix = positions.iter().map(|p| take_schema(rendered, p)).collect();
+                    // до 2020-12 позиции кортежа — массив в items, хвост закрывает additionalItems
+                    let (positions_key, rest_key) = match opts.draft {
+                        SchemaDraft::Draft202012 => ("prefixItems", "items"),
+                        SchemaDraft::Draft04 | SchemaDraft::Draft07 | SchemaDraft::Draft201909 => {
+                            ("items", "additionalItems")
+                        }
+                    };
+                    m.insert(positions_key.to_string(), Value::Array(prefix));
+                    m.insert(rest_key.to_string(), Value::Bool(false));
+                } else if let Some(items) = &self.items {
+                    m.insert("items".to_string(), take_schema(rendered, items));
                 }
             }
+            _ => {}
+        }
+    }
+
+    // Кортеж для detect_tuples — только если позиции различаются по типам, иначе
+    // хватает items; ArrayMode::PrefixItems требует лишь одного типа на позицию
+    fn tuple_positions(&self, opts: &InferOptions) -> Option<&[Node]> {
+        let positions = self.tuple.as_deref().filter(|_| opts.tracks_positions())?;
+        if self.arr_min_items != self.arr_max_items || self.arr_min_items != Some(positions.len()) {
+            return None;
+        }
+        // integer и number для однородности не различаем
+        let kinds = |p: &Node| -> AHashSet<TypeTag> {
+            p.types
+                .iter()
+                .map(|t| match t {
+                    TypeTag::Integer => TypeTag::Number,
+                    t => *t,
+                })
+                .collect()
+        };
+        let first = kinds(positions.first()?);
+        if opts.array_mode == ArrayMode::PrefixItems
+            && positions.iter().all(|p| kinds(p).len() == 1)
+        {
+            return Some(positions);
+        }
+        if !opts.detect_tuples {
+            return None;
+        }
+        positions
+            .iter()
+            .any(|p| kinds(p) != first)
+            .then_some(positions)
+    }
+
+    fn any_of_schema(
+        &self,
+        mut tags: Vec<TypeTag>,
+        opts: &InferOptions,
+        rendered: &mut Rendered,
+    ) -> Value {
+        if opts.numeric_widening && tags.contains(&TypeTag::Number) {
+            tags.retain(|t| *t != TypeTag::Integer);
+        }
+        tags.sort_by_key(|t| t.as_str());
+        let branches = tags
+            .into_iter()
+            .map(|tag| {
+                let mut b = Map::new();
+                b.insert("type".to_string(), Value::String(tag.as_str().to_string()));
+                self.insert_type_keywords(tag, &mut b, opts, rendered);
+                Value::Object(b)
+            })
+            .collect();
+        json!({ "anyOf": Value::Array(branches) })
+    }
+
+    // Объект или массив вместе с другим ненулевым типом — одной схемой не описать
+    fn mixes_structures(&self) -> bool {
+        let non_null = self.types.iter().filter(|t| **t != TypeTag::Null).count();
+        non_null > 1
+            && (self.types.contains(&TypeTag::Object) || self.types.contains(&TypeTag::Array))
+    }
+
+    // ConflictStrategy::Dominant: оставляет в tags самый частый ненулевой тип (integer
+    // и number — вместе) и null; возвращает отброшенные типы с их счётчиками
+    fn drop_minor_types(&self, tags: &mut Vec<TypeTag>) -> Vec<(TypeTag, u64)> {
+        let kind = |t: TypeTag| match t {
+            TypeTag::Integer => TypeTag::Number,
+            t => t,
+        };
+        let histogram: Vec<(TypeTag, u64)> = self
+            .type_counts
+            .histogram()
+            .into_iter()
+            .filter(|(t, _)| *t != TypeTag::Null)
+            .collect();
+        let mut totals: BTreeMap<&str, (TypeTag, u64)> = BTreeMap::new();
+        for (t, n) in &histogram {
+            totals.entry(kind(*t).as_str()).or_insert((kind(*t), 0)).1 += n;
+        }
+        // при равенстве — меньший по имени
+        let Some((dominant, _)) = totals.into_values().rev().max_by_key(|(_, n)| *n) else {
+            return Vec::new();
+        };
+        tags.retain(|t| *t == TypeTag::Null || kind(*t) == dominant);
+        histogram
+            .into_iter()
+            .filter(|(t, _)| kind(*t) != dominant)
+            .collect()
+    }
+
+    // Узлы, чьи схемы понадобятся render; true — items, описываемые через anyOf
+    fn rendered_children(&self, opts: &InferOptions) -> Vec<(&Node, bool)> {
+        let mut children: Vec<(&Node, bool)> =
+            self.properties.values().map(|c| (c, false)).collect();
+        children.extend(self.map_values.as_deref().map(|v| (v, false)));
+        match self.tuple_positions(opts) {
+            Some(positions) => children.extend(positions.iter().map(|p| (p, false))),
+            None => children.extend(
+                self.items
+                    .as_deref()
+                    .map(|i| (i, opts.items_any_of && i.mixes_structures())),
+            ),
+        }
+        children
+    }
+
+    /// Schema fragment for this node alone.
+    pub fn to_json_schema(&self, opts: &InferOptions) -> Value {
+        // Схемы строятся снизу вверх по явному стеку: глубина не упирается в стек потока
+        let mut order = Vec::new();
+        let mut stack = vec![(self, false)];
+        while let Some((node, any_of)) = stack.pop() {
+            stack.extend(node.rendered_children(opts));
+            order.push((node, any_of));
+        }
+        let mut rendered = Rendered::default();
+        for (node, any_of) in order.into_iter().rev() {
+            let schema = if any_of {
+                node.any_of_schema(node.types.iter().copied().collect(), opts, &mut rendered)
+            } else {
+                node.annotated_schema(opts, &mut rendered)
+            };
+            rendered.insert(std::ptr::from_ref(node), schema);
+        }
+        take_schema(&mut rendered, self)
+    }
+
+    fn annotated_schema(&self, opts: &InferOptions, rendered: &mut Rendered) -> Value {
+        let mut schema = self.render(opts, rendered);
+        if let (true, Value::Object(m)) = (opts.annotate_types && self.types.len() > 1, &mut schema)
+        {
+            let histogram = self.type_counts.histogram().into_iter();
+            let counts = histogram.map(|(t, n)| (t.as_str().to_string(), Value::from(n)));
+            m.insert("x-aif-types".to_string(), Value::Object(counts.collect()));
         }
+        schema
+    }
+
+    // Только null: настоящий тип ещё не встречался
+    fn is_null_only(&self) -> bool {
+        self.types.len() == 1 && self.types.contains(&TypeTag::Null)
     }
 
-    fn to_json_schema(&self) -> Value {
+    fn render(&self, opts: &InferOptions, rendered: &mut Rendered) -> Value {
+        if self.is_null_only() {
+            match opts.null_only_fields {
+                NullOnlyFields::Keep => {}
+                NullOnlyFields::Annotate => {
+                    return json!({ "type": "null", "x-aif-unknown": true });
+                }
+                NullOnlyFields::Open => return json!({}),
+            }
+        }
+        // Одно и то же скалярное значение во всех наблюдениях — схема сводится к const
+        if opts.detect_const && self.is_const && self.doc_count > 1 {
+            if let Some(c) = &self.const_candidate {
+                return json!({ "const": c });
+            }
+        }
+
+        // Каждый тип — отдельная ветвь anyOf со своими ключевыми словами
+        let mut tags: Vec<TypeTag> = self.types.iter().copied().collect();
+        if opts.numeric_widening && self.types.contains(&TypeTag::Number) {
+            tags.retain(|t| *t != TypeTag::Integer);
+        }
+        if opts.use_any_of && tags.len() > 1 {
+            return self.any_of_schema(tags, opts, rendered);
+        }
+        let mut dropped = Vec::new();
+        if self.mixes_structures() {
+            match opts.conflict_strategy {
+                ConflictStrategy::TypeArray => {}
+                ConflictStrategy::AnyOf => return self.any_of_schema(tags, opts, rendered),
+                ConflictStrategy::Dominant => dropped = self.drop_minor_types(&mut tags),
+            }
+        }
+        let is_dropped = |v: &Value| dropped.iter().any(|(t, _)| *t == TypeTag::of(v));
+
         let mut m = Map::new();
 
-        let mut types: Vec<&str> = self.types.iter().map(|t| t.as_str()).collect();
+        let mut types: Vec<&str> = tags.iter().map(|t| t.as_str()).collect();
         types.sort_by(|a, b| {
             // небольшая стабильная сортировка для одинакового вывода
             if a == b {
@@ -90,6 +1237,18 @@ impl Node {
             }
         });
 
+        // null вместе с другими типами можно вынести из массива type
+        // в draft-04 нет nullable, там null остаётся в массиве type
+        let split_null = match opts.nullable_style {
+            NullableStyle::TypeArray => false,
+            NullableStyle::Nullable => opts.draft != SchemaDraft::Draft04,
+            NullableStyle::AnyOf => true,
+        } && types.len() > 1
+            && self.types.contains(&TypeTag::Null);
+        if split_null {
+            types.retain(|t| *t != TypeTag::Null.as_str());
+        }
+
         match types.as_slice() {
             [one] => {
                 m.insert("type".to_string(), Value::String(one.to_string()));
@@ -107,19 +1266,55 @@ impl Node {
             _ => {}
         }
 
-        if self.types.contains(&TypeTag::Object) && !self.properties.is_empty() {
-            let mut props = Map::new();
-            for (k, v) in &self.properties {
-                props.insert(k.clone(), v.to_json_schema());
+        // Integer и Number делят одни и те же числовые ключевые слова
+        if tags.contains(&TypeTag::Integer) || tags.contains(&TypeTag::Number) {
+            self.insert_type_keywords(TypeTag::Number, &mut m, opts, rendered);
+        }
+        for tag in [TypeTag::String, TypeTag::Object, TypeTag::Array] {
+            if tags.contains(&tag) {
+                self.insert_type_keywords(tag, &mut m, opts, rendered);
             }
-            m.insert("properties".to_string(), Value::Object(props));
-            // MVP: без вычисления required — добавим на следующей итерации
-            // m.insert("required", Value::Array(vec![]));
         }
 
-        if self.types.contains(&TypeTag::Array) {
-            if let Some(items) = &self.items {
-                m.insert("items".to_string(), items.to_json_schema());
+        if let Some(values) = self.enum_values(opts) {
+            // enum полностью задаёт допустимые значения, type не нужен
+            m.remove("type");
+            m.insert("enum".to_string(), Value::Array(values));
+        }
+
+        if let Some(default) = self.default_value(opts).filter(|d| !is_dropped(d)) {
+            m.insert("default".to_string(), default);
+        }
+
+        if let Some(examples) = self
+            .examples
+            .as_ref()
+            .map(|r| r.values().iter().filter(|v| !is_dropped(v)))
+            .map(|kept| kept.cloned().collect::<Vec<_>>())
+            .filter(|kept| opts.include_examples && !kept.is_empty())
+        {
+            m.insert("examples".to_string(), Value::Array(examples));
+        }
+
+        if !dropped.is_empty() {
+            let counts = dropped
+                .iter()
+                .map(|(t, n)| (t.as_str().to_string(), Value::from(*n)));
+            m.insert(
+                "x-aif-dropped-types".to_string(),
+                Value::Object(counts.collect()),
+            );
+        }
+
+        if split_null {
+            match opts.nullable_style {
+                NullableStyle::AnyOf => {
+                    return json!({ "anyOf": [Value::Object(m), { "type": "null" }] });
+                }
+                NullableStyle::Nullable => {
+                    m.insert("nullable".to_string(), Value::Bool(true));
+                }
+                NullableStyle::TypeArray => {}
             }
         }
 
@@ -127,108 +1322,352 @@ impl Node {
     }
 }
 
-fn parse_samples(samples: &[String]) -> Result<Node, String> {
-    let mut root = Node::default();
-    for s in samples {
-        let v: Value = serde_json::from_str(s).map_err(|e| format!("Invalid JSON: {e}"))?;
-        root.observe(&v);
-    }
-    Ok(root)
+// Готовые схемы дочерних узлов по адресу узла, пока их не заберёт родитель
+type Rendered = AHashMap<*const Node, Value>;
+
+fn take_schema(rendered: &mut Rendered, node: &Node) -> Value {
+    rendered
+        .remove(&std::ptr::from_ref(node))
+        .unwrap_or_default()
 }
 
-fn collect_paths(schema: &Value, prefix: &str, acc: &mut AHashSet<String>) {
-    if let Some(obj) = schema.as_object() {
-        if let Some(props) = obj.get("properties").and_then(|p| p.as_object()) {
-            for (k, v) in props {
-                let next = if prefix.is_empty() {
-                    k.to_string()
-                } else {
-                    format!("{prefix}.{k}")
-                };
-                acc.insert(next.clone());
-                collect_paths(v, &next, acc);
+// firstName / first_name / userProfileURL -> First Name / First Name / User Profile URL
+fn key_to_title(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if matches!(c, '_' | '-' | ' ') {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
             }
+            continue;
         }
-        if let Some(items) = obj.get("items") {
-            let next = if prefix.is_empty() {
-                "[]".to_string()
-            } else {
-                format!("{prefix}[]")
-            };
-            acc.insert(next.clone());
-            collect_paths(items, &next, acc);
+        if let (true, Some(prev)) = (c.is_uppercase(), word.chars().last()) {
+            // граница camelCase или конец аббревиатуры перед словом (URLPath)
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if !prev.is_uppercase() || next_lower {
+                words.push(std::mem::take(&mut word));
+            }
         }
+        word.push(c);
     }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+        .iter()
+        .map(|w| {
+            let mut chars = w.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
-// Rust-native API used by integration tests
-pub fn infer_schema_rs(samples: &[String]) -> Result<String, String> {
-    let node = parse_samples(samples).map_err(|e| e)?;
-    let schema = node.to_json_schema();
-    serde_json::to_string_pretty(&json!({
-        "$schema": "https://json-schema.org/draft/2020-12/schema",
-        "type": "object",
-        "properties": schema.get("properties").cloned().unwrap_or_else(|| json!({}))
-    }))
-    .map_err(|e| format!("Serialize error: {e}"))
-}
-
-pub fn diff_schemas_rs(a: &str, b: &str) -> Result<String, String> {
-    let va: Value = serde_json::from_str(a)
-        .map_err(|e| format!("schema A parse error: {e}"))?;
-    let vb: Value = serde_json::from_str(b)
-        .map_err(|e| format!("schema B parse error: {e}"))?;
-
-    let mut ka = AHashSet::default();
-    let mut kb = AHashSet::default();
-    collect_paths(&va, "", &mut ka);
-    collect_paths(&vb, "", &mut kb);
-
-    let added: Vec<String> = kb.difference(&ka).cloned().collect();
-    let removed: Vec<String> = ka.difference(&kb).cloned().collect();
-    let common: Vec<String> = ka.intersection(&kb).cloned().collect();
-
-    let out = json!({
-        "added": added,
-        "removed": removed,
-        "common": common
-    });
-    serde_json::to_string_pretty(&out)
-        .map_err(|e| format!("Serialize error: {e}"))
-}
-
-/// infer_schema(samples: List[str]) -> str(JSON)
-#[pyfunction]
-fn infer_schema(samples: Vec<String>) -> PyResult<String> {
-    infer_schema_rs(&samples).map_err(PyValueError::new_err)
-}
-
-/// diff_schemas(a: str(JSON), b: str(JSON)) -> str(JSON)
-#[pyfunction]
-fn diff_schemas(a: String, b: String) -> PyResult<String> {
-    diff_schemas_rs(&a, &b).map_err(PyValueError::new_err)
-}
-
-#[pymodule]
-fn aif_core(_py: Python, m: &Bound<pyo3::types::PyModule>) -> PyResult<()> {
-    m.add_function(wrap_pyfunction!(infer_schema, m)?)?;
-    m.add_function(wrap_pyfunction!(diff_schemas, m)?)?;
-    Ok(())
+// Ключ похож на идентификатор: uuid или строка узнаваемой формы (user_123, 9f86d081)
+fn looks_like_id(key: &str) -> bool {
+    formats::is_uuid(key) || !PatternTracker::new(key).is_empty()
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use serde_json::Value;
+// Кандидаты для multipleOf у дробных полей, от крупного к мелкому
+const FLOAT_STEPS: [f64; 5] = [0.5, 0.25, 0.1, 0.05, 0.01];
 
-    #[test]
-    fn infer_simple_schema_object() {
-        let samples = vec![
-            r#"{"id":1,"name":"Alice","tags":["a","b"]}"#.to_string(),
-            r#"{"id":2,"name":"Bob","tags":[]}"#.to_string(),
-        ];
-        let out = infer_schema(samples).expect("infer ok");
-        let v: Value = serde_json::from_str(&out).unwrap();
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+// Знаки после запятой по исходной записи числа (нужен arbitrary_precision):
+// 5.00 -> 2, 1e2 -> 0, 1.25e1 -> 1, 1.5e-3 -> 4
+fn decimal_places(n: &Number) -> u32 {
+    let text = n.to_string();
+    let (mantissa, exp) = text
+        .split_once(['e', 'E'])
+        .map_or((text.as_str(), 0), |(m, e)| {
+            (m, e.parse::<i64>().unwrap_or(0))
+        });
+    let fraction = mantissa.split_once('.').map_or(0, |(_, f)| f.len() as i64);
+    (fraction - exp).clamp(0, i64::from(u32::MAX)) as u32
+}
+
+fn is_multiple_of(x: f64, step: f64) -> bool {
+    let k = (x / step).round();
+    (x - k * step).abs() <= 1e-9 * x.abs().max(1.0)
+}
+
+// Целые сравниваются точно (i64 и u64 помещаются в i128), остальное — как f64
+fn cmp_numbers(a: &Number, b: &Number) -> Ordering {
+    let int = |n: &Number| {
+        n.as_i64()
+            .map(i128::from)
+            .or_else(|| n.as_u64().map(i128::from))
+    };
+    match (int(a), int(b)) {
+        (Some(x), Some(y)) => x.cmp(&y),
+        _ => a
+            .as_f64()
+            .partial_cmp(&b.as_f64())
+            .unwrap_or(Ordering::Equal),
+    }
+}
+
+// Попарное структурное сравнение: вложенные объекты равны независимо от порядка ключей
+fn all_distinct(arr: &[Value]) -> bool {
+    arr.iter()
+        .enumerate()
+        .all(|(i, a)| arr[i + 1..].iter().all(|b| a != b))
+}
+
+// Добавляет значение в набор различных; при переполнении набор сбрасывается навсегда
+fn track_distinct<T: Eq + std::hash::Hash>(
+    values: &mut Option<AHashSet<T>>,
+    value: T,
+    opts: &InferOptions,
+) {
+    if let Some(set) = values {
+        set.insert(value);
+        if set.len() > opts.enum_cap {
+            *values = None;
+        }
+    }
+}
+
+// Размер куска не зависит от числа потоков, поэтому результат воспроизводим
+#[cfg(feature = "parallel")]
+const PARALLEL_CHUNK: usize = 512;
+
+#[cfg(not(feature = "parallel"))]
+fn parse_samples<S: AsRef<str>>(samples: &[S], opts: &InferOptions) -> Result<Node, InferError> {
+    let mut root = Node::default();
+    observe_samples(&mut root, samples, 0, opts)?;
+    Ok(root)
+}
+
+/// With the `parallel` feature, chunks of samples are inferred on the rayon
+/// pool and the chunk nodes are merged in input order.
+#[cfg(feature = "parallel")]
+fn parse_samples<S: AsRef<str>>(samples: &[S], opts: &InferOptions) -> Result<Node, InferError> {
+    use rayon::prelude::*;
+
+    let samples: Vec<&str> = samples.iter().map(AsRef::as_ref).collect();
+    let chunks: Vec<Result<Node, InferError>> = samples
+        .par_chunks(PARALLEL_CHUNK)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let mut node = Node::default();
+            observe_samples(&mut node, chunk, i * PARALLEL_CHUNK, opts)?;
+            Ok(node)
+        })
+        .collect();
+    let mut root = Node::default();
+    for chunk in chunks {
+        root.merge_with(chunk?);
+    }
+    Ok(root)
+}
+
+fn observe_samples<S: AsRef<str>>(
+    root: &mut Node,
+    samples: &[S],
+    offset: usize,
+    opts: &InferOptions,
+) -> Result<(), InferError> {
+    for (i, s) in samples.iter().enumerate() {
+        let v = parse_sample(s.as_ref(), offset + i, opts)?;
+        root.observe(&v, opts);
+    }
+    Ok(())
+}
+
+// Глубина проверяется до разбора: слишком глубокий документ не строится вовсе
+fn parse_sample(s: &str, index: usize, opts: &InferOptions) -> Result<Value, InferError> {
+    if let Some(limit) = opts.depth_limit {
+        depth::check_depth(s, limit)?;
+    }
+    serde_json::from_str(s).map_err(|source| InferError::InvalidJson { index, source })
+}
+
+// Rust-native API used by integration tests
+pub fn infer_schema_rs<S: AsRef<str>>(
+    samples: &[S],
+    options: InferOptions,
+) -> Result<String, InferError> {
+    infer_schema_annotated_rs(samples, options).map(|(schema, _)| schema)
+}
+
+/// `infer_schema_rs` that also returns the `annotations` keys which matched
+/// no node of the inferred schema.
+pub fn infer_schema_annotated_rs<S: AsRef<str>>(
+    samples: &[S],
+    options: InferOptions,
+) -> Result<(String, Vec<String>), InferError> {
+    let (samples, _) = skip_invalid(samples, &options);
+    infer_document(&samples, options)
+}
+
+/// `infer_schema_rs` that also returns the errors of the samples skipped
+/// under `ErrorMode::Lenient`; in strict mode the first one is returned as
+/// the error instead.
+pub fn infer_schema_with_errors_rs<S: AsRef<str>>(
+    samples: &[S],
+    options: InferOptions,
+) -> Result<InferResult, InferError> {
+    let (samples, errors) = skip_invalid(samples, &options);
+    let (schema, _) = infer_document(&samples, options)?;
+    Ok(InferResult { schema, errors })
+}
+
+// В мягком режиме плохие образцы отсеиваются заранее, их ошибки сохраняют исходные номера
+fn skip_invalid<'s, S: AsRef<str>>(
+    samples: &'s [S],
+    opts: &InferOptions,
+) -> (Vec<&'s str>, Vec<InferError>) {
+    let mut valid = Vec::with_capacity(samples.len());
+    let mut errors = Vec::new();
+    for (index, s) in samples.iter().enumerate() {
+        let s = s.as_ref();
+        if opts.error_mode == ErrorMode::Lenient {
+            if let Err(e) = parse_sample(s, index, opts) {
+                errors.push(e);
+                continue;
+            }
+        }
+        valid.push(s);
+    }
+    (valid, errors)
+}
+
+fn infer_document(
+    samples: &[&str],
+    options: InferOptions,
+) -> Result<(String, Vec<String>), InferError> {
+    let mut doc = match cluster::partition(samples, &options)? {
+        Some(p) => cluster::partition_schema(&p, &options),
+        None => root_schema(&parse_samples(samples, &options)?, &options),
+    };
+    conditions::apply_conditions(&mut doc, samples, &options)?;
+    contains::apply_contains(&mut doc, samples, &options)?;
+    finish_document(doc, &options)
+}
+
+/// `infer_schema_rs` over borrowed samples; nothing is copied before parsing.
+pub fn infer_schema_from_strs(
+    samples: &[&str],
+    options: InferOptions,
+) -> Result<String, InferError> {
+    infer_schema_rs(samples, options)
+}
+
+/// Infers a schema from JSON Lines read from `reader`; blank lines are skipped.
+pub fn infer_schema_from_reader<R: BufRead>(
+    reader: R,
+    options: InferOptions,
+) -> Result<String, InferError> {
+    let mut inferrer = SchemaInferrer::new(options);
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        inferrer.feed(&line)?;
+    }
+    inferrer.finish()
+}
+
+/// Infers a schema from a `.jsonl` file at `path`.
+pub fn infer_schema_from_jsonl_rs(path: &str, options: InferOptions) -> Result<String, InferError> {
+    infer_schema_from_reader(BufReader::new(File::open(path)?), options)
+}
+
+// Корневая схема документа вокруг выведенного узла
+fn root_schema(node: &Node, options: &InferOptions) -> Map<String, Value> {
+    let schema = node.to_json_schema(options);
+    let mut out = Map::new();
+    out.insert("$schema".to_string(), Value::from(options.draft.uri()));
+    // массив, скаляр или смесь типов в корне — схема узла целиком
+    let object_root = node.types.iter().all(|t| *t == TypeTag::Object);
+    if !object_root && !options.legacy_object_root {
+        if let Value::Object(schema) = schema {
+            out.extend(schema);
+        }
+        return out;
+    }
+    out.insert("type".to_string(), Value::from("object"));
+    out.insert(
+        "properties".to_string(),
+        schema
+            .get("properties")
+            .cloned()
+            .unwrap_or_else(|| json!({})),
+    );
+    // Остальные ключевые слова корневого объекта (required, additionalProperties, ...)
+    for key in [
+        "required",
+        "additionalProperties",
+        "propertyNames",
+        "dependentRequired",
+        "dependencies",
+        "minProperties",
+        "maxProperties",
+    ] {
+        if let Some(v) = schema.get(key) {
+            out.insert(key.to_string(), v.clone());
+        }
+    }
+    if options.additional_properties == AdditionalProperties::DenyAtRoot
+        && schema.get("properties").is_some()
+    {
+        out.insert("additionalProperties".to_string(), Value::Bool(false));
+    }
+    out
+}
+
+fn render_schema(node: &Node, options: &InferOptions) -> Result<String, InferError> {
+    finish_document(root_schema(node, options), options).map(|(schema, _)| schema)
+}
+
+// Проходы по готовому документу. $ref разрешается от его корня, поэтому $defs
+// выносятся последними; аннотированная форма с остальными уже не совпадёт.
+fn finish_document(
+    mut doc: Map<String, Value>,
+    options: &InferOptions,
+) -> Result<(String, Vec<String>), InferError> {
+    let unmatched = annotations::apply_annotations(&mut doc, &options.annotations);
+    if options.detect_recursion {
+        recursion::link_recursion(&mut doc, options);
+    }
+    if options.extract_defs {
+        let keyword = options.draft.defs_keyword();
+        defs::extract_defs(&mut doc, options.defs_min_properties, keyword);
+    }
+    if options.draft == SchemaDraft::Draft04 {
+        drafts::downgrade_to_draft04(&mut doc);
+    }
+    let schema = serde_json::to_string_pretty(&doc).map_err(InferError::SerializeError)?;
+    Ok((schema, unmatched))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(not(target_arch = "wasm32"))]
+    use crate::python::diff_schemas;
+    use serde_json::Value;
+
+    #[test]
+    fn infer_simple_schema_object() {
+        let samples = vec![
+            r#"{"id":1,"name":"Alice","tags":["a","b"]}"#.to_string(),
+            r#"{"id":2,"name":"Bob","tags":[]}"#.to_string(),
+        ];
+        let out = infer_schema_rs(&samples, InferOptions::default()).expect("infer ok");
+        let v: Value = serde_json::from_str(&out).unwrap();
 
         assert_eq!(v["type"], "object");
         assert!(v["properties"].get("id").is_some());
@@ -236,6 +1675,7 @@ mod tests {
         assert!(v["properties"].get("tags").is_some());
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
     #[test]
     fn diff_detects_added_removed_common() {
         // Схема A: только id
@@ -243,7 +1683,8 @@ mod tests {
           "$schema":"https://json-schema.org/draft/2020-12/schema",
           "type":"object",
           "properties":{"id":{"type":"integer"}}
-        }"#.to_string();
+        }"#
+        .to_string();
 
         // Схема B: id + name + tags
         let b = r#"{
@@ -254,16 +1695,17 @@ mod tests {
             "name":{"type":"string"},
             "tags":{"type":"array","items":{"type":"string"}}
           }
-        }"#.to_string();
+        }"#
+        .to_string();
 
-        let out = diff_schemas(a, b).expect("diff ok");
+        let out = diff_schemas(a, b, "paths", "dot", false, false).expect("diff ok");
         let d: Value = serde_json::from_str(&out).unwrap();
 
         let added = d["added"].as_array().unwrap();
         let added_set: std::collections::HashSet<_> =
             added.iter().filter_map(|s| s.as_str()).collect();
         assert!(added_set.contains("name"));
-        assert!(added_set.contains("tags"));   // для массивов будет ещё "tags[]", это нормально
+        assert!(added_set.contains("tags")); // для массивов будет ещё "tags[]", это нормально
 
         let removed = d["removed"].as_array().unwrap();
         assert!(removed.is_empty());
@@ -272,20 +1714,1806 @@ mod tests {
         assert!(common.iter().any(|s| s.as_str() == Some("id")));
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
     #[test]
     fn roundtrip_infer_then_diff() {
         let s1 = vec![r#"{"a":{"x":1}}"#.to_string()];
         let s2 = vec![r#"{"a":{"x":1,"y":"u"},"b":[1,2]}"#.to_string()];
 
-        let a = infer_schema(s1).unwrap();
-        let b = infer_schema(s2).unwrap();
-        let out = diff_schemas(a, b).unwrap();
+        let a = infer_schema_rs(&s1, InferOptions::default()).unwrap();
+        let b = infer_schema_rs(&s2, InferOptions::default()).unwrap();
+        let out = diff_schemas(a, b, "paths", "dot", false, false).unwrap();
         let d: Value = serde_json::from_str(&out).unwrap();
         let added = d["added"].as_array().unwrap();
 
         let added_set: std::collections::HashSet<_> =
             added.iter().filter_map(|s| s.as_str()).collect();
         assert!(added_set.contains("a.y"));
-        assert!(added_set.contains("b"));      // и, скорее всего, "b[]" тоже появится
+        assert!(added_set.contains("b")); // и, скорее всего, "b[]" тоже появится
+    }
+
+    #[test]
+    fn required_lists_keys_present_in_every_sample() {
+        let samples = vec![
+            r#"{"id":1,"name":"Alice","email":"a@x.io"}"#.to_string(),
+            r#"{"id":2,"name":"Bob"}"#.to_string(),
+            r#"{"name":"Carol","id":3}"#.to_string(),
+        ];
+        let out = infer_schema_rs(&samples, InferOptions::default()).unwrap();
+        let v: Value = serde_json::from_str(&out).unwrap();
+
+        assert_eq!(v["required"], serde_json::json!(["id", "name"]));
+    }
+
+    #[test]
+    fn required_is_counted_per_object_node() {
+        // name есть в каждом объекте user, но сам user встречается не всегда
+        let samples = vec![
+            r#"{"user":{"name":"a","age":1},"items":[{"sku":"x","qty":1},{"sku":"y"}]}"#
+                .to_string(),
+            r#"{"user":{"name":"b"}}"#.to_string(),
+            r#"{"other":true}"#.to_string(),
+        ];
+        let out = infer_schema_rs(&samples, InferOptions::default()).unwrap();
+        let v: Value = serde_json::from_str(&out).unwrap();
+
+        assert!(v.get("required").is_none());
+        assert_eq!(
+            v["properties"]["user"]["required"],
+            serde_json::json!(["name"])
+        );
+        assert_eq!(
+            v["properties"]["items"]["items"]["required"],
+            serde_json::json!(["sku"])
+        );
+    }
+
+    #[test]
+    fn required_is_omitted_when_no_key_qualifies() {
+        let samples = vec![r#"{"a":1}"#.to_string(), r#"{"b":2}"#.to_string()];
+        let out = infer_schema_rs(&samples, InferOptions::default()).unwrap();
+        let v: Value = serde_json::from_str(&out).unwrap();
+
+        assert!(v.get("required").is_none());
+    }
+
+    // Выведенная схема как Value — общая для тестов всех модулей
+    pub(crate) fn infer_with<S: AsRef<str>>(samples: &[S], options: InferOptions) -> Value {
+        serde_json::from_str(&infer_schema_rs(samples, options).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn required_threshold_counts_each_object() {
+        // id нет в одном образце из десяти; meta есть в двух, meta.v — в одной из них
+        let mut samples: Vec<String> = (0..9).map(|i| format!(r#"{{"id":{i}}}"#)).collect();
+        samples.push(r#"{"meta":{"v":1,"w":2}}"#.to_string());
+        samples[0] = r#"{"id":0,"meta":{"w":3}}"#.to_string();
+        let samples: Vec<&str> = samples.iter().map(String::as_str).collect();
+
+        let v = infer_with(&samples, InferOptions::default());
+        assert!(v.get("required").is_none());
+        assert_eq!(v["properties"]["meta"]["required"], json!(["w"]));
+
+        let opts = InferOptions {
+            required_threshold: 0.9,
+            annotate_presence: true,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts.clone());
+        assert_eq!(v["required"], json!(["id"]));
+        assert_eq!(v["properties"]["id"]["x-aif-presence"], 0.9);
+        assert_eq!(v["properties"]["meta"]["x-aif-presence"], 0.2);
+        // meta.v сравнивается с двумя объектами meta, а не с десятью образцами
+        let meta = &v["properties"]["meta"];
+        assert_eq!(meta["properties"]["v"]["x-aif-presence"], 0.5);
+        assert_eq!(meta["required"], json!(["w"]));
+
+        let half = InferOptions {
+            required_threshold: 0.5,
+            ..opts
+        };
+        let v = infer_with(&samples, half);
+        assert_eq!(v["properties"]["meta"]["required"], json!(["v", "w"]));
+        assert!(v["properties"]["id"].get("x-aif-presence").is_some());
+        let stripped: Value = serde_json::from_str(
+            &strip_extensions_rs(&serde_json::to_string(&v).unwrap()).unwrap(),
+        )
+        .unwrap();
+        assert!(stripped["properties"]["id"].get("x-aif-presence").is_none());
+    }
+
+    #[test]
+    fn nullable_style_any_of_and_nullable() {
+        let samples = [
+            r#"{"name":"a","tags":["x",null],"id":1}"#,
+            r#"{"name":null,"tags":[],"id":2}"#,
+        ];
+
+        let v = infer_with(&samples, InferOptions::default());
+        assert_eq!(
+            v["properties"]["name"]["type"],
+            serde_json::json!(["null", "string"])
+        );
+
+        let v = infer_with(
+            &samples,
+            InferOptions {
+                nullable_style: NullableStyle::AnyOf,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            v["properties"]["name"],
+            serde_json::json!({"anyOf": [{"type": "string"}, {"type": "null"}]})
+        );
+        assert_eq!(
+            v["properties"]["tags"]["items"],
+            serde_json::json!({"anyOf": [{"type": "string"}, {"type": "null"}]})
+        );
+        assert_eq!(
+            v["properties"]["id"],
+            serde_json::json!({"type": "integer"})
+        );
+
+        let v = infer_with(
+            &samples,
+            InferOptions {
+                nullable_style: NullableStyle::Nullable,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            v["properties"]["name"],
+            serde_json::json!({"type": "string", "nullable": true})
+        );
+        assert_eq!(
+            v["properties"]["id"],
+            serde_json::json!({"type": "integer"})
+        );
+    }
+
+    #[test]
+    fn numeric_bounds_are_opt_in() {
+        let samples = [r#"{"n":3,"x":0.5}"#, r#"{"n":-7,"x":2}"#, r#"{"n":10}"#];
+
+        let v = infer_with(&samples, InferOptions::default());
+        assert!(v["properties"]["n"].get("minimum").is_none());
+
+        let v = infer_with(
+            &samples,
+            InferOptions {
+                emit_numeric_bounds: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(v["properties"]["n"]["minimum"], serde_json::json!(-7));
+        assert_eq!(v["properties"]["n"]["maximum"], serde_json::json!(10));
+        assert_eq!(v["properties"]["x"]["minimum"], serde_json::json!(0.5));
+        assert_eq!(v["properties"]["x"]["maximum"], serde_json::json!(2));
+    }
+
+    #[test]
+    fn string_length_bounds() {
+        let samples = [
+            r#"{"s":""}"#,
+            r#"{"s":"a much longer string value"}"#,
+            r#"{"s":"mid"}"#,
+        ];
+        let v = infer_with(&samples, InferOptions::default());
+        assert!(v["properties"]["s"].get("minLength").is_none());
+
+        let v = infer_with(
+            &samples,
+            InferOptions {
+                emit_string_bounds: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(v["properties"]["s"]["minLength"], serde_json::json!(0));
+        assert_eq!(v["properties"]["s"]["maxLength"], serde_json::json!(26));
+    }
+
+    #[test]
+    fn string_bounds_count_chars_and_ignore_other_types() {
+        let samples = [r#"{"s":"héllo"}"#, r#"{"s":"日本"}"#, r#"{"s":123456789}"#];
+        let v = infer_with(
+            &samples,
+            InferOptions {
+                emit_string_bounds: true,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(v["properties"]["s"]["minLength"], serde_json::json!(2));
+        assert_eq!(v["properties"]["s"]["maxLength"], serde_json::json!(5));
+    }
+
+    #[test]
+    fn enum_for_low_cardinality_strings() {
+        let statuses = ["active", "paused", "deleted", "active", "active", "paused"];
+        let samples: Vec<String> = statuses
+            .iter()
+            .enumerate()
+            .map(|(i, s)| format!(r#"{{"status":"{s}","name":"user-{i}"}}"#))
+            .collect();
+        let samples: Vec<&str> = samples.iter().map(|s| s.as_str()).collect();
+        let opts = || InferOptions {
+            enum_min_observations: 5,
+            ..Default::default()
+        };
+
+        let v = infer_with(&samples, opts());
+        assert_eq!(
+            v["properties"]["status"],
+            serde_json::json!({"enum": ["active", "deleted", "paused"]})
+        );
+        // 6 различных имён — больше порога
+        let v = infer_with(
+            &samples,
+            InferOptions {
+                enum_threshold: 5,
+                ..opts()
+            },
+        );
+        assert!(v["properties"]["name"].get("enum").is_none());
+        // порог строгий: три значения при пороге 3 — уже не enum
+        let v = infer_with(
+            &samples,
+            InferOptions {
+                enum_threshold: 3,
+                enum_cap: 32,
+                ..opts()
+            },
+        );
+        assert!(v["properties"]["status"].get("enum").is_none());
+        let v = infer_with(
+            &samples,
+            InferOptions {
+                enum_threshold: 4,
+                enum_cap: 3,
+                ..opts()
+            },
+        );
+        assert_eq!(
+            v["properties"]["status"]["enum"].as_array().unwrap().len(),
+            3
+        );
+        // лимит ниже порога: после превышения лимита набор значений сбрасывается
+        let v = infer_with(
+            &samples,
+            InferOptions {
+                enum_cap: 2,
+                enum_threshold: 20,
+                ..opts()
+            },
+        );
+        assert!(v["properties"]["status"].get("enum").is_none());
+        // слишком мало наблюдений
+        let v = infer_with(&samples[..3], opts());
+        assert!(v["properties"]["status"].get("enum").is_none());
+    }
+
+    #[test]
+    fn const_for_repeated_scalar_values() {
+        let samples = [
+            r#"{"v":2,"kind":"event","ok":true,"n":1,"obj":{"a":1},"arr":[1]}"#,
+            r#"{"v":2,"kind":"event","ok":true,"n":2,"obj":{"a":1},"arr":[1]}"#,
+        ];
+        let v = infer_with(&samples, InferOptions::default());
+        let p = &v["properties"];
+
+        assert_eq!(p["v"]["const"], serde_json::json!(2));
+        assert_eq!(p["kind"]["const"], serde_json::json!("event"));
+        assert_eq!(p["ok"]["const"], serde_json::json!(true));
+        assert!(p["n"].get("const").is_none());
+        assert!(p["obj"].get("const").is_none());
+        assert!(p["arr"].get("const").is_none());
+        // вложенные скаляры при этом могут быть const
+        assert_eq!(p["obj"]["properties"]["a"]["const"], serde_json::json!(1));
+
+        // одного наблюдения недостаточно
+        let v = infer_with(&samples[..1], InferOptions::default());
+        assert!(v["properties"]["v"].get("const").is_none());
+
+        // повторы внутри одного образца — всё ещё один образец
+        let v = infer_with(
+            &[r#"{"rows":[{"v":1},{"v":1}],"t":["a","a"]}"#],
+            InferOptions::default(),
+        );
+        let p = &v["properties"];
+        assert_eq!(p["rows"]["items"]["properties"]["v"]["type"], "integer");
+        assert_eq!(p["t"]["items"], serde_json::json!({"type": "string"}));
+        let v = infer_with(
+            &[r#"{"m":{"x":{"k":1},"y":{"k":1}}}"#],
+            InferOptions {
+                detect_maps: true,
+                map_key_threshold: 1,
+                ..Default::default()
+            },
+        );
+        let values = &v["properties"]["m"]["additionalProperties"];
+        assert_eq!(values["properties"]["k"]["type"], "integer");
+        let v = infer_with(
+            &[r#"{"t":["a","a"]}"#, r#"{"t":["a"]}"#],
+            InferOptions::default(),
+        );
+        assert_eq!(v["properties"]["t"]["items"]["const"], "a");
+
+        let v = infer_with(
+            &samples,
+            InferOptions {
+                detect_const: false,
+                ..Default::default()
+            },
+        );
+        assert!(v["properties"]["v"].get("const").is_none());
+    }
+
+    #[test]
+    fn enum_for_small_integer_sets_and_mid_stream_overflow() {
+        let samples: Vec<String> = (0..30)
+            .map(|i| {
+                format!(
+                    r#"{{"code":{},"score":{},"ratio":{}}}"#,
+                    [200, 404, 500][i % 3],
+                    if i < 20 { i % 2 } else { i },
+                    if i == 0 {
+                        "0.5".to_string()
+                    } else {
+                        (i % 2).to_string()
+                    }
+                )
+            })
+            .collect();
+        let samples: Vec<&str> = samples.iter().map(|s| s.as_str()).collect();
+        let opts = || InferOptions {
+            enum_threshold: 5,
+            ..Default::default()
+        };
+
+        let v = infer_with(&samples, opts());
+        assert_eq!(
+            v["properties"]["code"]["enum"],
+            serde_json::json!([200, 404, 500])
+        );
+        assert!(v["properties"]["code"].get("type").is_none());
+        // первые 20 значений укладывались в порог, остальные — нет
+        assert!(v["properties"]["score"].get("enum").is_none());
+        assert_eq!(v["properties"]["score"]["type"], "integer");
+        // дробное значение отключает числовой enum
+        assert!(v["properties"]["ratio"].get("enum").is_none());
+
+        let v = infer_with(&samples[..20], opts());
+        assert_eq!(v["properties"]["score"]["enum"], serde_json::json!([0, 1]));
+    }
+
+    #[test]
+    fn boolean_strings_get_a_suggested_type() {
+        let samples: Vec<String> = (0..4)
+            .map(|i| {
+                json!({
+                    "flag": (["true", "False", "yes", "no"][i]),
+                    "active": (["Y", "y", "Y", "y"][i]),
+                    "legacy": (["yes", "Yes", "YES", "yes"][i]),
+                    "word": (["true", "maybe", "no", "yes"][i]),
+                })
+                .to_string()
+            })
+            .collect();
+        let samples: Vec<&str> = samples.iter().map(String::as_str).collect();
+        let opts = InferOptions {
+            detect_boolean_strings: true,
+            boolean_string_min_observations: 5,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts.clone());
+        assert_eq!(
+            v["properties"]["flag"],
+            json!({
+                "type": "string",
+                "x-aif-suggested-type": "boolean",
+                "enum": ["False", "no", "true", "yes"]
+            })
+        );
+        assert!(v["properties"]["word"]
+            .get("x-aif-suggested-type")
+            .is_none());
+        // только одно значение истинности и мало наблюдений — похоже на обычный enum
+        assert_eq!(v["properties"]["legacy"], json!({"type": "string"}));
+        let v = infer_with(
+            &samples,
+            InferOptions {
+                boolean_string_min_observations: 4,
+                boolean_true_strings: vec!["y".to_string()],
+                boolean_false_strings: vec!["n".to_string()],
+                ..opts.clone()
+            },
+        );
+        assert_eq!(v["properties"]["active"]["enum"], json!(["Y", "y"]));
+        assert!(v["properties"]["flag"].get("enum").is_none());
+
+        assert!(
+            infer_with(&samples, InferOptions::default())["properties"]["flag"]
+                .get("x-aif-suggested-type")
+                .is_none()
+        );
+        let back = Node::from_json_schema(&json!({
+            "type": "string", "x-aif-suggested-type": "boolean", "enum": ["no", "yes"]
+        }))
+        .unwrap();
+        assert_eq!(
+            back.to_json_schema(&opts)["x-aif-suggested-type"],
+            "boolean"
+        );
+    }
+
+    #[test]
+    fn decimal_places_follow_the_written_literal() {
+        let places = |s: &str| decimal_places(&serde_json::from_str::<Number>(s).unwrap());
+        assert_eq!(places("5.00"), 2);
+        assert_eq!(places("0.1"), 1);
+        assert_eq!(places("1e2"), 0);
+        assert_eq!(places("1.25E1"), 1);
+        assert_eq!(places("1.5e-3"), 4);
+        assert_eq!(places("-12.345"), 3);
+
+        let samples = [
+            r#"{"price":5.00,"rate":0.125,"big":1e2,"pi":3.14159265,"mixed":2}"#,
+            r#"{"price":19.9,"rate":1.5e-1,"big":2.5e1,"pi":2.71828182,"mixed":0.1}"#,
+        ];
+        let opts = InferOptions {
+            detect_decimal_places: true,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts.clone());
+        let step = |p: &str| v["properties"][p].get("multipleOf").cloned();
+        assert_eq!(step("price"), Some(json!(0.01)));
+        assert_eq!(step("rate"), Some(json!(0.001)));
+        // 1e2 и 2.5e1 — целые значения в экспоненциальной записи
+        assert_eq!(step("big"), None);
+        // восемь знаков — это не фиксированная точность
+        assert_eq!(step("pi"), None);
+        assert_eq!(step("mixed"), Some(json!(0.1)));
+        assert!(
+            infer_with(&samples, InferOptions::default())["properties"]["price"]
+                .get("multipleOf")
+                .is_none()
+        );
+
+        let back = Node::from_json_schema(&v["properties"]["price"]).unwrap();
+        assert_eq!(back.to_json_schema(&opts)["multipleOf"], json!(0.01));
+    }
+
+    #[test]
+    fn epoch_timestamps_in_seconds_or_milliseconds() {
+        let samples: Vec<String> = (0..5)
+            .map(|i| {
+                json!({
+                    "created": 1_700_000_000 + i * 3600,
+                    "created_ms": 1_700_000_000_000_i64 + i,
+                    "count": i,
+                    "old": 900_000_000 + i,
+                    "mixed": if i == 0 { json!(1.5e9) } else { json!(1_600_000_000 + i) },
+                })
+                .to_string()
+            })
+            .collect();
+        let samples: Vec<&str> = samples.iter().map(String::as_str).collect();
+        let opts = InferOptions {
+            detect_epoch_timestamps: true,
+            epoch_min_observations: 5,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts.clone());
+        let semantic = |p: &str| v["properties"][p].get("x-aif-semantic").cloned();
+        assert_eq!(semantic("created"), Some(json!("unix-timestamp-seconds")));
+        assert_eq!(
+            semantic("created_ms"),
+            Some(json!("unix-timestamp-milliseconds"))
+        );
+        assert_eq!(semantic("count"), None);
+        // 1998 год — раньше окна по умолчанию
+        assert_eq!(semantic("old"), None);
+        assert_eq!(semantic("mixed"), None);
+
+        let wider = InferOptions {
+            epoch_window: (0, 4_102_444_800),
+            ..opts.clone()
+        };
+        let v = infer_with(&samples, wider);
+        assert_eq!(
+            v["properties"]["old"]["x-aif-semantic"],
+            "unix-timestamp-seconds"
+        );
+        let fewer = InferOptions {
+            epoch_min_observations: 6,
+            ..opts
+        };
+        assert!(infer_with(&samples, fewer)["properties"]["created"]
+            .get("x-aif-semantic")
+            .is_none());
+    }
+
+    #[test]
+    fn type_tags_display_parse_and_list() {
+        let all: Vec<TypeTag> = TypeTag::all().collect();
+        assert_eq!(all.len(), 7);
+        for tag in all {
+            assert_eq!(tag.to_string().parse::<TypeTag>().unwrap(), tag);
+        }
+        assert_eq!(TypeTag::Integer.to_string(), "integer");
+        assert!(matches!(
+            "Integer".parse::<TypeTag>(),
+            Err(InferError::UnknownType(name)) if name == "Integer"
+        ));
+
+        let root = parse_samples(
+            &[r#"{"v":"a"}"#, r#"{"v":null}"#, r#"{"v":2}"#],
+            &InferOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            root.get_at_path("v").unwrap().observed_types(),
+            [TypeTag::Null, TypeTag::Integer, TypeTag::String]
+        );
+    }
+
+    #[test]
+    fn node_accessors() {
+        let root = parse_samples(
+            &[
+                r#"{"zeta":1,"alpha":[{"k":true}],"mid":null}"#,
+                r#"{"alpha":[]}"#,
+            ],
+            &InferOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(root.property_names(), ["alpha", "mid", "zeta"]);
+        assert!(root.has_property("mid"));
+        assert!(!root.has_property("k"));
+        assert!(root.items_node().is_none());
+        let alpha = root.get_at_path("alpha").unwrap();
+        assert_eq!(alpha.observed_types(), [TypeTag::Array]);
+        let items = alpha.items_node().unwrap();
+        assert_eq!(items.property_names(), ["k"]);
+        assert_eq!(items.observed_types(), [TypeTag::Object]);
+    }
+
+    #[test]
+    fn node_equality_is_structural() {
+        let samples = [
+            r#"{"id":1,"tags":["a"],"meta":{"x":true,"y":null}}"#,
+            r#"{"id":2.5,"meta":{"y":"s"},"extra":[]}"#,
+        ];
+        let opts = InferOptions::default();
+        let first = parse_samples(&samples, &opts).unwrap();
+        assert_eq!(first, parse_samples(&samples, &opts).unwrap());
+        // другой порядок образцов меняет порядок ключей, но не структуру
+        let reversed = parse_samples(&[samples[1], samples[0]], &opts).unwrap();
+        assert_ne!(
+            first.properties.keys().collect::<Vec<_>>(),
+            reversed.properties.keys().collect::<Vec<_>>()
+        );
+        assert_eq!(first, reversed);
+        // другие значения тех же типов — та же структура
+        let same_shape = [
+            r#"{"id":7,"tags":["zzz"],"meta":{"x":false,"y":null}}"#,
+            r#"{"id":0.1,"meta":{"y":""},"extra":[]}"#,
+        ];
+        assert_eq!(first, parse_samples(&same_shape, &opts).unwrap());
+        let other = parse_samples(&[r#"{"id":1,"tags":[1]}"#], &opts).unwrap();
+        assert_ne!(first, other);
+    }
+
+    #[test]
+    fn integer_and_string_enums_share_thresholds() {
+        let samples: Vec<String> = (0..6)
+            .map(|i| json!({ "status": i % 3 + 1, "kind": (["a", "b", "c"][i % 3]) }).to_string())
+            .collect();
+        let samples: Vec<&str> = samples.iter().map(String::as_str).collect();
+        let opts = |enum_min_observations, enum_threshold| InferOptions {
+            enum_min_observations,
+            enum_threshold,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts(6, 4));
+        assert_eq!(v["properties"]["status"]["enum"], json!([1, 2, 3]));
+        assert_eq!(v["properties"]["kind"]["enum"], json!(["a", "b", "c"]));
+        // те же пороги не пропускают ни один из двух
+        for (min, threshold) in [(7, 4), (6, 3)] {
+            let v = infer_with(&samples, opts(min, threshold));
+            assert!(v["properties"]["status"].get("enum").is_none());
+            assert!(v["properties"]["kind"].get("enum").is_none());
+        }
+    }
+
+    #[test]
+    fn const_replaces_type_schema_for_discriminators() {
+        let samples = [
+            r#"{"type":"event","payload":{"id":1}}"#,
+            r#"{"type":"event","payload":{"id":2}}"#,
+            r#"{"type":"event","payload":{"id":3}}"#,
+        ];
+        let v = infer_with(&samples, InferOptions::default());
+
+        assert_eq!(
+            v["properties"]["type"],
+            serde_json::json!({"const": "event"})
+        );
+        assert_eq!(
+            v["properties"]["payload"]["properties"]["id"]["type"],
+            "integer"
+        );
+    }
+
+    #[test]
+    fn date_time_format_detection() {
+        let samples = [
+            r#"{"at":"2024-06-01T13:45:00Z","note":"2024-06-01T13:45:00Z"}"#,
+            r#"{"at":"2024-06-02T08:00:00.5+02:00","note":"yesterday"}"#,
+            r#"{"at":"2024-06-03T10:00:00Z","note":"2024-06-03T10:00:00Z"}"#,
+        ];
+        let opts = || InferOptions {
+            detect_formats: true,
+            ..Default::default()
+        };
+
+        let v = infer_with(&samples, opts());
+        assert_eq!(v["properties"]["at"]["format"], "date-time");
+        // одно неподходящее значение сбрасывает кандидата навсегда
+        assert!(v["properties"]["note"].get("format").is_none());
+
+        let v = infer_with(&samples, InferOptions::default());
+        assert!(v["properties"]["at"].get("format").is_none());
+    }
+
+    #[test]
+    fn use_any_of_splits_multi_type_fields() {
+        let samples = [
+            r#"{"v":1,"o":{"a":1},"n":null}"#,
+            r#"{"v":"x","o":[true],"n":"s"}"#,
+        ];
+        let opts = InferOptions {
+            use_any_of: true,
+            nullable_style: NullableStyle::Nullable,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts);
+
+        assert_eq!(
+            v["properties"]["v"],
+            serde_json::json!({"anyOf": [{"type": "integer"}, {"type": "string"}]})
+        );
+        // у каждой ветви свои properties/items
+        assert_eq!(
+            v["properties"]["o"],
+            serde_json::json!({"anyOf": [
+                {"type": "array", "items": {"type": "boolean"}},
+                {"type": "object", "properties": {"a": {"type": "integer"}}, "required": ["a"]}
+            ]})
+        );
+        assert_eq!(
+            v["properties"]["n"],
+            serde_json::json!({"anyOf": [{"type": "null"}, {"type": "string"}]})
+        );
+    }
+
+    #[test]
+    fn property_counts_for_objects() {
+        let samples = [
+            r#"{"m":{"a":1,"b":2},"e":{},"x":1}"#,
+            r#"{"m":{"c":3},"e":{},"x":"s"}"#,
+        ];
+        let opts = InferOptions {
+            emit_property_counts: true,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts);
+
+        assert_eq!(v["properties"]["m"]["minProperties"], serde_json::json!(1));
+        assert_eq!(v["properties"]["m"]["maxProperties"], serde_json::json!(2));
+        assert_eq!(
+            v["properties"]["e"],
+            serde_json::json!({"type": "object", "minProperties": 0, "maxProperties": 0})
+        );
+        // поле ни разу не было объектом
+        assert!(v["properties"]["x"].get("minProperties").is_none());
+    }
+
+    #[test]
+    fn property_counts_span_two_to_five_keys() {
+        let samples = [r#"{"a":1,"b":2}"#, r#"{"a":1,"b":2,"c":3,"d":4,"e":5}"#];
+        let opts = InferOptions {
+            emit_property_counts: true,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts);
+        assert_eq!(v["minProperties"], 2);
+        assert_eq!(v["maxProperties"], 5);
+        let v = infer_with(&samples, InferOptions::default());
+        assert!(v.get("minProperties").is_none());
+    }
+
+    #[test]
+    fn null_only_fields_modes() {
+        let samples = [
+            r#"{"id":1,"gone":null,"rows":[null],"meta":{"owner":null,"n":1}}"#,
+            r#"{"id":2,"gone":null,"rows":[null,null],"meta":{"owner":null,"n":2}}"#,
+        ];
+        let v = infer_with(&samples, InferOptions::default());
+        assert_eq!(v["properties"]["gone"], json!({"type": "null"}));
+        assert_eq!(v["required"], json!(["gone", "id", "meta", "rows"]));
+
+        let mode = |null_only_fields| InferOptions {
+            null_only_fields,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, mode(NullOnlyFields::Annotate));
+        let unknown = json!({"type": "null", "x-aif-unknown": true});
+        assert_eq!(v["properties"]["gone"], unknown);
+        assert_eq!(v["properties"]["rows"]["items"], unknown);
+        assert_eq!(v["properties"]["meta"]["properties"]["owner"], unknown);
+        assert_eq!(v["required"], json!(["id", "meta", "rows"]));
+        assert_eq!(v["properties"]["meta"]["required"], json!(["n"]));
+
+        let v = infer_with(&samples, mode(NullOnlyFields::Open));
+        assert_eq!(v["properties"]["gone"], json!({}));
+        assert_eq!(v["properties"]["rows"]["items"], json!({}));
+        assert_eq!(v["properties"]["meta"]["properties"]["owner"], json!({}));
+        assert_eq!(v["properties"]["meta"]["required"], json!(["n"]));
+    }
+
+    #[test]
+    fn always_empty_objects_are_marked() {
+        let samples = [
+            r#"{"e":{},"o":{"a":1},"n":null}"#,
+            r#"{"e":{},"o":{},"n":{}}"#,
+        ];
+        let v = infer_with(&samples, InferOptions::default());
+        assert_eq!(
+            v["properties"]["e"],
+            json!({"type": "object", "maxProperties": 0})
+        );
+        assert!(v["properties"]["o"].get("maxProperties").is_none());
+        assert_eq!(v["properties"]["n"]["maxProperties"], 0);
+
+        let opts = InferOptions {
+            annotate_empty_objects: true,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts.clone());
+        assert_eq!(
+            v["properties"]["e"],
+            json!({"type": "object", "x-aif-empty": true})
+        );
+        let back = Node::from_json_schema(&v["properties"]["e"]).unwrap();
+        assert_eq!(
+            back.to_json_schema(&InferOptions::default()),
+            json!({"type": "object", "maxProperties": 0})
+        );
+        // непрозрачный объект из схемы пустым не считается
+        let opaque = Node::from_json_schema(&json!({"type": "object"})).unwrap();
+        assert_eq!(opaque.to_json_schema(&opts), json!({"type": "object"}));
+    }
+
+    #[test]
+    fn pattern_for_shared_string_shapes() {
+        let samples = [
+            r#"{"code":"AB-1234","id":"ord_17","hash":"9f86d081","name":"x"}"#,
+            r#"{"code":"QX-0042","id":"ord_9","hash":"2c26b46b","name":"AB-1"}"#,
+        ];
+        let opts = InferOptions {
+            detect_patterns: true,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts);
+
+        assert_eq!(v["properties"]["code"]["pattern"], "^[A-Z]{2}-[0-9]{4}$");
+        assert_eq!(v["properties"]["id"]["pattern"], "^ord_[0-9]{1,2}$");
+        assert_eq!(v["properties"]["hash"]["pattern"], "^[0-9a-f]{8}$");
+        assert!(v["properties"]["name"].get("pattern").is_none());
+
+        let v = infer_with(&samples, InferOptions::default());
+        assert!(v["properties"]["code"].get("pattern").is_none());
+
+        // различных значений больше порога enum — каждое всё равно проверено
+        let mut ids: Vec<String> = (0..50).map(|i| format!(r#"{{"id":"ord_{i}"}}"#)).collect();
+        let opts = InferOptions {
+            detect_patterns: true,
+            ..Default::default()
+        };
+        let v = infer_with(&ids, opts.clone());
+        assert_eq!(v["properties"]["id"]["pattern"], "^ord_[0-9]{1,2}$");
+        ids.push(r#"{"id":"ord_x"}"#.to_string());
+        let v = infer_with(&ids, opts);
+        assert!(v["properties"]["id"].get("pattern").is_none());
+    }
+
+    #[test]
+    fn dictionary_objects_collapse_to_additional_properties() {
+        let wide: Map<String, Value> = (0..10_000)
+            .map(|i| (format!("user_{i}"), Value::from(i)))
+            .collect();
+        let mut samples = vec![json!({ "counts": wide, "meta": {"a": 1} }).to_string()];
+        samples.extend(
+            (0..10_000).map(|i| format!(r#"{{"counts":{{"k{i}":"x"}},"meta":{{"a":2}}}}"#)),
+        );
+        let opts = InferOptions {
+            detect_maps: true,
+            map_key_threshold: 100,
+            additional_properties: AdditionalProperties::Deny,
+            ..Default::default()
+        };
+        let root = parse_samples(&samples, &opts).unwrap();
+        let counts = &root.properties["counts"];
+        assert!(counts.properties.is_empty());
+        assert_eq!(counts.map_values.as_ref().unwrap().seen_count, 20_000);
+
+        let v: Value = serde_json::from_str(&render_schema(&root, &opts).unwrap()).unwrap();
+        assert_eq!(
+            v["properties"]["counts"],
+            json!({"type": "object", "additionalProperties": {"type": ["integer", "string"]}})
+        );
+        assert_eq!(
+            v["properties"]["meta"]["additionalProperties"],
+            json!(false)
+        );
+    }
+
+    #[test]
+    fn property_names_for_maps_until_a_key_breaks_the_shape() {
+        let opts = InferOptions {
+            detect_maps: true,
+            map_key_threshold: 2,
+            ..Default::default()
+        };
+        let samples = [
+            r#"{"m":{"id_1":1,"id_20":2,"id_3":3}}"#,
+            r#"{"m":{"id_4":5}}"#,
+        ];
+        let v = infer_with(&samples, opts.clone());
+        assert_eq!(
+            v["properties"]["m"]["propertyNames"],
+            json!({"pattern": "^id_[0-9]{1,2}$"})
+        );
+
+        let samples = [
+            r#"{"m":{"id_1":1,"id_20":2,"id_3":3}}"#,
+            r#"{"m":{"other":5}}"#,
+        ];
+        let v = infer_with(&samples, opts);
+        assert!(v["properties"]["m"].get("propertyNames").is_none());
+    }
+
+    #[test]
+    fn property_names_for_objects_with_patterned_keys() {
+        let opts = InferOptions {
+            infer_property_names: true,
+            ..Default::default()
+        };
+        let samples = [
+            r#"{"by_day":{"2024-01-01":1,"2024-01-02":2},"by_id":{"6f1c2a8e-3b4d-4e5f-8a9b-0c1d2e3f4a5b":true},"misc":{"a-b":1}}"#,
+            r#"{"by_day":{"2024-02-10":3},"misc":{"c":2}}"#,
+        ];
+        let v = infer_with(&samples, opts.clone());
+        let names = |p: &str| v["properties"][p]["propertyNames"]["pattern"].clone();
+        assert_eq!(names("by_day"), "^[0-9]{4}-[0-9]{2}-[0-9]{2}$");
+        assert!(names("by_id")
+            .as_str()
+            .unwrap()
+            .starts_with("^[0-9a-fA-F]{8}-"));
+        // один ключ с дефисом ломает идентификатор
+        assert!(v["properties"]["misc"].get("propertyNames").is_none());
+        assert_eq!(
+            v["propertyNames"],
+            json!({"pattern": "^[A-Za-z_][A-Za-z0-9_]*$"})
+        );
+        assert!(infer_with(&samples, InferOptions::default())
+            .get("propertyNames")
+            .is_none());
+
+        let mut root = parse_samples(&samples[..1], &opts).unwrap();
+        root.merge_with(parse_samples(&samples[1..], &opts).unwrap());
+        assert_eq!(
+            root.to_json_schema(&opts)["properties"]["by_day"]["propertyNames"],
+            v["properties"]["by_day"]["propertyNames"]
+        );
+        let mut back = Node::from_json_schema(&v).unwrap();
+        back.merge_with(parse_samples(&[r#"{"by_day":{"2024-03-01":1}}"#], &opts).unwrap());
+        assert_eq!(
+            back.to_json_schema(&opts)["properties"]["by_day"]["propertyNames"],
+            v["properties"]["by_day"]["propertyNames"]
+        );
+    }
+
+    #[test]
+    fn map_detection_can_require_id_like_keys() {
+        let keys: Map<String, Value> = ["alpha", "beta", "gamma", "delta"]
+            .iter()
+            .zip(1..)
+            .map(|(k, i)| (k.to_string(), Value::from(i)))
+            .collect();
+        let samples = [json!({ "m": keys }).to_string()];
+        let samples: Vec<&str> = samples.iter().map(String::as_str).collect();
+        let opts = |map_keys_look_like_ids| InferOptions {
+            detect_maps: true,
+            map_key_threshold: 2,
+            map_keys_look_like_ids,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts(true));
+        assert!(v["properties"]["m"].get("properties").is_some());
+        let v = infer_with(&samples, opts(false));
+        assert_eq!(
+            v["properties"]["m"]["additionalProperties"],
+            json!({"type": "integer"})
+        );
+    }
+
+    #[test]
+    fn get_at_path_descends_properties_and_items() {
+        let samples = [r#"{"a":{"b":{"c":"x"}},"arr":[{"id":1}],"grid":[[true]]}"#.to_string()];
+        let root = parse_samples(&samples, &InferOptions::default()).unwrap();
+        let types = |path: &str| {
+            let node = root.get_at_path(path)?;
+            let mut types: Vec<&str> = node.types.iter().map(|t| t.as_str()).collect();
+            types.sort();
+            Some(types)
+        };
+
+        assert_eq!(types("a.b.c"), Some(vec!["string"]));
+        assert_eq!(types("arr[]"), Some(vec!["object"]));
+        assert_eq!(types("arr[].id"), Some(vec!["integer"]));
+        assert_eq!(types("grid[][]"), Some(vec!["boolean"]));
+        assert_eq!(types(""), Some(vec!["object"]));
+        assert_eq!(types("a.x"), None);
+        assert_eq!(types("a[]"), None);
+    }
+
+    #[test]
+    fn default_from_dominant_value() {
+        let mut samples = vec![r#"{"level":"info","retries":3,"flag":true,"o":{}}"#; 8];
+        samples.push(r#"{"level":"debug","retries":5,"flag":false,"o":{}}"#);
+        samples.push(r#"{"level":"warn","retries":3,"flag":false,"o":{}}"#);
+        let opts = InferOptions {
+            emit_default: true,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts.clone());
+
+        assert_eq!(v["properties"]["level"]["default"], "info");
+        assert_eq!(v["properties"]["retries"]["default"], 3);
+        assert_eq!(v["properties"]["flag"]["default"], true);
+        assert!(v["properties"]["o"].get("default").is_none());
+
+        let strict = infer_with(
+            &samples,
+            InferOptions {
+                default_min_share: 0.9,
+                ..opts.clone()
+            },
+        );
+        assert!(strict["properties"]["level"].get("default").is_none());
+        assert_eq!(strict["properties"]["retries"]["default"], 3);
+
+        // равные частоты: побеждает меньшая JSON-сериализация
+        let tie = infer_with(
+            &[r#"{"a":"b"}"#, r#"{"a":"a"}"#, r#"{"a":2}"#, r#"{"a":10}"#],
+            InferOptions {
+                default_min_share: 0.25,
+                ..opts
+            },
+        );
+        assert_eq!(tie["properties"]["a"]["default"], "a");
+    }
+
+    #[test]
+    fn base64_fields_get_content_keywords() {
+        let samples = [
+            r#"{"png":"iVBORw0KGgoAAAANSUhEUg==","blob":"cGxhaW4gdGV4dCBwYXlsb2FkIQ==","short":"abcd","mixed":"iVBORw0KGgoAAAANSUhEUg=="}"#,
+            r#"{"png":"iVBORw0KGgoAAAAA","blob":"/9j/4AAQSkZJRg==","short":"efgh","mixed":"not base64 at all!!"}"#,
+        ];
+        let opts = InferOptions {
+            detect_base64: true,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts);
+
+        assert_eq!(v["properties"]["png"]["contentEncoding"], "base64");
+        assert_eq!(v["properties"]["png"]["contentMediaType"], "image/png");
+        assert_eq!(v["properties"]["blob"]["contentEncoding"], "base64");
+        assert!(v["properties"]["blob"].get("contentMediaType").is_none());
+        assert!(v["properties"]["short"].get("contentEncoding").is_none());
+        assert!(v["properties"]["mixed"].get("contentEncoding").is_none());
+
+        let v = infer_with(&samples, InferOptions::default());
+        assert!(v["properties"]["png"].get("contentEncoding").is_none());
+    }
+
+    #[test]
+    fn numeric_strings_get_a_suggested_type() {
+        let samples = [
+            r#"{"qty":"42","price":"3.14","code":"007","mixed":"12","blank":""}"#,
+            r#"{"qty":"-1","price":"2","code":"10","mixed":"n/a","blank":"1"}"#,
+        ];
+        let opts = InferOptions {
+            detect_numeric_strings: true,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts.clone());
+        let props = &v["properties"];
+        assert_eq!(
+            props["qty"],
+            json!({"type": "string", "x-aif-suggested-type": "integer"})
+        );
+        assert_eq!(props["price"]["x-aif-suggested-type"], "number");
+        for field in ["code", "mixed", "blank"] {
+            assert!(
+                props[field].get("x-aif-suggested-type").is_none(),
+                "{field}"
+            );
+        }
+
+        let aggressive = InferOptions {
+            numeric_string_pattern: true,
+            ..opts
+        };
+        let v = infer_with(&samples, aggressive);
+        assert_eq!(
+            v["properties"]["qty"],
+            json!({"type": ["string"], "pattern": "^-?(0|[1-9][0-9]*)$"})
+        );
+        let schema = serde_json::to_string(&v).unwrap();
+        assert!(validate_sample_rs(
+            &schema,
+            r#"{"qty":"5","price":"1e3","code":"x","mixed":"y","blank":""}"#
+        )
+        .unwrap()
+        .is_empty());
+    }
+
+    #[test]
+    fn integer_and_number_widen_to_number() {
+        let samples = [
+            r#"{"v":1,"rows":[{"x":2}],"n":null,"m":[1]}"#,
+            r#"{"v":1.5,"rows":[{"x":2.5}],"n":3,"m":[0.5,"a"]}"#,
+            r#"{"v":2,"rows":[],"n":3.5,"m":[]}"#,
+        ];
+        let v = infer_with(&samples, InferOptions::default());
+        assert_eq!(v["properties"]["v"]["type"], "number");
+        assert_eq!(
+            v["properties"]["rows"]["items"]["properties"]["x"]["type"],
+            "number"
+        );
+        assert_eq!(v["properties"]["n"]["type"], json!(["null", "number"]));
+        assert_eq!(
+            v["properties"]["m"]["items"]["type"],
+            json!(["number", "string"])
+        );
+
+        let any_of = infer_with(
+            &samples,
+            InferOptions {
+                use_any_of: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(any_of["properties"]["v"], json!({"type": "number"}));
+
+        let raw = infer_with(
+            &samples,
+            InferOptions {
+                numeric_widening: false,
+                ..Default::default()
+            },
+        );
+        assert_eq!(raw["properties"]["v"]["type"], json!(["integer", "number"]));
+        assert_eq!(
+            raw["properties"]["n"]["type"],
+            json!(["integer", "null", "number"])
+        );
+    }
+
+    #[test]
+    fn fixed_length_mixed_arrays_become_tuples() {
+        let samples = [
+            r#"{"row":["ann",31,true],"point":[1.5,2.5],"mixed":["a",1],"long":[1,"a",true]}"#,
+            r#"{"row":["bob",42,false],"point":[3,4.5],"mixed":["b",2,3],"long":["b",2,false]}"#,
+        ];
+        let opts = InferOptions {
+            detect_tuples: true,
+            tuple_max_len: 2,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts.clone());
+        // длина 3 больше tuple_max_len
+        assert!(v["properties"]["row"].get("prefixItems").is_none());
+
+        let v = infer_with(
+            &samples,
+            InferOptions {
+                tuple_max_len: 8,
+                ..opts
+            },
+        );
+        let row = &v["properties"]["row"];
+        assert_eq!(
+            row["prefixItems"],
+            json!([{"type": "string"}, {"type": "integer"}, {"type": "boolean"}])
+        );
+        assert_eq!(row["items"], false);
+        // однородные позиции и разная длина — обычный items
+        assert_eq!(v["properties"]["point"]["items"], json!({"type": "number"}));
+        assert!(v["properties"]["mixed"].get("prefixItems").is_none());
+        assert_eq!(
+            v["properties"]["long"]["prefixItems"][1],
+            json!({"type": ["integer", "string"]})
+        );
+
+        let v = infer_with(&samples, InferOptions::default());
+        assert!(v["properties"]["row"].get("prefixItems").is_none());
+    }
+
+    #[test]
+    fn type_histogram_is_exact_and_survives_maps_and_merges() {
+        let mut samples: Vec<String> = (0..950).map(|i| format!(r#"{{"v":"s{i}"}}"#)).collect();
+        samples.extend((0..48).map(|_| r#"{"v":null}"#.to_string()));
+        samples.extend([r#"{"v":1}"#.to_string(), r#"{"v":2}"#.to_string()]);
+        let opts = InferOptions {
+            annotate_types: true,
+            ..Default::default()
+        };
+        let v = infer_with(
+            &samples.iter().map(String::as_str).collect::<Vec<_>>(),
+            opts.clone(),
+        );
+        let histogram = &v["properties"]["v"]["x-aif-types"];
+        assert_eq!(histogram, &json!({"string": 950, "null": 48, "integer": 2}));
+        let order: Vec<&String> = histogram.as_object().unwrap().keys().collect();
+        assert_eq!(order, ["string", "null", "integer"]);
+        // одного типа — без гистограммы
+        let single = infer_with(&[r#"{"a":"x"}"#], opts.clone());
+        assert!(single["properties"]["a"].get("x-aif-types").is_none());
+
+        // словарь: значения всех ключей сходятся в один узел со сложенными счётчиками
+        let maps: Vec<String> = (0..30)
+            .map(|i| format!(r#"{{"m":{{"k{i}":{},"j{i}":"x"}}}}"#, i % 2))
+            .collect();
+        let refs: Vec<&str> = maps.iter().map(String::as_str).collect();
+        let maps_opts = InferOptions {
+            detect_maps: true,
+            ..Default::default()
+        };
+        let root = parse_samples(&refs, &maps_opts).unwrap();
+        let values = root.properties["m"].map_values.as_ref().unwrap();
+        assert_eq!(values.type_counts.get(TypeTag::Integer), 30);
+        assert_eq!(values.type_counts.get(TypeTag::String), 30);
+
+        let merged = merge_nodes(root.clone(), root);
+        let values = merged.properties["m"].map_values.as_ref().unwrap();
+        assert_eq!(values.type_counts.get(TypeTag::String), 60);
+        assert_eq!(values.type_counts.dominant(), Some(TypeTag::Integer));
+
+        // счётчики читаются обратно из схемы
+        let back = Node::from_json_schema(&v).unwrap();
+        let counts = back.properties["v"].type_counts;
+        assert_eq!(counts.get(TypeTag::Null), 48);
+        assert_eq!(counts.dominant(), Some(TypeTag::String));
+    }
+
+    #[test]
+    fn prefix_items_mode_for_fixed_length_arrays() {
+        let samples = [
+            r#"{"pos":[1.5,2],"row":["a",1],"nul":[1,null],"var":[1,2],"empty":[]}"#,
+            r#"{"pos":[3,4.5],"row":["b",2],"nul":[2,3],"var":[1,2,3],"empty":[]}"#,
+        ];
+        let opts = InferOptions {
+            array_mode: ArrayMode::PrefixItems,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts);
+        let props = &v["properties"];
+        // однородные позиции тоже становятся кортежем
+        assert_eq!(
+            props["pos"]["prefixItems"],
+            json!([{"type": "number"}, {"type": "number"}])
+        );
+        assert_eq!(props["pos"]["items"], false);
+        assert_eq!(props["row"]["prefixItems"][0]["type"], "string");
+        // null на позиции — тип непостоянен; разная длина — обычный items
+        assert!(props["nul"].get("prefixItems").is_none());
+        assert!(props["var"].get("prefixItems").is_none());
+        assert!(props["empty"].get("prefixItems").is_none());
+
+        let v = infer_with(&samples, InferOptions::default());
+        assert_eq!(v["properties"]["pos"]["items"], json!({"type": "number"}));
+    }
+
+    #[test]
+    fn draft_selects_uri_and_tuple_spelling() {
+        let samples = [
+            r#"{"pt":["a",1],"from":{"x":1,"y":2},"to":{"x":3,"y":4}}"#,
+            r#"{"pt":["b",2],"from":{"x":5,"y":6},"to":{"x":7,"y":8}}"#,
+        ];
+        let opts = |draft| InferOptions {
+            draft,
+            detect_tuples: true,
+            extract_defs: true,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts(SchemaDraft::Draft07));
+        assert_eq!(v["$schema"], "http://json-schema.org/draft-07/schema#");
+        let pt = &v["properties"]["pt"];
+        assert_eq!(
+            pt["items"],
+            json!([{"type": "string"}, {"type": "integer"}])
+        );
+        assert_eq!(pt["additionalItems"], false);
+        assert!(pt.get("prefixItems").is_none());
+        assert_eq!(v["properties"]["to"], json!({"$ref": "#/definitions/from"}));
+        assert!(v["definitions"]["from"].is_object());
+
+        let draft7 = serde_json::to_string(&v).unwrap();
+        let errors = validate_sample_rs(&draft7, r#"{"pt":["c",3,4],"from":{},"to":{}}"#);
+        assert_eq!(errors.unwrap()[0].path, "pt[2]");
+        // прочитанный обратно кортеж остаётся кортежем
+        let merged: Value =
+            serde_json::from_str(&merge_schemas_rs(&draft7, &draft7).unwrap()).unwrap();
+        assert_eq!(
+            merged["properties"]["pt"]["prefixItems"][1]["type"],
+            "integer"
+        );
+
+        let v = infer_with(&samples, opts(SchemaDraft::Draft201909));
+        assert_eq!(v["$schema"], "https://json-schema.org/draft/2019-09/schema");
+        assert_eq!(v["properties"]["pt"]["additionalItems"], false);
+        assert!(v["$defs"]["from"].is_object());
+
+        let v = infer_with(&samples, opts(SchemaDraft::Draft202012));
+        assert_eq!(v["$schema"], "https://json-schema.org/draft/2020-12/schema");
+        assert_eq!(v["properties"]["pt"]["items"], false);
+        assert_eq!(v["properties"]["pt"]["prefixItems"][0]["type"], "string");
+    }
+
+    #[test]
+    fn array_samples_give_array_root() {
+        let samples = [r#"["a","b"]"#, r#"["c"]"#, "[]"];
+        let v = infer_with(&samples, InferOptions::default());
+        assert_eq!(v["type"], "array");
+        assert_eq!(v["items"]["type"], "string");
+        assert!(v.get("properties").is_none());
+
+        // потоковый вывод строит тот же корень
+        let mut inferrer = SchemaInferrer::new(InferOptions::default());
+        for s in samples {
+            inferrer.feed(s).unwrap();
+        }
+        let streamed: Value = serde_json::from_str(&inferrer.finish().unwrap()).unwrap();
+        assert_eq!(streamed, v);
+    }
+
+    #[test]
+    fn large_batches_match_streaming() {
+        let samples: Vec<String> = (0..2000)
+            .map(|i| match i % 3 {
+                0 => format!(r#"{{"id":{i},"name":"n{i}","tags":["a"]}}"#),
+                1 => format!(r#"{{"id":{i},"score":{}.5,"tags":[]}}"#, i % 7),
+                _ => format!(r#"{{"id":"x{i}","nested":{{"k":{}}}}}"#, i % 2 == 0),
+            })
+            .collect();
+        let opts = InferOptions {
+            emit_numeric_bounds: true,
+            emit_string_bounds: true,
+            emit_array_bounds: true,
+            ..Default::default()
+        };
+        let mut inferrer = SchemaInferrer::new(opts.clone());
+        for s in &samples {
+            inferrer.feed(s).unwrap();
+        }
+        assert_eq!(
+            infer_schema_rs(&samples, opts).unwrap(),
+            inferrer.finish().unwrap()
+        );
+
+        let mut broken = samples.clone();
+        broken[1500] = "{".to_string();
+        let err = infer_schema_rs(&broken, InferOptions::default()).unwrap_err();
+        assert!(matches!(err, InferError::InvalidJson { index: 1500, .. }));
+    }
+
+    #[test]
+    fn mixed_structure_items_split_into_any_of() {
+        let samples = [
+            r#"{"refs":[{"id":1,"name":"a"},"ref-2",null],"nums":[1,"x"],"lists":[[1],{"k":true}]}"#,
+            r#"{"refs":[{"id":3}],"nums":[2],"lists":[]}"#,
+        ];
+        let opts = InferOptions {
+            items_any_of: true,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts);
+
+        assert_eq!(
+            v["properties"]["refs"]["items"],
+            json!({"anyOf": [
+                {"type": "null"},
+                {"type": "object", "properties": {"id": {"type": "integer"}, "name": {"type": "string"}}, "required": ["id"]},
+                {"type": "string"},
+            ]})
+        );
+        // только скаляры — обычный items
+        assert_eq!(
+            v["properties"]["nums"]["items"]["type"],
+            json!(["integer", "string"])
+        );
+        let lists = &v["properties"]["lists"]["items"]["anyOf"];
+        assert_eq!(lists[0]["items"], json!({"type": "integer"}));
+        assert_eq!(lists[1]["properties"]["k"], json!({"type": "boolean"}));
+
+        let v = infer_with(&samples, InferOptions::default());
+        assert_eq!(
+            v["properties"]["refs"]["items"]["type"],
+            json!(["null", "object", "string"])
+        );
+    }
+
+    #[test]
+    fn conflict_strategy_for_object_or_scalar_fields() {
+        let samples = [
+            r#"{"metadata":{"source":"a"},"size":1}"#,
+            r#"{"metadata":{"source":"b","tags":["x"]},"size":"big"}"#,
+            r#"{"metadata":"n/a","size":2.5}"#,
+            r#"{"metadata":null,"size":3}"#,
+        ];
+        let with = |conflict_strategy| InferOptions {
+            conflict_strategy,
+            include_examples: true,
+            ..Default::default()
+        };
+
+        let v = infer_with(&samples, with(ConflictStrategy::TypeArray));
+        let metadata = &v["properties"]["metadata"];
+        assert_eq!(metadata["type"], json!(["null", "object", "string"]));
+        assert!(metadata.get("properties").is_some());
+
+        let v = infer_with(&samples, with(ConflictStrategy::AnyOf));
+        let branches = v["properties"]["metadata"]["anyOf"].as_array().unwrap();
+        assert_eq!(branches.len(), 3);
+        assert_eq!(branches[1]["required"], json!(["source"]));
+        assert_eq!(branches[2], json!({"type": "string"}));
+        // одни скаляры — не конфликт структур
+        assert_eq!(v["properties"]["size"]["type"], json!(["number", "string"]));
+
+        let v = infer_with(&samples, with(ConflictStrategy::Dominant));
+        let metadata = &v["properties"]["metadata"];
+        assert_eq!(metadata["type"], json!(["null", "object"]));
+        assert_eq!(metadata["required"], json!(["source"]));
+        assert_eq!(metadata["x-aif-dropped-types"], json!({"string": 1}));
+        assert!(metadata.get("examples").is_none());
+
+        // integer и number считаются вместе и перевешивают объект
+        let samples = [
+            r#"{"v":{"k":1}}"#,
+            r#"{"v":1}"#,
+            r#"{"v":2.5}"#,
+            r#"{"v":"s"}"#,
+        ];
+        let v = infer_with(&samples, with(ConflictStrategy::Dominant));
+        let field = &v["properties"]["v"];
+        assert_eq!(field["type"], "number");
+        assert_eq!(
+            field["x-aif-dropped-types"],
+            json!({"object": 1, "string": 1})
+        );
+        assert_eq!(field["examples"], json!([1, 2.5]));
+        assert!(field.get("properties").is_none());
+    }
+
+    #[test]
+    fn property_order_is_sorted_or_first_seen() {
+        let samples = [
+            r#"{"zeta":1,"alpha":{"y":1,"b":2},"mid":"x"}"#,
+            r#"{"omega":true,"alpha":{"a":3},"zeta":2}"#,
+        ];
+        let reversed = [samples[1], samples[0]];
+        assert_eq!(
+            infer_schema_from_strs(&samples, InferOptions::default()).unwrap(),
+            infer_schema_from_strs(&reversed, InferOptions::default()).unwrap()
+        );
+
+        let keys = |v: &Value| -> Vec<String> { v.as_object().unwrap().keys().cloned().collect() };
+        let sorted = infer_with(&samples, InferOptions::default());
+        assert_eq!(
+            keys(&sorted["properties"]),
+            ["alpha", "mid", "omega", "zeta"]
+        );
+
+        let opts = InferOptions {
+            preserve_insertion_order: true,
+            ..Default::default()
+        };
+        let ordered = infer_with(&samples, opts);
+        assert_eq!(
+            keys(&ordered["properties"]),
+            ["zeta", "alpha", "mid", "omega"]
+        );
+        assert_eq!(
+            keys(&ordered["properties"]["alpha"]["properties"]),
+            ["y", "b", "a"]
+        );
+    }
+
+    #[test]
+    fn examples_only_for_scalar_values() {
+        let samples = [
+            r#"{"s":"aaaaaaaaaa","n":1,"o":{"x":true},"a":[1]}"#,
+            r#"{"s":"b","n":1.5,"o":{"x":true},"a":[2]}"#,
+            r#"{"s":"b","n":2,"o":{"x":false},"a":[3]}"#,
+        ];
+        let opts = InferOptions {
+            include_examples: true,
+            examples_per_field: 2,
+            example_max_len: 4,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts);
+
+        assert_eq!(v["properties"]["s"]["examples"], json!(["aaaa…", "b"]));
+        assert_eq!(
+            v["properties"]["n"]["examples"].as_array().unwrap().len(),
+            2
+        );
+        assert!(v["properties"]["o"].get("examples").is_none());
+        assert!(v["properties"]["a"].get("examples").is_none());
+        assert_eq!(
+            v["properties"]["a"]["items"]["examples"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(
+            v["properties"]["o"]["properties"]["x"]["examples"],
+            json!([true, false])
+        );
+
+        let v = infer_with(&samples, InferOptions::default());
+        assert!(v["properties"]["s"].get("examples").is_none());
+    }
+
+    #[test]
+    fn titles_from_property_names() {
+        for (key, title) in [
+            ("firstName", "First Name"),
+            ("first_name", "First Name"),
+            ("ID", "ID"),
+            ("userProfileURL", "User Profile URL"),
+            ("URLPath", "URL Path"),
+            ("__private_key", "Private Key"),
+            ("kebab-case", "Kebab Case"),
+            ("address2", "Address2"),
+        ] {
+            assert_eq!(key_to_title(key), title, "{key}");
+        }
+
+        let samples = [r#"{"firstName":"a","home_page":{"pageURL":"b"},"n":null}"#];
+        let opts = InferOptions {
+            generate_titles: true,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts);
+        assert_eq!(v["properties"]["firstName"]["title"], "First Name");
+        assert_eq!(
+            v["properties"]["home_page"]["properties"]["pageURL"]["title"],
+            "Page URL"
+        );
+        assert!(v.get("title").is_none());
+        let v = infer_with(&samples, InferOptions::default());
+        assert!(v["properties"]["firstName"].get("title").is_none());
+    }
+
+    #[test]
+    fn examples_reservoir_holds_three_observed_values() {
+        let samples: Vec<String> = (0..100)
+            .map(|i| json!({ "n": i, "s": format!("v{i}") }).to_string())
+            .collect();
+        let samples: Vec<&str> = samples.iter().map(String::as_str).collect();
+        let opts = InferOptions {
+            include_examples: true,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts);
+        for (field, valid) in [
+            ("n", (0..100).map(Value::from).collect::<Vec<_>>()),
+            (
+                "s",
+                (0..100).map(|i| Value::from(format!("v{i}"))).collect(),
+            ),
+        ] {
+            let examples = v["properties"][field]["examples"].as_array().unwrap();
+            assert_eq!(examples.len(), 3, "{field}");
+            assert!(examples.iter().all(|e| valid.contains(e)), "{field}");
+        }
+    }
+
+    #[test]
+    fn array_item_count_bounds() {
+        let samples = [
+            r#"{"t":[1,2,3],"e":[],"m":[1]}"#,
+            r#"{"t":[],"e":[],"m":"x"}"#,
+            r#"{"t":[4],"e":[],"m":[2,3]}"#,
+        ];
+        let opts = InferOptions {
+            emit_array_bounds: true,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts);
+
+        assert_eq!(v["properties"]["t"]["minItems"], serde_json::json!(0));
+        assert_eq!(v["properties"]["t"]["maxItems"], serde_json::json!(3));
+        // всегда пустой массив — без items
+        assert_eq!(v["properties"]["e"]["minItems"], serde_json::json!(0));
+        assert_eq!(v["properties"]["e"]["maxItems"], serde_json::json!(0));
+        assert!(v["properties"]["e"].get("items").is_none());
+        // скалярные значения поля на границы не влияют
+        assert_eq!(v["properties"]["m"]["minItems"], serde_json::json!(1));
+        assert_eq!(v["properties"]["m"]["maxItems"], serde_json::json!(2));
+
+        let v = infer_with(&samples, InferOptions::default());
+        assert!(v["properties"]["t"].get("minItems").is_none());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn diff_reports_format_changes_as_modified() {
+        let opts = || InferOptions {
+            detect_formats: true,
+            ..Default::default()
+        };
+        let a = infer_schema_rs(
+            &[
+                r#"{"id":"0f8fad5b-d9cb-469f-a165-70867728950e"}"#.to_string(),
+                r#"{"id":"7C9E6679-7425-40DE-944B-E07FC1F90AE7"}"#.to_string(),
+            ],
+            opts(),
+        )
+        .unwrap();
+        let b = infer_schema_rs(
+            &[
+                r#"{"id":"0f8fad5b-d9cb-469f-a165-70867728950e"}"#.to_string(),
+                r#"{"id":"user-42"}"#.to_string(),
+            ],
+            opts(),
+        )
+        .unwrap();
+        let sa: Value = serde_json::from_str(&a).unwrap();
+        assert_eq!(sa["properties"]["id"]["format"], "uuid");
+
+        let d: Value =
+            serde_json::from_str(&diff_schemas(a, b, "paths", "dot", false, false).unwrap())
+                .unwrap();
+        assert_eq!(
+            d["modified"],
+            serde_json::json!([{"path": "id", "keyword": "format", "old": "uuid", "new": null}])
+        );
+    }
+
+    #[test]
+    fn unique_items_only_when_no_array_had_duplicates() {
+        let samples = [
+            r#"{"set":["a","b"],"bag":[1,2],"objs":[{"x":1,"y":2}]}"#,
+            r#"{"set":["c"],"bag":[3,3],"objs":[{"y":2,"x":1},{"x":1,"y":2}],"nested":[[1,[2]],[1,[3]]]}"#,
+            r#"{"long":[1,2,3],"nested":[{"a":[1]},{"a":[1,2]}]}"#,
+        ];
+        let opts = InferOptions {
+            detect_unique_items: true,
+            unique_items_max_len: 2,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, opts);
+
+        assert_eq!(
+            v["properties"]["set"]["uniqueItems"],
+            serde_json::json!(true)
+        );
+        assert!(v["properties"]["bag"].get("uniqueItems").is_none());
+        assert!(v["properties"]["objs"].get("uniqueItems").is_none());
+        assert_eq!(
+            v["properties"]["nested"]["uniqueItems"],
+            serde_json::json!(true)
+        );
+        // длинный массив не проверялся — уникальность не доказана
+        assert!(v["properties"]["long"].get("uniqueItems").is_none());
+
+        let v = infer_with(&samples, InferOptions::default());
+        assert!(v["properties"]["set"].get("uniqueItems").is_none());
+    }
+
+    #[test]
+    fn deny_additional_properties_on_every_object_with_properties() {
+        let samples = [r#"{"a":{"b":1},"c":{},"d":[{"e":true}]}"#];
+        let with = |additional_properties| InferOptions {
+            additional_properties,
+            ..Default::default()
+        };
+        let v = infer_with(&samples, with(AdditionalProperties::Deny));
+
+        assert_eq!(v["additionalProperties"], serde_json::json!(false));
+        assert_eq!(
+            v["properties"]["a"]["additionalProperties"],
+            serde_json::json!(false)
+        );
+        assert!(v["properties"]["c"].get("additionalProperties").is_none());
+        assert_eq!(
+            v["properties"]["d"]["items"]["additionalProperties"],
+            serde_json::json!(false)
+        );
+
+        let v = infer_with(&samples, with(AdditionalProperties::DenyAtRoot));
+        assert_eq!(v["additionalProperties"], serde_json::json!(false));
+        assert!(v["properties"]["a"].get("additionalProperties").is_none());
+
+        let v = infer_with(&samples, InferOptions::default());
+        assert!(v.get("additionalProperties").is_none());
+    }
+
+    #[test]
+    fn format_dropped_past_the_sample_size() {
+        let samples = [
+            r#"{"d":"2024-01-02","e":"2024-01-02"}"#,
+            r#"{"d":"2024-03-04","e":"2024-03-04"}"#,
+            r#"{"d":"not a date","e":"2024-05-06"}"#,
+        ];
+        let opts = |format_sample_size| InferOptions {
+            detect_formats: true,
+            format_sample_size,
+            ..Default::default()
+        };
+        // третья строка не проверялась — формату нельзя верить
+        let v = infer_with(&samples, opts(2));
+        assert!(v["properties"]["d"].get("format").is_none());
+        assert!(v["properties"]["e"].get("format").is_none());
+
+        let v = infer_with(&samples, opts(InferOptions::default().format_sample_size));
+        assert!(v["properties"]["d"].get("format").is_none());
+        assert_eq!(v["properties"]["e"]["format"], "date");
+    }
+
+    #[test]
+    fn uri_format_needs_every_value_to_parse() {
+        let samples = [
+            r#"{"src":"https://example.com/x?y=1","dst":"s3://bucket/key"}"#,
+            r#"{"src":"file:///tmp/a.json","dst":"not a uri"}"#,
+        ];
+        let v = infer_with(
+            &samples,
+            InferOptions {
+                detect_formats: true,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(v["properties"]["src"]["format"], "uri");
+        assert!(v["properties"]["dst"].get("format").is_none());
+    }
+
+    #[test]
+    fn ip_formats_and_mixed_families() {
+        let samples = [
+            r#"{"v4":"10.0.0.1","v6":"2001:db8::1","any":"10.0.0.1","bad":"999.1.1.1"}"#,
+            r#"{"v4":"192.168.1.20","v6":"::1","any":"fe80::1","bad":"10.0.0.1"}"#,
+        ];
+        let opts = |ip_any_of| InferOptions {
+            detect_formats: true,
+            ip_any_of,
+            ..Default::default()
+        };
+
+        let v = infer_with(&samples, opts(false));
+        let p = &v["properties"];
+        assert_eq!(p["v4"]["format"], "ipv4");
+        assert_eq!(p["v6"]["format"], "ipv6");
+        assert!(p["any"].get("format").is_none());
+        assert!(p["any"].get("anyOf").is_none());
+        assert!(p["bad"].get("format").is_none());
+
+        let v = infer_with(&samples, opts(true));
+        assert_eq!(
+            v["properties"]["any"]["anyOf"],
+            serde_json::json!([{"format": "ipv4"}, {"format": "ipv6"}])
+        );
+        assert!(v["properties"]["bad"].get("anyOf").is_none());
+    }
+
+    #[test]
+    fn invalid_json_carries_sample_index() {
+        let samples: Vec<String> = [r#"{"a":1}"#, "[]", "{}", r#"{"a":"#, "1"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let err = infer_schema_rs(&samples, InferOptions::default()).unwrap_err();
+
+        assert!(matches!(err, InferError::InvalidJson { index: 3, .. }));
+        assert!(err.to_string().starts_with("invalid JSON in sample 3"));
+    }
+
+    #[test]
+    fn numeric_bounds_keep_integer_representation() {
+        let samples = [
+            r#"{"mix":1,"big":18446744073709551615,"neg":-9223372036854775808}"#,
+            r#"{"mix":2.5,"big":18446744073709551614,"neg":0}"#,
+            r#"{"mix":-3,"big":0,"neg":-1.5}"#,
+        ];
+        let v = infer_with(
+            &samples,
+            InferOptions {
+                emit_numeric_bounds: true,
+                ..Default::default()
+            },
+        );
+        let p = &v["properties"];
+
+        assert_eq!(p["mix"]["minimum"].to_string(), "-3");
+        assert_eq!(p["mix"]["maximum"].to_string(), "2.5");
+        assert_eq!(p["big"]["minimum"].to_string(), "0");
+        assert_eq!(p["big"]["maximum"].to_string(), "18446744073709551615");
+        assert_eq!(p["neg"]["minimum"].to_string(), "-9223372036854775808");
+        assert_eq!(p["neg"]["maximum"].to_string(), "0");
+    }
+
+    #[test]
+    fn exclusive_zero_bounds_for_strictly_signed_fields() {
+        let samples: Vec<String> = (1..=5)
+            .map(|i| format!(r#"{{"amount":{},"debt":-{i}.5,"delta":{}}}"#, i * 10, i - 3))
+            .collect();
+        let samples: Vec<&str> = samples.iter().map(|s| s.as_str()).collect();
+        let opts = |min_obs| InferOptions {
+            emit_numeric_bounds: true,
+            exclusive_zero_bounds: true,
+            exclusive_bounds_min_observations: min_obs,
+            ..Default::default()
+        };
+
+        let v = infer_with(&samples, opts(5));
+        let p = &v["properties"];
+        assert_eq!(p["amount"]["exclusiveMinimum"], serde_json::json!(0));
+        assert!(p["amount"].get("minimum").is_none());
+        assert_eq!(p["amount"]["maximum"], serde_json::json!(50));
+        assert_eq!(p["debt"]["exclusiveMaximum"], serde_json::json!(0));
+        assert_eq!(p["debt"]["minimum"], serde_json::json!(-5.5));
+        assert_eq!(p["delta"]["minimum"], serde_json::json!(-2));
+        assert!(p["delta"].get("exclusiveMinimum").is_none());
+
+        // мало наблюдений — остаются точные границы
+        let v = infer_with(&samples, opts(6));
+        assert_eq!(v["properties"]["amount"]["minimum"], serde_json::json!(10));
+    }
+
+    #[test]
+    fn multiple_of_from_gcd_and_float_steps() {
+        let samples = [
+            r#"{"price":15,"neg":-10,"odd":3,"zero":0,"q":0.25,"cents":1.01,"noisy":0.3333}"#,
+            r#"{"price":40,"neg":25,"odd":4,"zero":0,"q":1.75,"cents":2,"noisy":1}"#,
+            r#"{"price":0,"neg":0,"odd":9,"zero":0,"q":-0.5,"cents":0.1,"noisy":2}"#,
+        ];
+        let opts = |on| InferOptions {
+            detect_multiple_of: on,
+            detect_const: false,
+            ..Default::default()
+        };
+
+        let v = infer_with(&samples, opts(true));
+        let p = &v["properties"];
+        assert_eq!(p["price"]["multipleOf"], serde_json::json!(5));
+        assert_eq!(p["neg"]["multipleOf"], serde_json::json!(5));
+        assert!(p["odd"].get("multipleOf").is_none());
+        assert!(p["zero"].get("multipleOf").is_none());
+        assert_eq!(p["q"]["multipleOf"], serde_json::json!(0.25));
+        assert_eq!(p["cents"]["multipleOf"], serde_json::json!(0.01));
+        assert!(p["noisy"].get("multipleOf").is_none());
+
+        let v = infer_with(&samples, opts(false));
+        assert!(v["properties"]["price"].get("multipleOf").is_none());
     }
 }
@@ -0,0 +1,413 @@
+use std::cmp::{max_by, min_by};
+
+use crate::diff::parse_schema;
+use crate::{cmp_numbers, gcd, render_schema, InferError, InferOptions, Node, TypeTag};
+
+impl Node {
+    /// Folds `other` into `self` as if every value it observed had been
+    /// observed here as well.
+    pub fn merge_with(&mut self, other: Node) {
+        let numeric =
+            |n: &Node| n.types.contains(&TypeTag::Integer) || n.types.contains(&TypeTag::Number);
+        let (self_numeric, other_numeric) = (numeric(self), numeric(&other));
+        let self_strings = self.types.contains(&TypeTag::String);
+        let other_strings = other.types.contains(&TypeTag::String);
+        let self_arrays = self.types.contains(&TypeTag::Array);
+        let other_arrays = other.types.contains(&TypeTag::Array);
+        let self_objects = self.types.contains(&TypeTag::Object);
+        let other_objects = other.types.contains(&TypeTag::Object);
+
+        match (&mut self.examples, other.examples) {
+            (Some(examples), Some(other_examples)) => examples.merge(other_examples),
+            (examples @ None, other_examples) => *examples = other_examples,
+            (Some(_), None) => {}
+        }
+
+        self.value_counts = match (self.seen_count, other.seen_count) {
+            (0, _) => other.value_counts,
+            (_, 0) => self.value_counts.take(),
+            _ => self
+                .value_counts
+                .take()
+                .zip(other.value_counts)
+                .map(|(mut a, b)| {
+                    for (k, n) in b {
+                        *a.entry(k).or_insert(0) += n;
+                    }
+                    a
+                }),
+        };
+
+        // Константа сохраняется, только если обе стороны держали одно и то же значение
+        if self.seen_count == 0 {
+            self.is_const = other.is_const;
+            self.const_candidate = other.const_candidate;
+        } else if other.seen_count > 0
+            && !(self.is_const && other.is_const && self.const_candidate == other.const_candidate)
+        {
+            self.is_const = false;
+            self.const_candidate = None;
+        }
+
+        self.num_min = merge_bound(
+            self.num_min.take(),
+            self_numeric,
+            other.num_min,
+            other_numeric,
+            |a, b| min_by(a, b, cmp_numbers),
+        );
+        self.num_max = merge_bound(
+            self.num_max.take(),
+            self_numeric,
+            other.num_max,
+            other_numeric,
+            |a, b| max_by(a, b, cmp_numbers),
+        );
+        if self.number_count == 0 {
+            self.int_values = other.int_values;
+            self.float_steps = other.float_steps;
+        } else if other.number_count > 0 {
+            self.int_values = union(self.int_values.take(), other.int_values);
+            self.float_steps &= other.float_steps;
+        }
+        self.int_gcd = gcd(self.int_gcd, other.int_gcd);
+        self.decimal_places = self.decimal_places.max(other.decimal_places);
+        self.number_count += other.number_count;
+
+        self.str_min_len = merge_bound(
+            self.str_min_len,
+            self_strings,
+            other.str_min_len,
+            other_strings,
+            usize::min,
+        );
+        self.str_max_len = merge_bound(
+            self.str_max_len,
+            self_strings,
+            other.str_max_len,
+            other_strings,
+            usize::max,
+        );
+        if self.string_count == 0 {
+            self.string_values = other.string_values;
+            self.formats = other.formats;
+            self.patterns = other.patterns;
+            self.base64 = other.base64;
+            self.numeric_strings = other.numeric_strings;
+            self.boolean_strings = other.boolean_strings;
+        } else if other.string_count > 0 {
+            self.string_values = union(self.string_values.take(), other.string_values);
+            self.formats = self.formats.intersect(other.formats);
+            self.patterns = match (self.patterns.take(), other.patterns) {
+                (Some(mut a), Some(b)) => {
+                    a.merge(b);
+                    Some(a).filter(|p| !p.is_empty())
+                }
+                _ => None,
+            };
+            self.base64 = match (self.base64, other.base64) {
+                (Some(a), Some(b)) => Some(a.merge(b)),
+                _ => None,
+            };
+            self.numeric_strings = match (self.numeric_strings, other.numeric_strings) {
+                (Some(a), Some(b)) => Some(a.merge(b)),
+                _ => None,
+            };
+            self.boolean_strings = match (self.boolean_strings.take(), other.boolean_strings) {
+                (Some(mut a), Some(b)) => {
+                    a.merge(b);
+                    Some(a)
+                }
+                _ => None,
+            };
+        }
+        self.string_count += other.string_count;
+
+        self.obj_min_props = merge_bound(
+            self.obj_min_props,
+            self_objects,
+            other.obj_min_props,
+            other_objects,
+            usize::min,
+        );
+        self.obj_max_props = merge_bound(
+            self.obj_max_props,
+            self_objects,
+            other.obj_max_props,
+            other_objects,
+            usize::max,
+        );
+        self.arr_min_items = merge_bound(
+            self.arr_min_items,
+            self_arrays,
+            other.arr_min_items,
+            other_arrays,
+            usize::min,
+        );
+        self.arr_max_items = merge_bound(
+            self.arr_max_items,
+            self_arrays,
+            other.arr_max_items,
+            other_arrays,
+            usize::max,
+        );
+        self.unique_items_candidate = match (self_arrays, other_arrays) {
+            (false, _) => other.unique_items_candidate,
+            (_, false) => self.unique_items_candidate,
+            _ => self.unique_items_candidate && other.unique_items_candidate,
+        };
+        self.tuple = match (self_arrays, other_arrays) {
+            (false, _) => other.tuple,
+            (_, false) => self.tuple.take(),
+            _ => match (self.tuple.take(), other.tuple) {
+                (Some(mut a), Some(b)) if a.len() == b.len() => {
+                    for (position, other_position) in a.iter_mut().zip(b) {
+                        position.merge_with(other_position);
+                    }
+                    Some(a)
+                }
+                _ => None,
+            },
+        };
+        match (&mut self.items, other.items) {
+            (Some(items), Some(other_items)) => items.merge_with(*other_items),
+            (items @ None, other_items) => *items = other_items,
+            (Some(_), None) => {}
+        }
+
+        self.map_keys.merge(other.map_keys);
+        if !self_objects {
+            self.key_patterns = other.key_patterns;
+        } else if other_objects {
+            self.key_patterns = match (self.key_patterns.take(), other.key_patterns) {
+                (Some(mut a), Some(b)) => a.merge(b).then_some(a),
+                _ => None,
+            };
+        }
+        if !self_objects {
+            self.co_occurrence = other.co_occurrence;
+        } else if other_objects {
+            self.co_occurrence = match (self.co_occurrence.take(), other.co_occurrence) {
+                (Some(mut a), Some(b)) => {
+                    a.merge(b);
+                    Some(a)
+                }
+                _ => None,
+            };
+        }
+        match (&mut self.map_values, other.map_values) {
+            (Some(values), Some(other_values)) => values.merge_with(*other_values),
+            (values @ None, other_values) => *values = other_values,
+            (Some(_), None) => {}
+        }
+        for (k, child) in other.properties {
+            match self.properties.get_mut(&k) {
+                Some(existing) => existing.merge_with(child),
+                None => {
+                    self.properties.insert(k, child);
+                }
+            }
+        }
+        // словарь с любой стороны поглощает именованные свойства другой
+        if self.map_values.is_some() {
+            self.collapse_to_map();
+        }
+
+        self.types.extend(other.types);
+        self.type_counts.merge(&other.type_counts);
+        self.seen_count += other.seen_count;
+        self.sample_count += other.sample_count;
+        self.doc_count += other.doc_count;
+        self.last_doc = self.last_doc.max(other.last_doc);
+        if let Some(values) = &mut self.map_values {
+            values.clamp_doc_count(self.doc_count);
+        }
+    }
+}
+
+// Граница объединения: сторона без значений этого типа не влияет, а сторона
+// с такими значениями, но без границы, снимает её
+fn merge_bound<T>(
+    a: Option<T>,
+    a_seen: bool,
+    b: Option<T>,
+    b_seen: bool,
+    pick: fn(T, T) -> T,
+) -> Option<T> {
+    match (a_seen, b_seen) {
+        (false, _) => b,
+        (_, false) => a,
+        _ => a.zip(b).map(|(a, b)| pick(a, b)),
+    }
+}
+
+// Наборы значений для enum: если хоть одна сторона перестала их отслеживать — конец
+fn union<T: Eq + std::hash::Hash>(
+    a: Option<ahash::AHashSet<T>>,
+    b: Option<ahash::AHashSet<T>>,
+) -> Option<ahash::AHashSet<T>> {
+    let (mut a, b) = (a?, b?);
+    a.extend(b);
+    Some(a)
+}
+
+/// `a` with everything `b` observed folded in; see `Node::merge_with`. Either
+/// side may come from `Node::from_json_schema`, so a persisted schema can be
+/// extended with the root of a `SchemaInferrer` fed with new samples.
+pub fn merge_nodes(mut a: Node, b: Node) -> Node {
+    a.merge_with(b);
+    a
+}
+
+/// Returns a schema accepting every document valid under either `a` or `b`.
+pub fn merge_schemas_rs(a: &str, b: &str) -> Result<String, InferError> {
+    let va = parse_schema(a, "schema A")?;
+    let vb = parse_schema(b, "schema B")?;
+    let mut merged = Node::from_json_schema(&va)?;
+    merged.merge_with(Node::from_json_schema(&vb)?);
+    render_schema(&merged, &merge_options())
+}
+
+// Узлы из схем несут только ключевые слова исходных схем — выводим их все обратно
+fn merge_options() -> InferOptions {
+    schema_options(InferOptions::default())
+}
+
+// base с выводом всех ключевых слов, которые могут нести восстановленные из схем узлы;
+// required и аннотации частот считаются по весам схем, а не по наблюдениям
+pub(crate) fn schema_options(base: InferOptions) -> InferOptions {
+    InferOptions {
+        emit_numeric_bounds: true,
+        emit_string_bounds: true,
+        emit_array_bounds: true,
+        emit_property_counts: true,
+        detect_unique_items: true,
+        detect_multiple_of: true,
+        detect_formats: true,
+        ip_any_of: true,
+        emit_default: true,
+        detect_base64: true,
+        detect_tuples: true,
+        detect_dependent_required: true,
+        required_threshold: 1.0,
+        annotate_frequency: false,
+        annotate_presence: false,
+        annotate_types: false,
+        ..base
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn merge(a: &str, b: &str) -> Value {
+        serde_json::from_str(&merge_schemas_rs(a, b).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn merge_unions_types_and_relaxes_required() {
+        let a = r#"{"type":"object","properties":{
+            "id":{"type":"integer"},"name":{"type":"string"}},"required":["id","name"]}"#;
+        let b = r#"{"type":"object","properties":{
+            "id":{"type":"string"},"tags":{"type":"array","items":{"type":"string"}}},
+            "required":["id","tags"]}"#;
+        let v = merge(a, b);
+        assert_eq!(
+            v["properties"]["id"]["type"],
+            serde_json::json!(["integer", "string"])
+        );
+        assert_eq!(v["properties"]["name"]["type"], "string");
+        assert_eq!(v["properties"]["tags"]["items"]["type"], "string");
+        assert_eq!(v["required"], serde_json::json!(["id"]));
+    }
+
+    #[test]
+    fn merge_keeps_shared_const_and_unions_nested_items() {
+        let a = r#"{"type":"object","properties":{
+            "v":{"const":1},
+            "rows":{"type":"array","items":{"type":"object",
+                "properties":{"x":{"type":"integer"}},"required":["x"]}}},
+            "required":["v","rows"]}"#;
+        let b = r#"{"type":"object","properties":{
+            "v":{"const":1},
+            "rows":{"type":"array","items":{"type":"object",
+                "properties":{"x":{"type":"integer"},"y":{"type":"null"}},"required":["x","y"]}}},
+            "required":["v","rows"]}"#;
+        let v = merge(a, b);
+        assert_eq!(v["properties"]["v"], serde_json::json!({"const": 1}));
+        let items = &v["properties"]["rows"]["items"];
+        assert_eq!(items["required"], serde_json::json!(["x"]));
+        assert_eq!(items["properties"]["y"]["type"], "null");
+
+        let c = r#"{"type":"object","properties":{"v":{"const":2}},"required":["v"]}"#;
+        // разные константы объединяются в enum
+        assert_eq!(
+            merge(a, c)["properties"]["v"],
+            serde_json::json!({"enum": [1, 2], "minimum": 1, "maximum": 2})
+        );
+    }
+
+    #[test]
+    fn merge_widens_bounds_and_drops_missing_ones() {
+        let a = r#"{"type":"object","properties":{
+            "n":{"type":"integer","minimum":1,"maximum":5},
+            "s":{"type":"string","format":"email","minLength":3,"maxLength":10},
+            "ip":{"type":"string","format":"ipv4"}}}"#;
+        let b = r#"{"type":"object","properties":{
+            "n":{"type":"integer","minimum":-3},
+            "s":{"type":"string","format":"email","minLength":5,"maxLength":40},
+            "ip":{"type":"string","format":"ipv6"}}}"#;
+        let v = merge(a, b);
+        assert_eq!(
+            v["properties"]["n"],
+            serde_json::json!({"type": "integer", "minimum": -3})
+        );
+        assert_eq!(
+            v["properties"]["s"],
+            serde_json::json!({"type": "string", "format": "email", "minLength": 3, "maxLength": 40})
+        );
+        assert_eq!(
+            v["properties"]["ip"]["anyOf"],
+            serde_json::json!([{"format": "ipv4"}, {"format": "ipv6"}])
+        );
+    }
+
+    #[test]
+    fn merge_nodes_matches_observing_everything() {
+        let opts = InferOptions::default();
+        let node = |samples: &[&str]| crate::parse_samples(samples, &opts).unwrap();
+        let render = |n: &Node| n.to_json_schema(&opts);
+
+        // у одной стороны items нет вовсе, у другой есть
+        let a = node(&[r#"{"id":1,"tags":[]}"#]);
+        let b = node(&[r#"{"id":"x","tags":["t"],"extra":{"k":true}}"#]);
+        let merged = merge_nodes(a.clone(), b.clone());
+        assert_eq!(
+            render(&merged),
+            render(&node(&[
+                r#"{"id":1,"tags":[]}"#,
+                r#"{"id":"x","tags":["t"],"extra":{"k":true}}"#
+            ]))
+        );
+        assert_eq!(
+            render(&merge_nodes(b, a))["properties"]["tags"]["items"],
+            serde_json::json!({"type": "string"})
+        );
+        assert_eq!(
+            render(&merged)["properties"]["id"]["type"],
+            serde_json::json!(["integer", "string"])
+        );
+    }
+
+    #[test]
+    fn merge_rejects_unknown_type() {
+        let err = merge_schemas_rs(
+            r#"{"type":"object","properties":{"a":{"type":"decimal"}}}"#,
+            r#"{"type":"object"}"#,
+        )
+        .unwrap_err();
+        assert!(matches!(err, InferError::UnsupportedSchema { ref path, .. } if path == "a"));
+    }
+}
@@ -0,0 +1,157 @@
+use ahash::{AHashMap, AHashSet};
+use serde_json::{json, Map, Value};
+
+use crate::diff::parse_schema;
+use crate::{InferError, InferOptions, Node, TypeTag};
+
+/// Structural difference between two inference nodes, shaped like the nodes
+/// themselves: only the parts that changed are present.
+#[derive(Debug, Clone, Default)]
+pub struct NodeDiff {
+    pub type_added: AHashSet<TypeTag>,
+    pub type_removed: AHashSet<TypeTag>,
+    pub properties_added: AHashMap<String, Node>,
+    pub properties_removed: AHashMap<String, Node>,
+    pub properties_changed: AHashMap<String, NodeDiff>,
+    pub items_diff: Option<Box<NodeDiff>>,
+}
+
+impl NodeDiff {
+    pub fn is_empty(&self) -> bool {
+        self.type_added.is_empty()
+            && self.type_removed.is_empty()
+            && self.properties_added.is_empty()
+            && self.properties_removed.is_empty()
+            && self.properties_changed.is_empty()
+            && self.items_diff.is_none()
+    }
+
+    /// JSON view of the diff; added and removed properties are rendered as
+    /// schemas, every list and map is sorted.
+    pub fn to_json(&self) -> Value {
+        let types = |set: &AHashSet<TypeTag>| {
+            let mut names: Vec<&str> = set.iter().map(|t| t.as_str()).collect();
+            names.sort();
+            json!(names)
+        };
+        let schemas = |props: &AHashMap<String, Node>| {
+            let opts = InferOptions::default();
+            let mut map: Map<String, Value> = props
+                .iter()
+                .map(|(k, n)| (k.clone(), n.to_json_schema(&opts)))
+                .collect();
+            map.sort_keys();
+            Value::Object(map)
+        };
+        let mut changed: Map<String, Value> = self
+            .properties_changed
+            .iter()
+            .map(|(k, d)| (k.clone(), d.to_json()))
+            .collect();
+        changed.sort_keys();
+        json!({
+            "type_added": types(&self.type_added),
+            "type_removed": types(&self.type_removed),
+            "properties_added": schemas(&self.properties_added),
+            "properties_removed": schemas(&self.properties_removed),
+            "properties_changed": changed,
+            "items_diff": self.items_diff.as_ref().map(|d| d.to_json()),
+        })
+    }
+}
+
+/// Compares `a` (old) with `b` (new) level by level.
+pub fn diff_nodes(a: &Node, b: &Node) -> NodeDiff {
+    let mut diff = NodeDiff {
+        type_added: b.types.difference(&a.types).copied().collect(),
+        type_removed: a.types.difference(&b.types).copied().collect(),
+        ..NodeDiff::default()
+    };
+
+    for (k, new) in &b.properties {
+        match a.properties.get(k) {
+            None => {
+                diff.properties_added.insert(k.clone(), new.clone());
+            }
+            Some(old) => {
+                let child = diff_nodes(old, new);
+                if !child.is_empty() {
+                    diff.properties_changed.insert(k.clone(), child);
+                }
+            }
+        }
+    }
+    for (k, old) in &a.properties {
+        if !b.properties.contains_key(k) {
+            diff.properties_removed.insert(k.clone(), old.clone());
+        }
+    }
+
+    // Отсутствующий узел элементов сравниваем как пустой
+    let items = match (&a.items, &b.items) {
+        (None, None) => None,
+        (old, new) => {
+            let empty = Node::default();
+            let old = old.as_deref().unwrap_or(&empty);
+            let new = new.as_deref().unwrap_or(&empty);
+            Some(diff_nodes(old, new))
+        }
+    };
+    diff.items_diff = items.filter(|d| !d.is_empty()).map(Box::new);
+    diff
+}
+
+/// `diff_nodes` over two schemas reconstructed with `Node::from_json_schema`,
+/// rendered as pretty-printed JSON.
+pub fn diff_schema_nodes_json_rs(a: &str, b: &str) -> Result<String, InferError> {
+    let a = Node::from_json_schema(&parse_schema(a, "schema A")?)?;
+    let b = Node::from_json_schema(&parse_schema(b, "schema B")?)?;
+    serde_json::to_string_pretty(&diff_nodes(&a, &b).to_json()).map_err(InferError::SerializeError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_samples;
+
+    fn node(samples: &[&str]) -> Node {
+        parse_samples(samples, &InferOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn diff_keeps_hierarchy_and_item_type_changes() {
+        let a = node(&[r#"{"id":1,"old":true,"rows":[{"v":1}],"tags":["a"]}"#]);
+        let b = node(&[r#"{"id":2,"new":null,"rows":[{"v":"x"}],"tags":[1.5]}"#]);
+        let d = diff_nodes(&a, &b);
+
+        assert!(d.type_added.is_empty() && d.type_removed.is_empty());
+        assert_eq!(d.properties_added.keys().collect::<Vec<_>>(), vec!["new"]);
+        assert_eq!(d.properties_removed.keys().collect::<Vec<_>>(), vec!["old"]);
+        assert!(!d.properties_changed.contains_key("id"));
+
+        let tags = d.properties_changed["tags"].items_diff.as_ref().unwrap();
+        assert_eq!(tags.type_added, AHashSet::from_iter([TypeTag::Number]));
+        assert_eq!(tags.type_removed, AHashSet::from_iter([TypeTag::String]));
+
+        let rows = d.properties_changed["rows"].items_diff.as_ref().unwrap();
+        let v = &rows.properties_changed["v"];
+        assert_eq!(v.type_added, AHashSet::from_iter([TypeTag::String]));
+        assert_eq!(v.type_removed, AHashSet::from_iter([TypeTag::Integer]));
+    }
+
+    #[test]
+    fn node_diff_json_from_schemas() {
+        let a =
+            r#"{"type":"object","properties":{"t":{"type":"array","items":{"type":"string"}}}}"#;
+        let b = r#"{"type":"object","properties":{"t":{"type":"array","items":{"type":["integer","string"]}}}}"#;
+        let v: Value = serde_json::from_str(&diff_schema_nodes_json_rs(a, b).unwrap()).unwrap();
+        assert_eq!(
+            v["properties_changed"]["t"]["items_diff"]["type_added"],
+            json!(["integer"])
+        );
+        assert_eq!(
+            v["properties_changed"]["t"]["items_diff"]["items_diff"],
+            Value::Null
+        );
+    }
+}
@@ -0,0 +1,451 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+use crate::Annotation;
+
+/// How a field that was observed both as `null` and as another type is rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum NullableStyle {
+    /// `"type": ["null", "string"]`
+    #[default]
+    TypeArray,
+    /// `"anyOf": [{"type": "string"}, {"type": "null"}]`
+    AnyOf,
+    /// `"type": "string", "nullable": true` (OpenAPI 3.0 / draft-07 tooling);
+    /// with `SchemaDraft::Draft04`, which has no `nullable`, as `TypeArray`
+    Nullable,
+}
+
+/// How a field that was `null` in every observation is rendered; the real
+/// type has usually just not been seen yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum NullOnlyFields {
+    /// `{"type": "null"}`
+    #[default]
+    Keep,
+    /// `{"type": "null", "x-aif-unknown": true}`, and the field is not `required`.
+    Annotate,
+    /// `{}` (any value), and the field is not `required`.
+    Open,
+}
+
+/// Whether inferred objects reject keys that were never observed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AdditionalProperties {
+    /// No `additionalProperties` keyword.
+    #[default]
+    Allow,
+    /// `additionalProperties: false` on every object node with `properties`.
+    Deny,
+    /// `additionalProperties: false` on the root object only.
+    DenyAtRoot,
+}
+
+/// How arrays of one fixed length are described.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ArrayMode {
+    /// One `items` schema for every element (tuples only via `detect_tuples`).
+    #[default]
+    Items,
+    /// `prefixItems` per position whenever all arrays had the same length
+    /// (up to `tuple_max_len`) and every position held a single type.
+    PrefixItems,
+}
+
+/// How a field seen both as an object or array and as another non-null type
+/// (`"metadata": "n/a"` next to `"metadata": {...}`) is rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ConflictStrategy {
+    /// One schema with a `type` array and the keywords of every type.
+    #[default]
+    TypeArray,
+    /// `anyOf` with one branch per type, each with only its own keywords.
+    AnyOf,
+    /// Only the most frequent type (`integer` and `number` count together);
+    /// the other types and their counts go to `x-aif-dropped-types`.
+    /// `null` is kept.
+    Dominant,
+}
+
+/// JSON Schema dialect of the output: the `$schema` URI and the spelling of
+/// tuples and definitions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SchemaDraft {
+    /// Tuples as in draft-07, `definitions`, `exclusiveMinimum: true` next to
+    /// `minimum`.
+    /// `const` becomes a one-value `enum`; keywords draft-04 lacks (`contains`,
+    /// `if`, `propertyNames`, ...) are dropped and listed in
+    /// `x-aif-dropped-keywords`.
+    Draft04,
+    /// Tuples as `items: [...]` plus `additionalItems: false`, `definitions`.
+    Draft07,
+    /// Tuples as in draft-07, `$defs`.
+    Draft201909,
+    /// Tuples as `prefixItems` plus `items: false`, `$defs`.
+    #[default]
+    Draft202012,
+}
+
+impl SchemaDraft {
+    pub fn uri(self) -> &'static str {
+        match self {
+            SchemaDraft::Draft04 => "http://json-schema.org/draft-04/schema#",
+            SchemaDraft::Draft07 => "http://json-schema.org/draft-07/schema#",
+            SchemaDraft::Draft201909 => "https://json-schema.org/draft/2019-09/schema",
+            SchemaDraft::Draft202012 => "https://json-schema.org/draft/2020-12/schema",
+        }
+    }
+
+    /// Keyword holding reusable sub-schemas.
+    pub fn defs_keyword(self) -> &'static str {
+        match self {
+            SchemaDraft::Draft04 | SchemaDraft::Draft07 => "definitions",
+            _ => "$defs",
+        }
+    }
+}
+
+/// `if` / `then` / `else` for the objects holding `field`, a dot path to the
+/// discriminating property (`payment.kind`), split on whether it equals `value`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConditionalRule {
+    pub field: String,
+    pub value: Value,
+}
+
+/// What happens to samples that are not valid JSON or exceed `depth_limit`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ErrorMode {
+    /// The first bad sample fails the whole call.
+    #[default]
+    Strict,
+    /// Bad samples are skipped; `SchemaInferrer::finish_with_errors` and
+    /// `infer_schema_with_errors_rs` report them next to the schema.
+    Lenient,
+}
+
+/// Element shape that every array at `path`, a dot path such as `events` or
+/// `rows[].tags`, is checked to contain.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContainsRule {
+    pub path: String,
+    pub candidate: ContainsCandidate,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ContainsCandidate {
+    /// Any sub-schema, checked the way `validate_sample_rs` checks samples.
+    Schema(Value),
+    /// Objects whose fields equal the given values (`{"type": "init"}`).
+    Fields(BTreeMap<String, Value>),
+}
+
+impl ContainsCandidate {
+    /// The sub-schema emitted under `contains`.
+    pub fn to_schema(&self) -> Value {
+        match self {
+            ContainsCandidate::Schema(schema) => schema.clone(),
+            ContainsCandidate::Fields(fields) => json!({
+                "type": "object",
+                "properties": fields
+                    .iter()
+                    .map(|(k, v)| (k.clone(), json!({ "const": v })))
+                    .collect::<Map<String, Value>>(),
+                "required": fields.keys().collect::<Vec<_>>(),
+            }),
+        }
+    }
+}
+
+/// Knobs for `infer_schema_rs`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferOptions {
+    pub draft: SchemaDraft,
+    pub nullable_style: NullableStyle,
+    pub null_only_fields: NullOnlyFields,
+    /// Share of an object's observations (0.0–1.0) that must hold a key for it
+    /// to be `required`; each object counts its own observations, so a key of
+    /// an optional nested object is measured against that object alone.
+    pub required_threshold: f64,
+    /// Render multi-type fields as `anyOf` of single-type sub-schemas instead
+    /// of a `type` array; `null` becomes a plain `{"type": "null"}` branch.
+    pub use_any_of: bool,
+    /// Like `use_any_of`, but only for array items that mix objects or arrays
+    /// with other types (`[{"id": 1}, "ref"]`); each branch carries only the
+    /// keywords of its own type.
+    pub items_any_of: bool,
+    /// Rendering of nodes that mix structures with other types; `use_any_of`
+    /// and `items_any_of` take precedence where they apply.
+    pub conflict_strategy: ConflictStrategy,
+    /// Render fields seen as both `integer` and `number` as plain `number`,
+    /// which already covers integers. Off keeps both names, showing that the
+    /// field is only sometimes non-integral.
+    pub numeric_widening: bool,
+    /// Emit `minimum`/`maximum` from the observed numeric range.
+    pub emit_numeric_bounds: bool,
+    /// Replace the tight bound with `exclusiveMinimum: 0` (`exclusiveMaximum: 0`)
+    /// for fields that were never observed at or below (above) zero.
+    pub exclusive_zero_bounds: bool,
+    /// Numeric observations required before `exclusive_zero_bounds` applies.
+    pub exclusive_bounds_min_observations: u64,
+    /// Emit `multipleOf` from the GCD of integer values, or the coarsest of
+    /// 0.5/0.25/0.1/0.05/0.01 that divides every fractional value.
+    pub detect_multiple_of: bool,
+    /// Emit `multipleOf: 0.01` (0.1, 0.001, ...) for fractional fields from
+    /// the most decimal places any value was written with (`5.00` has two,
+    /// `1.5e-3` four); takes precedence over `detect_multiple_of`.
+    pub detect_decimal_places: bool,
+    /// More decimal places than this are not a fixed precision.
+    pub decimal_max_places: u32,
+    /// Add `x-aif-semantic: "unix-timestamp-seconds"` (or `-milliseconds`) to
+    /// integer fields whose every value falls inside `epoch_window`.
+    pub detect_epoch_timestamps: bool,
+    /// Accepted range in seconds since 1970, both ends inclusive; for
+    /// milliseconds the same range is scaled by 1000. Defaults to 2001..2100.
+    pub epoch_window: (i64, i64),
+    /// Integer observations needed before a field is called a timestamp.
+    pub epoch_min_observations: u64,
+    /// Emit `minLength`/`maxLength` (in Unicode scalar values) for string fields.
+    pub emit_string_bounds: bool,
+    /// Emit `minItems`/`maxItems` from the observed array lengths.
+    pub emit_array_bounds: bool,
+    /// Emit `minProperties`/`maxProperties` from the observed key counts.
+    pub emit_property_counts: bool,
+    /// Emit `uniqueItems: true` for array fields where no array held two
+    /// structurally equal elements.
+    pub detect_unique_items: bool,
+    /// Arrays longer than this are not checked for duplicates (the check is
+    /// quadratic) and rule `uniqueItems` out for their field.
+    pub unique_items_max_len: usize,
+    /// Treat fixed-length arrays whose positions hold different types as
+    /// tuples: one schema per position, spelled as `draft` requires.
+    pub detect_tuples: bool,
+    /// Whether fixed-length arrays get `prefixItems`; see `ArrayMode`.
+    pub array_mode: ArrayMode,
+    /// Longer arrays are never tracked per position.
+    pub tuple_max_len: usize,
+    /// Keep a reservoir sample of distinct scalar values per node and emit
+    /// it as `examples`.
+    pub include_examples: bool,
+    /// Reservoir size for `include_examples`; 0 keeps no examples.
+    pub examples_per_field: usize,
+    /// Emit a `title` for every property derived from its key
+    /// (`firstName` and `first_name` both become `First Name`).
+    pub generate_titles: bool,
+    /// Longer string examples are cut to this many characters plus `…`.
+    pub example_max_len: usize,
+    /// Emit the most frequent scalar value of a field as `default` when it
+    /// makes up at least `default_min_share` of the field's observations.
+    pub emit_default: bool,
+    /// Share of observations (0.0–1.0) the most frequent value needs for `default`.
+    pub default_min_share: f64,
+    /// Emit `const` for scalar fields that held one value in every observation.
+    pub detect_const: bool,
+    /// Emit `format` for string fields whose every value matches a known format
+    /// (date-time, date, time, duration, uuid, ipv4, ipv6, email, uri, hostname,
+    /// json-pointer).
+    pub detect_formats: bool,
+    /// Emit an anchored `pattern` when every string of a field shares one of a
+    /// few conservative shapes (fixed-length digits or hex, prefix + digits,
+    /// `[A-Z]+-[0-9]+` codes). Skipped when a `format` is emitted.
+    pub detect_patterns: bool,
+    /// Emit `contentEncoding: base64` for string fields whose every value is
+    /// canonical padded base64, plus `contentMediaType` when all of them
+    /// decode to PNG, JPEG, gzip or PDF data.
+    pub detect_base64: bool,
+    /// Shorter strings never count as base64 (`"abcd"` is valid base64 too).
+    pub base64_min_len: usize,
+    /// Add `x-aif-suggested-type: "integer"` (or `"number"`) to string fields
+    /// whose every value spelled a number; the type stays `string`.
+    pub detect_numeric_strings: bool,
+    /// With `detect_numeric_strings`, emit `type: ["string"]` with a `pattern`
+    /// matching the numbers instead of the extension keyword.
+    pub numeric_string_pattern: bool,
+    /// Add `x-aif-suggested-type: "boolean"` and an `enum` of the spellings to
+    /// string fields whose every value is in the truth-word vocabulary below.
+    pub detect_boolean_strings: bool,
+    /// Words read as true by `detect_boolean_strings`, in any case.
+    pub boolean_true_strings: Vec<String>,
+    /// Words read as false by `detect_boolean_strings`, in any case.
+    pub boolean_false_strings: Vec<String>,
+    /// A field that only ever saw one truth value needs this many observations.
+    pub boolean_string_min_observations: u64,
+    /// Most strings of a field checked against the formats; a field with more
+    /// strings gets no `format`. Unlimited by default.
+    pub format_sample_size: u64,
+    /// With `detect_formats`, describe fields mixing IPv4 and IPv6 values as
+    /// `anyOf` of the two formats instead of leaving them unformatted.
+    pub ip_any_of: bool,
+    /// Collapse objects with too many distinct keys into a dictionary:
+    /// `{"type": "object", "additionalProperties": <value schema>}`.
+    pub detect_maps: bool,
+    /// Distinct keys an object node may collect before it becomes a map.
+    pub map_key_threshold: usize,
+    /// With `detect_maps`, only collapse when every key looks like an ID
+    /// (digits, hex, uuid, `prefix_123`, ...).
+    pub map_keys_look_like_ids: bool,
+    /// Emit `propertyNames: {"pattern": ...}` for objects whose keys all
+    /// look like UUIDs, `YYYY-MM-DD` dates or identifiers.
+    pub infer_property_names: bool,
+    /// Track which keys of an object appear together and emit
+    /// `dependentRequired` (draft-07 `dependencies`) for keys that were always
+    /// accompanied by the same optional keys.
+    pub detect_dependent_required: bool,
+    /// Objects with more distinct keys than this stop tracking co-occurrence.
+    pub dependent_required_max_properties: usize,
+    /// Fewest objects holding a key before its dependencies are emitted.
+    pub dependent_required_min_support: u64,
+    /// Where `additionalProperties: false` is emitted; maps are exempt.
+    pub additional_properties: AdditionalProperties,
+    /// Render every root as `"type": "object"` with its properties, as before
+    /// array and scalar roots were supported; such samples then add nothing.
+    pub legacy_object_root: bool,
+    /// Root field whose value routes each sample into its own `oneOf` branch
+    /// (batch APIs only); samples without it go to a catch-all branch.
+    pub discriminator: Option<String>,
+    /// Without `discriminator`, pick a root string field present in every
+    /// sample, with few values that split the samples into different shapes.
+    pub detect_discriminator: bool,
+    /// Most distinct values a detected discriminator may take.
+    pub discriminator_max_values: usize,
+    /// Conditional sub-schemas inferred per rule (batch APIs only); rules
+    /// whose value never occurs are skipped.
+    pub conditional_rules: Vec<ConditionalRule>,
+    /// `contains` (and `minContains` from 2019-09 on, when more than one
+    /// match was guaranteed) for arrays whose every observation held the
+    /// rule's candidate (batch APIs only); one empty array drops the rule.
+    pub contains_rules: Vec<ContainsRule>,
+    /// Emit `properties` in the order keys were first observed; otherwise
+    /// they are sorted by name.
+    pub preserve_insertion_order: bool,
+    /// Move object sub-schemas that repeat at two or more paths into `$defs`
+    /// and reference them with `$ref`.
+    pub extract_defs: bool,
+    /// Smallest number of properties an object needs to be moved to `$defs`.
+    pub defs_min_properties: usize,
+    /// Replace an object sub-schema with the same property names and types as
+    /// an enclosing one by a `$ref` to it (`#` for the root, otherwise a
+    /// `$defs` entry), so tree-shaped data validates at any depth.
+    pub detect_recursion: bool,
+    /// Smallest number of properties an object needs to take part in
+    /// recursion detection.
+    pub recursion_min_properties: usize,
+    /// How many enclosing objects, nearest first, a sub-schema is compared with.
+    pub recursion_max_depth: usize,
+    /// Add `x-aif-frequency: {count, total}` to every property: how many of the
+    /// objects holding it had the key. `strip_extensions_rs` removes it again.
+    pub annotate_frequency: bool,
+    /// Add `x-aif-presence`, the share of the objects holding a property that
+    /// had the key, to every property; shows what `required_threshold` saw.
+    pub annotate_presence: bool,
+    /// Add `x-aif-types` with the exact count per type to nodes that saw more
+    /// than one type, most frequent first.
+    pub annotate_types: bool,
+    /// Mark objects that were only ever `{}` with `x-aif-empty: true` instead
+    /// of `maxProperties: 0`.
+    pub annotate_empty_objects: bool,
+    /// Titles and descriptions keyed by dot path (`rows[].id`) or JSON Pointer
+    /// (`/properties/rows`); see `infer_schema_annotated_rs` for the keys that
+    /// matched nothing.
+    pub annotations: BTreeMap<String, Annotation>,
+    /// Most levels of nested objects and arrays a sample may open; deeper
+    /// samples fail with `InferError::DepthLimitExceeded`.
+    pub depth_limit: Option<usize>,
+    pub error_mode: ErrorMode,
+    /// Hard limit on distinct values tracked per node for enum detection; once
+    /// a node exceeds it, tracking stops for good.
+    pub enum_cap: usize,
+    /// `enum` is emitted only for fewer distinct strings or integers than this.
+    pub enum_threshold: usize,
+    /// Minimum number of observations before `enum` is emitted.
+    pub enum_min_observations: u64,
+}
+
+impl Default for InferOptions {
+    fn default() -> Self {
+        Self {
+            draft: SchemaDraft::default(),
+            nullable_style: NullableStyle::default(),
+            null_only_fields: NullOnlyFields::default(),
+            required_threshold: 1.0,
+            use_any_of: false,
+            items_any_of: false,
+            conflict_strategy: ConflictStrategy::default(),
+            numeric_widening: true,
+            emit_numeric_bounds: false,
+            exclusive_zero_bounds: false,
+            exclusive_bounds_min_observations: 10,
+            detect_multiple_of: false,
+            detect_decimal_places: false,
+            decimal_max_places: 4,
+            detect_epoch_timestamps: false,
+            epoch_window: (978_307_200, 4_102_444_800),
+            epoch_min_observations: 20,
+            emit_string_bounds: false,
+            emit_array_bounds: false,
+            emit_property_counts: false,
+            detect_unique_items: false,
+            unique_items_max_len: 100,
+            detect_tuples: false,
+            array_mode: ArrayMode::Items,
+            tuple_max_len: 8,
+            include_examples: false,
+            examples_per_field: 3,
+            generate_titles: false,
+            example_max_len: 64,
+            emit_default: false,
+            default_min_share: 0.8,
+            detect_const: true,
+            detect_formats: false,
+            detect_patterns: false,
+            detect_base64: false,
+            base64_min_len: 16,
+            detect_numeric_strings: false,
+            numeric_string_pattern: false,
+            detect_boolean_strings: false,
+            boolean_true_strings: vec!["true".to_string(), "yes".to_string()],
+            boolean_false_strings: vec!["false".to_string(), "no".to_string()],
+            boolean_string_min_observations: 20,
+            format_sample_size: u64::MAX,
+            ip_any_of: false,
+            detect_maps: false,
+            map_key_threshold: 50,
+            map_keys_look_like_ids: false,
+            infer_property_names: false,
+            detect_dependent_required: false,
+            dependent_required_max_properties: 32,
+            dependent_required_min_support: 3,
+            additional_properties: AdditionalProperties::default(),
+            legacy_object_root: false,
+            discriminator: None,
+            detect_discriminator: false,
+            discriminator_max_values: 10,
+            conditional_rules: Vec::new(),
+            contains_rules: Vec::new(),
+            preserve_insertion_order: false,
+            extract_defs: false,
+            defs_min_properties: 2,
+            detect_recursion: false,
+            recursion_min_properties: 2,
+            recursion_max_depth: 8,
+            annotate_frequency: false,
+            annotate_presence: false,
+            annotate_types: false,
+            annotate_empty_objects: false,
+            annotations: BTreeMap::new(),
+            depth_limit: None,
+            error_mode: ErrorMode::default(),
+            enum_cap: 32,
+            enum_threshold: 20,
+            enum_min_observations: 20,
+        }
+    }
+}
+
+impl InferOptions {
+    // Позиции массивов нужны и для кортежей, и для режима PrefixItems
+    pub(crate) fn tracks_positions(&self) -> bool {
+        self.detect_tuples || self.array_mode == ArrayMode::PrefixItems
+    }
+}
@@ -0,0 +1,191 @@
+use serde_json::{json, Value};
+
+use crate::diff::parse_schema;
+use crate::InferError;
+
+/// RFC 6902 JSON Patch (`add`, `remove` and `replace` operations with JSON
+/// Pointer paths) that turns schema `a` into schema `b`.
+pub fn schema_patch_rs(a: &str, b: &str) -> Result<String, InferError> {
+    let va = parse_schema(a, "schema A")?;
+    let vb = parse_schema(b, "schema B")?;
+    let mut ops = Vec::new();
+    diff_values(&va, &vb, "", &mut ops);
+    serde_json::to_string_pretty(&Value::Array(ops)).map_err(InferError::SerializeError)
+}
+
+/// Applies a JSON Patch to `schema`. Only `add`, `remove` and `replace` are
+/// supported; operations run in order and the first failing one aborts.
+pub fn apply_schema_patch_rs(schema: &str, patch: &str) -> Result<String, InferError> {
+    let mut doc = parse_schema(schema, "schema")?;
+    let patch = parse_schema(patch, "patch")?;
+    let ops = patch.as_array().ok_or_else(|| InferError::InvalidPatch {
+        reason: "patch must be an array of operations".to_string(),
+    })?;
+    for (i, op) in ops.iter().enumerate() {
+        apply_op(&mut doc, op).map_err(|reason| InferError::InvalidPatch {
+            reason: format!("operation {i}: {reason}"),
+        })?;
+    }
+    serde_json::to_string_pretty(&doc).map_err(InferError::SerializeError)
+}
+
+fn child(path: &str, token: &str) -> String {
+    format!("{path}/{}", token.replace('~', "~0").replace('/', "~1"))
+}
+
+fn diff_values(a: &Value, b: &Value, path: &str, ops: &mut Vec<Value>) {
+    match (a, b) {
+        (Value::Object(x), Value::Object(y)) => {
+            for k in x.keys().filter(|k| !y.contains_key(*k)) {
+                ops.push(json!({ "op": "remove", "path": child(path, k) }));
+            }
+            for (k, new) in y {
+                match x.get(k) {
+                    Some(old) => diff_values(old, new, &child(path, k), ops),
+                    None => ops.push(json!({ "op": "add", "path": child(path, k), "value": new })),
+                }
+            }
+        }
+        (Value::Array(x), Value::Array(y)) => {
+            for (i, (old, new)) in x.iter().zip(y).enumerate() {
+                diff_values(old, new, &format!("{path}/{i}"), ops);
+            }
+            // лишние элементы удаляем с конца, чтобы индексы не сдвигались
+            for i in (y.len()..x.len()).rev() {
+                ops.push(json!({ "op": "remove", "path": format!("{path}/{i}") }));
+            }
+            for (i, new) in y.iter().enumerate().skip(x.len()) {
+                ops.push(json!({ "op": "add", "path": format!("{path}/{i}"), "value": new }));
+            }
+        }
+        _ if a != b => ops.push(json!({ "op": "replace", "path": path, "value": b })),
+        _ => {}
+    }
+}
+
+fn apply_op(doc: &mut Value, op: &Value) -> Result<(), String> {
+    let field = |name: &str| op.get(name).ok_or_else(|| format!("missing {name}"));
+    let kind = field("op")?.as_str().ok_or("op must be a string")?;
+    let path = field("path")?.as_str().ok_or("path must be a string")?;
+    let value = || field("value").cloned();
+
+    // корень заменяется целиком
+    if path.is_empty() {
+        return match kind {
+            "add" | "replace" => {
+                *doc = value()?;
+                Ok(())
+            }
+            "remove" => Err("cannot remove the root".to_string()),
+            other => Err(format!("unsupported op {other}")),
+        };
+    }
+    let (parent, token) = path
+        .rsplit_once('/')
+        .filter(|(parent, _)| parent.is_empty() || parent.starts_with('/'))
+        .ok_or_else(|| format!("{path} is not a JSON Pointer"))?;
+    let token = token.replace("~1", "/").replace("~0", "~");
+    let missing = || format!("{path} does not exist");
+    match doc.pointer_mut(parent).ok_or_else(missing)? {
+        Value::Object(m) => match kind {
+            "add" => {
+                m.insert(token, value()?);
+            }
+            "replace" => *m.get_mut(&token).ok_or_else(missing)? = value()?,
+            "remove" => {
+                m.shift_remove(&token).ok_or_else(missing)?;
+            }
+            other => return Err(format!("unsupported op {other}")),
+        },
+        Value::Array(items) => {
+            let index = match token.as_str() {
+                "-" if kind == "add" => items.len(),
+                t => t
+                    .parse::<usize>()
+                    .map_err(|_| format!("bad array index in {path}"))?,
+            };
+            match kind {
+                "add" if index <= items.len() => items.insert(index, value()?),
+                "replace" if index < items.len() => items[index] = value()?,
+                "remove" if index < items.len() => {
+                    items.remove(index);
+                }
+                "add" | "replace" | "remove" => return Err(missing()),
+                other => return Err(format!("unsupported op {other}")),
+            }
+        }
+        _ => return Err(format!("parent of {path} is not an object or array")),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{infer_schema_from_strs, InferOptions};
+
+    fn round_trip(a: &str, b: &str) -> Value {
+        let patch = schema_patch_rs(a, b).unwrap();
+        let patched: Value =
+            serde_json::from_str(&apply_schema_patch_rs(a, &patch).unwrap()).unwrap();
+        assert_eq!(patched, serde_json::from_str::<Value>(b).unwrap());
+        serde_json::from_str(&patch).unwrap()
+    }
+
+    #[test]
+    fn patch_round_trips_inferred_schemas() {
+        let opts = InferOptions {
+            emit_array_bounds: true,
+            ..Default::default()
+        };
+        let a = infer_schema_from_strs(
+            &[r#"{"id":1,"a/b":"x","tags":["t"],"gone":true}"#],
+            opts.clone(),
+        )
+        .unwrap();
+        let b = infer_schema_from_strs(
+            &[r#"{"id":"1","a/b":"x","tags":["t","u",1],"new":{"k":null}}"#],
+            opts,
+        )
+        .unwrap();
+        let patch = round_trip(&a, &b);
+        let ops: Vec<(&str, &str)> = patch
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|op| (op["op"].as_str().unwrap(), op["path"].as_str().unwrap()))
+            .collect();
+        assert!(ops.contains(&("remove", "/properties/gone")));
+        assert!(ops.contains(&("replace", "/properties/id/type")));
+        assert!(ops.contains(&("add", "/properties/new")));
+        assert!(!ops.iter().any(|(_, p)| p.starts_with("/properties/a~1b")));
+
+        round_trip(&b, &a);
+        round_trip(r#"{"enum":[1,2,3,4]}"#, r#"{"enum":[1,5]}"#);
+        round_trip(r#"{"type":"object"}"#, "true");
+        assert_eq!(round_trip(&a, &a), json!([]));
+    }
+
+    #[test]
+    fn apply_rejects_bad_operations() {
+        let schema = r#"{"properties":{"id":{"type":"integer"}},"required":["id"]}"#;
+        let err = |patch: &str| {
+            apply_schema_patch_rs(schema, patch)
+                .unwrap_err()
+                .to_string()
+        };
+        assert!(err(r#"{"op":"add"}"#).contains("array"));
+        assert!(err(r#"[{"op":"remove","path":"/properties/nope"}]"#).contains("does not exist"));
+        assert!(err(r#"[{"op":"move","from":"/a","path":"/b"}]"#).contains("unsupported op move"));
+        assert!(err(r#"[{"op":"add","path":"/required/7","value":"x"}]"#).contains("operation 0"));
+        assert!(err(r#"[{"op":"replace","path":"/properties/id"}]"#).contains("missing value"));
+
+        let out = apply_schema_patch_rs(
+            schema,
+            r#"[{"op":"add","path":"/required/-","value":"x"},{"op":"remove","path":"/required/0"}]"#,
+        )
+        .unwrap();
+        let v: Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(v["required"], json!(["x"]));
+    }
+}
@@ -0,0 +1,523 @@
+use ahash::AHashSet;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::formats::is_uuid;
+
+/// Range of lengths seen for one part of a shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct Span {
+    min: usize,
+    max: usize,
+}
+
+impl Span {
+    fn new(n: usize) -> Span {
+        Span { min: n, max: n }
+    }
+
+    fn widen(self, other: Span) -> Span {
+        Span {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    fn quantifier(self) -> String {
+        if self.min == self.max {
+            format!("{{{}}}", self.min)
+        } else {
+            format!("{{{},{}}}", self.min, self.max)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct Hex {
+    len: usize,
+    lower: bool,
+    upper: bool,
+}
+
+/// Conservative string shapes that every value of a field still shares:
+/// fixed-length digits, fixed-length hex, a literal prefix followed by
+/// digits, and `[A-Z]+-[0-9]+` codes. Narrowed with every observed value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatternTracker {
+    digits: Option<usize>,
+    hex: Option<Hex>,
+    prefixed: Option<(String, Span)>,
+    code: Option<(Span, Span)>,
+}
+
+/// Shape shared by every key of a dictionary node, for `propertyNames`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyShape {
+    seen: bool,
+    uuid: bool,
+    patterns: Option<PatternTracker>,
+}
+
+impl KeyShape {
+    fn of(key: &str) -> KeyShape {
+        KeyShape {
+            seen: true,
+            uuid: is_uuid(key),
+            patterns: Some(PatternTracker::new(key)).filter(|p| !p.is_empty()),
+        }
+    }
+
+    pub fn observe(&mut self, key: &str) {
+        self.merge(KeyShape::of(key));
+    }
+
+    /// Keeps what the keys behind both shapes have in common.
+    pub fn merge(&mut self, other: KeyShape) {
+        if !other.seen {
+            return;
+        }
+        if !self.seen {
+            *self = other;
+            return;
+        }
+        self.uuid &= other.uuid;
+        self.patterns = match (self.patterns.take(), other.patterns) {
+            (Some(mut a), Some(b)) => {
+                a.merge(b);
+                Some(a).filter(|p| !p.is_empty())
+            }
+            _ => None,
+        };
+    }
+
+    /// Every key was a UUID.
+    pub fn uuids() -> KeyShape {
+        KeyShape {
+            seen: true,
+            uuid: true,
+            patterns: None,
+        }
+    }
+
+    /// `{"format": "uuid"}` or `{"pattern": ...}`; None for heterogeneous keys.
+    pub fn property_names(&self) -> Option<Value> {
+        if self.uuid {
+            return Some(json!({ "format": "uuid" }));
+        }
+        let pattern = self.patterns.as_ref()?.pattern()?;
+        Some(json!({ "pattern": pattern }))
+    }
+}
+
+type KeyCheck = fn(&str) -> bool;
+
+// Кандидаты для propertyNames обычных объектов, от самого узкого к общему:
+// uuid, ключ-дата, идентификатор
+const KEY_PATTERNS: [(&str, KeyCheck); 3] = [
+    (
+        "^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$",
+        is_uuid,
+    ),
+    ("^[0-9]{4}-[0-9]{2}-[0-9]{2}$", is_date_key),
+    ("^[A-Za-z_][A-Za-z0-9_]*$", is_identifier),
+];
+
+/// `propertyNames` candidates (uuid, date key, identifier) that every key of
+/// an object node still matches.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyPatterns {
+    // биты KEY_PATTERNS
+    candidates: u8,
+    // ключи, уже проверенные по кандидатам
+    checked: AHashSet<String>,
+}
+
+impl Default for KeyPatterns {
+    fn default() -> KeyPatterns {
+        KeyPatterns {
+            candidates: (1 << KEY_PATTERNS.len()) - 1,
+            checked: AHashSet::new(),
+        }
+    }
+}
+
+impl KeyPatterns {
+    /// Only the candidate whose regex is `pattern`, if it is one of them.
+    pub fn from_pattern(pattern: &str) -> Option<KeyPatterns> {
+        let i = KEY_PATTERNS.iter().position(|(p, _)| *p == pattern)?;
+        Some(KeyPatterns {
+            candidates: 1 << i,
+            checked: AHashSet::new(),
+        })
+    }
+
+    /// Drops the candidates `key` does not match; false once none is left.
+    pub fn observe(&mut self, key: &str) -> bool {
+        if !self.checked.contains(key) {
+            for (i, (_, matches)) in KEY_PATTERNS.iter().enumerate() {
+                if !matches(key) {
+                    self.candidates &= !(1 << i);
+                }
+            }
+            self.checked.insert(key.to_string());
+        }
+        self.keep()
+    }
+
+    /// Keeps the candidates both sides still have; false once none is left.
+    pub fn merge(&mut self, other: KeyPatterns) -> bool {
+        self.candidates &= other.candidates;
+        self.checked.extend(other.checked);
+        self.keep()
+    }
+
+    // ключи нужны только пока есть что проверять
+    fn keep(&mut self) -> bool {
+        if self.candidates == 0 {
+            self.checked.clear();
+        }
+        self.candidates != 0
+    }
+
+    /// The narrowest candidate every key matched.
+    pub fn pattern(&self) -> Option<&'static str> {
+        KEY_PATTERNS
+            .iter()
+            .enumerate()
+            .find(|(i, _)| self.candidates & (1 << i) != 0)
+            .map(|(_, (p, _))| *p)
+    }
+}
+
+fn is_date_key(key: &str) -> bool {
+    let b = key.as_bytes();
+    b.len() == 10
+        && b.iter().enumerate().all(|(i, c)| match i {
+            4 | 7 => *c == b'-',
+            _ => c.is_ascii_digit(),
+        })
+}
+
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// Короче — слишком похоже на обычные слова
+const MIN_HEX_LEN: usize = 4;
+
+impl PatternTracker {
+    pub fn new(s: &str) -> PatternTracker {
+        let mut tracker = PatternTracker {
+            digits: digits_len(s),
+            hex: hex_shape(s),
+            prefixed: prefixed_shape(s),
+            code: code_shape(s),
+        };
+        tracker.verify(s);
+        tracker
+    }
+
+    /// Drops every shape `s` does not have, and every shape whose regex does
+    /// not match `s`.
+    pub fn observe(&mut self, s: &str) {
+        self.merge(PatternTracker::new(s));
+        self.verify(s);
+    }
+
+    /// Keeps the shapes shared by the values behind both trackers.
+    pub fn merge(&mut self, other: PatternTracker) {
+        self.digits = self.digits.filter(|n| other.digits == Some(*n));
+        self.hex = match (self.hex, other.hex) {
+            (Some(a), Some(b)) if a.len == b.len => Some(Hex {
+                len: a.len,
+                lower: a.lower || b.lower,
+                upper: a.upper || b.upper,
+            }),
+            _ => None,
+        };
+        self.prefixed = match (self.prefixed.take(), other.prefixed) {
+            (Some((p, a)), Some((q, b))) if p == q => Some((p, a.widen(b))),
+            _ => None,
+        };
+        self.code = match (self.code, other.code) {
+            (Some((la, da)), Some((lb, db))) => Some((la.widen(lb), da.widen(db))),
+            _ => None,
+        };
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.digits.is_none()
+            && self.hex.is_none()
+            && self.prefixed.is_none()
+            && self.code.is_none()
+    }
+
+    /// The most specific surviving shape as an anchored regex.
+    pub fn pattern(&self) -> Option<String> {
+        self.candidates().into_iter().flatten().next()
+    }
+
+    // Выражения уцелевших форм, от самой узкой к общей
+    fn candidates(&self) -> [Option<String>; 4] {
+        let hex = self.hex.map(|h| {
+            let class = match (h.lower, h.upper) {
+                (true, true) => "[0-9a-fA-F]",
+                (false, true) => "[0-9A-F]",
+                _ => "[0-9a-f]",
+            };
+            format!("^{class}{{{}}}$", h.len)
+        });
+        [
+            self.digits.map(|n| format!("^[0-9]{{{n}}}$")),
+            hex,
+            self.prefixed.as_ref().map(|(prefix, digits)| {
+                format!("^{}[0-9]{}$", escape(prefix), digits.quantifier())
+            }),
+            self.code.map(|(letters, digits)| {
+                format!(
+                    "^[A-Z]{}-[0-9]{}$",
+                    letters.quantifier(),
+                    digits.quantifier()
+                )
+            }),
+        ]
+    }
+
+    // Снимает формы, чьё сгенерированное выражение не принимает s. Формы только
+    // расширяются, так что принятые раньше значения подходят и дальше.
+    fn verify(&mut self, s: &str) {
+        let chars: Vec<char> = s.chars().collect();
+        let [digits, hex, prefixed, code] = self.candidates().map(|p| {
+            p.and_then(|p| parse_generated(&p))
+                .is_some_and(|atoms| matches_atoms(&atoms, &chars))
+        });
+        if !digits {
+            self.digits = None;
+        }
+        if !hex {
+            self.hex = None;
+        }
+        if !prefixed {
+            self.prefixed = None;
+        }
+        if !code {
+            self.code = None;
+        }
+    }
+}
+
+fn digits_len(s: &str) -> Option<usize> {
+    (!s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())).then_some(s.len())
+}
+
+fn hex_shape(s: &str) -> Option<Hex> {
+    if s.len() < MIN_HEX_LEN || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some(Hex {
+        len: s.len(),
+        lower: s.bytes().any(|b| b.is_ascii_lowercase()),
+        upper: s.bytes().any(|b| b.is_ascii_uppercase()),
+    })
+}
+
+// Непустой префикс без цифры на конце и непустой хвост из цифр
+fn prefixed_shape(s: &str) -> Option<(String, Span)> {
+    let prefix = s.trim_end_matches(|c: char| c.is_ascii_digit());
+    let digits = s.len() - prefix.len();
+    (!prefix.is_empty() && digits > 0).then(|| (prefix.to_string(), Span::new(digits)))
+}
+
+fn code_shape(s: &str) -> Option<(Span, Span)> {
+    let (letters, digits) = s.split_once('-')?;
+    let ok = !letters.is_empty()
+        && letters.bytes().all(|b| b.is_ascii_uppercase())
+        && digits_len(digits).is_some();
+    ok.then(|| (Span::new(letters.len()), Span::new(digits.len())))
+}
+
+// Шаг сгенерированного выражения: символ или класс из диапазонов, min..=max раз
+struct Atom {
+    ranges: Vec<(char, char)>,
+    min: usize,
+    max: usize,
+}
+
+// Разбирает только то, что строит pattern: ^, литералы (метасимволы через \),
+// классы из диапазонов, {n} и {n,m}, $; на всём прочем — None
+fn parse_generated(pattern: &str) -> Option<Vec<Atom>> {
+    let body = pattern.strip_prefix('^')?.strip_suffix('$')?;
+    let mut atoms: Vec<Atom> = Vec::new();
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        let ranges = match c {
+            '\\' => {
+                let c = chars.next()?;
+                vec![(c, c)]
+            }
+            '[' => {
+                let class: Vec<char> = chars.by_ref().take_while(|c| *c != ']').collect();
+                let mut ranges = Vec::new();
+                let mut i = 0;
+                while i < class.len() {
+                    if class.get(i + 1) == Some(&'-') && i + 2 < class.len() {
+                        ranges.push((class[i], class[i + 2]));
+                        i += 3;
+                    } else {
+                        ranges.push((class[i], class[i]));
+                        i += 1;
+                    }
+                }
+                ranges
+            }
+            '{' => {
+                let quantifier: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                let (min, max) = match quantifier.split_once(',') {
+                    Some((min, max)) => (min.parse().ok()?, max.parse().ok()?),
+                    None => {
+                        let n = quantifier.parse().ok()?;
+                        (n, n)
+                    }
+                };
+                let last = atoms.last_mut()?;
+                (last.min, last.max) = (min, max);
+                continue;
+            }
+            c if "^$.|?*+()]}".contains(c) => return None,
+            c => vec![(c, c)],
+        };
+        atoms.push(Atom {
+            ranges,
+            min: 1,
+            max: 1,
+        });
+    }
+    Some(atoms)
+}
+
+fn matches_atoms(atoms: &[Atom], s: &[char]) -> bool {
+    let Some((atom, rest)) = atoms.split_first() else {
+        return s.is_empty();
+    };
+    let run = s
+        .iter()
+        .take(atom.max)
+        .take_while(|c| atom.ranges.iter().any(|(lo, hi)| (lo..=hi).contains(c)))
+        .count();
+    // с отступлением: сначала самый длинный повтор
+    (atom.min..=run).rev().any(|n| matches_atoms(rest, &s[n..]))
+}
+
+fn escape(literal: &str) -> String {
+    let mut out = String::with_capacity(literal.len());
+    for c in literal.chars() {
+        if "\\^$.|?*+()[]{}/".contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(values: &[&str]) -> Option<String> {
+        let mut t = PatternTracker::new(values[0]);
+        for v in &values[1..] {
+            t.observe(v);
+        }
+        t.pattern()
+    }
+
+    #[test]
+    fn picks_the_most_specific_shape() {
+        assert_eq!(pattern(&["01234", "98765"]).unwrap(), "^[0-9]{5}$");
+        assert_eq!(pattern(&["0a1f", "99ff"]).unwrap(), "^[0-9a-f]{4}$");
+        assert_eq!(pattern(&["0A1F", "99ff"]).unwrap(), "^[0-9a-fA-F]{4}$");
+        assert_eq!(
+            pattern(&["user.7", "user.42"]).unwrap(),
+            "^user\\.[0-9]{1,2}$"
+        );
+        assert_eq!(
+            pattern(&["AB-1234", "XYZ-0001"]).unwrap(),
+            "^[A-Z]{2,3}-[0-9]{4}$"
+        );
+    }
+
+    #[test]
+    fn key_shape_drops_on_heterogeneous_keys() {
+        let mut keys = KeyShape::default();
+        assert_eq!(keys.property_names(), None);
+        keys.observe("user_1");
+        keys.observe("user_22");
+        assert_eq!(
+            keys.property_names(),
+            Some(json!({"pattern": "^user_[0-9]{1,2}$"}))
+        );
+        keys.observe("admin");
+        assert_eq!(keys.property_names(), None);
+
+        let mut keys = KeyShape::default();
+        keys.observe("6f1c2a8e-3b4d-4e5f-8a9b-0c1d2e3f4a5b");
+        assert_eq!(keys.property_names(), Some(json!({"format": "uuid"})));
+    }
+
+    #[test]
+    fn key_patterns_narrow_to_the_shared_candidate() {
+        let mut keys = KeyPatterns::default();
+        assert!(keys.observe("2024-01-31"));
+        assert_eq!(keys.pattern(), Some("^[0-9]{4}-[0-9]{2}-[0-9]{2}$"));
+        assert!(!keys.observe("user_id"));
+        assert_eq!(keys.pattern(), None);
+        assert!(keys.checked.is_empty());
+
+        let mut keys = KeyPatterns::default();
+        assert!(keys.observe("user_id"));
+        assert!(keys.observe("_Rev2"));
+        assert_eq!(keys.pattern(), Some("^[A-Za-z_][A-Za-z0-9_]*$"));
+        assert!(!keys.clone().observe("2nd"));
+        let uuid = KeyPatterns::from_pattern(KEY_PATTERNS[0].0).unwrap();
+        assert!(!keys.merge(uuid));
+        assert_eq!(KeyPatterns::from_pattern("^x$"), None);
+    }
+
+    #[test]
+    fn generated_patterns_are_checked_against_values() {
+        let mut t = PatternTracker::new("v1.2+x(7");
+        t.observe("v1.2+x(42");
+        let verified = t.pattern().unwrap();
+        assert_eq!(verified, r"^v1\.2\+x\([0-9]{1,2}$");
+        // значение проверено выражением каждой уцелевшей формы
+        let mut checked = PatternTracker::new("v1.2+x(7");
+        checked.prefixed = Some(("v1X2+x(".to_string(), Span::new(1)));
+        checked.verify("v1.2+x(7");
+        assert!(checked.is_empty());
+
+        let atoms = parse_generated(&verified).unwrap();
+        let matches = |s: &str| matches_atoms(&atoms, &s.chars().collect::<Vec<_>>());
+        assert!(matches("v1.2+x(0"));
+        assert!(!matches("v1X2+x(0"));
+        assert!(!matches("v1.2+x("));
+        let code = parse_generated("^[A-Z]{2,3}-[0-9]{4}$").unwrap();
+        assert!(matches_atoms(
+            &code,
+            &"ABC-0001".chars().collect::<Vec<_>>()
+        ));
+        assert!(!matches_atoms(&code, &"A-0001".chars().collect::<Vec<_>>()));
+        // неэкранированный метасимвол — не наша форма
+        assert!(parse_generated("^v1.2[0-9]{1}$").is_none());
+    }
+
+    #[test]
+    fn nothing_when_a_value_breaks_every_shape() {
+        assert_eq!(pattern(&["01234", "9876"]), None);
+        assert_eq!(pattern(&["AB-12", "hello"]), None);
+        assert_eq!(pattern(&["abc"]), None);
+    }
+}
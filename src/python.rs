@@ -0,0 +1,575 @@
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::pybacked::PyBackedStr;
+use pyo3::types::{PyBool, PyDict};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::{
+    apply_schema_patch_rs, diff_schema_nodes_json_rs, diff_schemas_json_rs,
+    infer_schema_annotated_rs, infer_schema_from_jsonl_rs, infer_schema_rs, merge_schemas_rs,
+    schema_coverage_json_rs, schema_fingerprint_rs, schema_patch_rs, schema_similarity_rs,
+    schema_similarity_typed_rs, schema_stats_rs, schema_to_sql_ddl_rs, schema_to_typescript_rs,
+    strip_extensions_rs, validate_sample_rs, AdditionalProperties, Annotation, ArrayMode,
+    ConditionalRule, ConflictStrategy, ContainsCandidate, ContainsRule, DiffOptions, ErrorMode,
+    InferError, InferOptions, Node, NullOnlyFields, NullableStyle, PathFormat, SchemaDraft,
+    SchemaInferrer, TypeTag,
+};
+
+impl From<InferError> for PyErr {
+    fn from(e: InferError) -> Self {
+        PyValueError::new_err(e.to_string())
+    }
+}
+
+// bool проверяется раньше int: в Python True — тоже целое
+fn py_scalar(value: &Bound<'_, PyAny>) -> PyResult<Value> {
+    if value.is_none() {
+        Ok(Value::Null)
+    } else if let Ok(b) = value.downcast::<PyBool>() {
+        Ok(Value::Bool(b.is_true()))
+    } else if let Ok(i) = value.extract::<i64>() {
+        Ok(Value::from(i))
+    } else if let Ok(x) = value.extract::<f64>() {
+        Ok(Value::from(x))
+    } else if let Ok(s) = value.extract::<String>() {
+        Ok(Value::String(s))
+    } else {
+        Err(PyTypeError::new_err(
+            "rule values must be None, bool, int, float or str",
+        ))
+    }
+}
+
+fn infer_options_from_kwargs(kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<InferOptions> {
+    let mut opts = InferOptions::default();
+    let Some(kwargs) = kwargs else {
+        return Ok(opts);
+    };
+    for (key, value) in kwargs.iter() {
+        let key: String = key.extract()?;
+        match key.as_str() {
+            "draft" => {
+                let draft: String = value.extract()?;
+                opts.draft = match draft.as_str() {
+                    "draft-04" => SchemaDraft::Draft04,
+                    "draft-07" => SchemaDraft::Draft07,
+                    "2019-09" => SchemaDraft::Draft201909,
+                    "2020-12" => SchemaDraft::Draft202012,
+                    other => return Err(PyValueError::new_err(format!("unknown draft: {other}"))),
+                };
+            }
+            "null_only_fields" => {
+                let mode: String = value.extract()?;
+                opts.null_only_fields = match mode.as_str() {
+                    "keep" => NullOnlyFields::Keep,
+                    "annotate" => NullOnlyFields::Annotate,
+                    "open" => NullOnlyFields::Open,
+                    other => {
+                        return Err(PyValueError::new_err(format!(
+                            "unknown null_only_fields: {other}"
+                        )))
+                    }
+                };
+            }
+            "required_threshold" => opts.required_threshold = value.extract()?,
+            "nullable_style" => {
+                let style: String = value.extract()?;
+                opts.nullable_style = match style.as_str() {
+                    "type_array" => NullableStyle::TypeArray,
+                    "any_of" => NullableStyle::AnyOf,
+                    "nullable" => NullableStyle::Nullable,
+                    other => {
+                        return Err(PyValueError::new_err(format!(
+                            "unknown nullable_style: {other}"
+                        )))
+                    }
+                };
+            }
+            "emit_numeric_bounds" => opts.emit_numeric_bounds = value.extract()?,
+            "exclusive_zero_bounds" => opts.exclusive_zero_bounds = value.extract()?,
+            "exclusive_bounds_min_observations" => {
+                opts.exclusive_bounds_min_observations = value.extract()?
+            }
+            "detect_multiple_of" => opts.detect_multiple_of = value.extract()?,
+            "detect_decimal_places" => opts.detect_decimal_places = value.extract()?,
+            "decimal_max_places" => opts.decimal_max_places = value.extract()?,
+            "detect_epoch_timestamps" => opts.detect_epoch_timestamps = value.extract()?,
+            "epoch_window" => opts.epoch_window = value.extract()?,
+            "epoch_min_observations" => opts.epoch_min_observations = value.extract()?,
+            "emit_string_bounds" => opts.emit_string_bounds = value.extract()?,
+            "emit_array_bounds" => opts.emit_array_bounds = value.extract()?,
+            "emit_property_counts" => opts.emit_property_counts = value.extract()?,
+            "detect_unique_items" => opts.detect_unique_items = value.extract()?,
+            "unique_items_max_len" => opts.unique_items_max_len = value.extract()?,
+            "detect_maps" => opts.detect_maps = value.extract()?,
+            "map_key_threshold" => opts.map_key_threshold = value.extract()?,
+            "map_keys_look_like_ids" => opts.map_keys_look_like_ids = value.extract()?,
+            "infer_property_names" => opts.infer_property_names = value.extract()?,
+            "detect_dependent_required" => opts.detect_dependent_required = value.extract()?,
+            "dependent_required_max_properties" => {
+                opts.dependent_required_max_properties = value.extract()?
+            }
+            "dependent_required_min_support" => {
+                opts.dependent_required_min_support = value.extract()?
+            }
+            "numeric_widening" => opts.numeric_widening = value.extract()?,
+            "items_any_of" => opts.items_any_of = value.extract()?,
+            "conflict_strategy" => {
+                let strategy: String = value.extract()?;
+                opts.conflict_strategy = match strategy.as_str() {
+                    "type_array" => ConflictStrategy::TypeArray,
+                    "any_of" => ConflictStrategy::AnyOf,
+                    "dominant" => ConflictStrategy::Dominant,
+                    other => {
+                        return Err(PyValueError::new_err(format!(
+                            "unknown conflict_strategy: {other}"
+                        )))
+                    }
+                };
+            }
+            "use_any_of" => opts.use_any_of = value.extract()?,
+            "detect_const" => opts.detect_const = value.extract()?,
+            "detect_formats" => opts.detect_formats = value.extract()?,
+            "emit_default" => opts.emit_default = value.extract()?,
+            "default_min_share" => opts.default_min_share = value.extract()?,
+            "detect_tuples" => opts.detect_tuples = value.extract()?,
+            "tuple_max_len" => opts.tuple_max_len = value.extract()?,
+            "array_mode" => {
+                let mode: String = value.extract()?;
+                opts.array_mode = match mode.as_str() {
+                    "items" => ArrayMode::Items,
+                    "prefix_items" => ArrayMode::PrefixItems,
+                    other => {
+                        return Err(PyValueError::new_err(format!(
+                            "unknown array_mode: {other}"
+                        )))
+                    }
+                };
+            }
+            "include_examples" => opts.include_examples = value.extract()?,
+            "generate_titles" => opts.generate_titles = value.extract()?,
+            "examples_per_field" => opts.examples_per_field = value.extract()?,
+            "example_max_len" => opts.example_max_len = value.extract()?,
+            "detect_patterns" => opts.detect_patterns = value.extract()?,
+            "detect_base64" => opts.detect_base64 = value.extract()?,
+            "base64_min_len" => opts.base64_min_len = value.extract()?,
+            "detect_numeric_strings" => opts.detect_numeric_strings = value.extract()?,
+            "detect_boolean_strings" => opts.detect_boolean_strings = value.extract()?,
+            "boolean_true_strings" => opts.boolean_true_strings = value.extract()?,
+            "boolean_false_strings" => opts.boolean_false_strings = value.extract()?,
+            "boolean_string_min_observations" => {
+                opts.boolean_string_min_observations = value.extract()?
+            }
+            "numeric_string_pattern" => opts.numeric_string_pattern = value.extract()?,
+            "format_sample_size" => opts.format_sample_size = value.extract()?,
+            "ip_any_of" => opts.ip_any_of = value.extract()?,
+            "additional_properties" => {
+                let mode: String = value.extract()?;
+                opts.additional_properties = match mode.as_str() {
+                    "allow" => AdditionalProperties::Allow,
+                    "deny" => AdditionalProperties::Deny,
+                    "deny_at_root" => AdditionalProperties::DenyAtRoot,
+                    other => {
+                        return Err(PyValueError::new_err(format!(
+                            "unknown additional_properties: {other}"
+                        )))
+                    }
+                };
+            }
+            "legacy_object_root" => opts.legacy_object_root = value.extract()?,
+            "discriminator" => opts.discriminator = value.extract()?,
+            "detect_discriminator" => opts.detect_discriminator = value.extract()?,
+            "discriminator_max_values" => opts.discriminator_max_values = value.extract()?,
+            "conditional_rules" => {
+                let rules: Vec<(String, Bound<'_, PyAny>)> = value.extract()?;
+                opts.conditional_rules = rules
+                    .into_iter()
+                    .map(|(field, value)| {
+                        Ok(ConditionalRule {
+                            field,
+                            value: py_scalar(&value)?,
+                        })
+                    })
+                    .collect::<PyResult<_>>()?;
+            }
+            // кортежи (путь, схема JSON-строкой) или (путь, {поле: значение})
+            "contains_rules" => {
+                let rules: Vec<(String, Bound<'_, PyAny>)> = value.extract()?;
+                opts.contains_rules = rules
+                    .into_iter()
+                    .map(|(path, candidate)| {
+                        let candidate = match candidate.extract::<String>() {
+                            Ok(schema) => ContainsCandidate::Schema(
+                                serde_json::from_str(&schema)
+                                    .map_err(|e| PyValueError::new_err(e.to_string()))?,
+                            ),
+                            Err(_) => {
+                                let fields: BTreeMap<String, Bound<'_, PyAny>> =
+                                    candidate.extract()?;
+                                let fields = fields
+                                    .into_iter()
+                                    .map(|(k, v)| Ok((k, py_scalar(&v)?)))
+                                    .collect::<PyResult<_>>()?;
+                                ContainsCandidate::Fields(fields)
+                            }
+                        };
+                        Ok(ContainsRule { path, candidate })
+                    })
+                    .collect::<PyResult<_>>()?;
+            }
+            "preserve_insertion_order" => opts.preserve_insertion_order = value.extract()?,
+            "extract_defs" => opts.extract_defs = value.extract()?,
+            "defs_min_properties" => opts.defs_min_properties = value.extract()?,
+            "detect_recursion" => opts.detect_recursion = value.extract()?,
+            "recursion_min_properties" => opts.recursion_min_properties = value.extract()?,
+            "recursion_max_depth" => opts.recursion_max_depth = value.extract()?,
+            "annotate_frequency" => opts.annotate_frequency = value.extract()?,
+            "annotate_presence" => opts.annotate_presence = value.extract()?,
+            "annotate_types" => opts.annotate_types = value.extract()?,
+            "annotate_empty_objects" => opts.annotate_empty_objects = value.extract()?,
+            "depth_limit" => opts.depth_limit = value.extract()?,
+            "error_mode" => {
+                let mode: String = value.extract()?;
+                opts.error_mode = match mode.as_str() {
+                    "strict" => ErrorMode::Strict,
+                    "lenient" => ErrorMode::Lenient,
+                    other => {
+                        return Err(PyValueError::new_err(format!(
+                            "unknown error_mode: {other}"
+                        )))
+                    }
+                };
+            }
+            "annotations" => {
+                let by_path: BTreeMap<String, BTreeMap<String, String>> = value.extract()?;
+                for (path, fields) in by_path {
+                    let mut annotation = Annotation::default();
+                    for (field, text) in fields {
+                        match field.as_str() {
+                            "title" => annotation.title = Some(text),
+                            "description" => annotation.description = Some(text),
+                            other => {
+                                return Err(PyTypeError::new_err(format!(
+                                    "unknown annotation field: {other}"
+                                )))
+                            }
+                        }
+                    }
+                    opts.annotations.insert(path, annotation);
+                }
+            }
+            "enum_cap" => opts.enum_cap = value.extract()?,
+            "enum_threshold" => opts.enum_threshold = value.extract()?,
+            "enum_min_observations" => opts.enum_min_observations = value.extract()?,
+            other => {
+                return Err(PyTypeError::new_err(format!(
+                    "infer_schema() got an unexpected keyword argument '{other}'"
+                )))
+            }
+        }
+    }
+    Ok(opts)
+}
+
+/// infer_schema(samples: List[str], **options) -> str(JSON)
+#[pyfunction]
+#[pyo3(signature = (samples, **options))]
+fn infer_schema(
+    samples: Vec<PyBackedStr>,
+    options: Option<&Bound<'_, PyDict>>,
+) -> PyResult<String> {
+    let options = infer_options_from_kwargs(options)?;
+    Ok(infer_schema_rs(&samples, options)?)
+}
+
+/// infer_schema_annotated(samples: List[str], annotations=..., **options)
+/// -> (str(JSON), List[str] of unmatched annotation paths)
+#[pyfunction]
+#[pyo3(signature = (samples, **options))]
+fn infer_schema_annotated(
+    samples: Vec<PyBackedStr>,
+    options: Option<&Bound<'_, PyDict>>,
+) -> PyResult<(String, Vec<String>)> {
+    let options = infer_options_from_kwargs(options)?;
+    Ok(infer_schema_annotated_rs(&samples, options)?)
+}
+
+/// infer_schema_from_jsonl(path: str, **options) -> str(JSON)
+#[pyfunction]
+#[pyo3(signature = (path, **options))]
+fn infer_schema_from_jsonl(path: &str, options: Option<&Bound<'_, PyDict>>) -> PyResult<String> {
+    let options = infer_options_from_kwargs(options)?;
+    Ok(infer_schema_from_jsonl_rs(path, options)?)
+}
+
+/// diff_schemas(a: str(JSON), b: str(JSON), format: str = "paths",
+///              path_format: str = "dot", include_extensions: bool = False,
+///              mark_removed_as_deprecated: bool = False) -> str(JSON)
+#[pyfunction]
+#[pyo3(signature = (
+    a,
+    b,
+    format = "paths",
+    path_format = "dot",
+    include_extensions = false,
+    mark_removed_as_deprecated = false
+))]
+pub(crate) fn diff_schemas(
+    a: String,
+    b: String,
+    format: &str,
+    path_format: &str,
+    include_extensions: bool,
+    mark_removed_as_deprecated: bool,
+) -> PyResult<String> {
+    let path_format = match path_format {
+        "dot" => PathFormat::Dot,
+        "json_pointer" => PathFormat::JsonPointer,
+        other => {
+            return Err(PyValueError::new_err(format!(
+                "unknown path_format: {other}"
+            )))
+        }
+    };
+    match format {
+        "paths" => {
+            let options = DiffOptions {
+                path_format,
+                include_extensions,
+                mark_removed_as_deprecated,
+            };
+            Ok(diff_schemas_json_rs(&a, &b, options)?)
+        }
+        "node" => Ok(diff_schema_nodes_json_rs(&a, &b)?),
+        other => Err(PyValueError::new_err(format!(
+            "unknown diff format: {other}"
+        ))),
+    }
+}
+
+/// strip_extensions(schema: str(JSON)) -> str(JSON) without `x-` keywords
+#[pyfunction]
+fn strip_extensions(schema: String) -> PyResult<String> {
+    Ok(strip_extensions_rs(&schema)?)
+}
+
+/// schema_patch(a: str(JSON), b: str(JSON)) -> str(JSON Patch turning a into b)
+#[pyfunction]
+fn schema_patch(a: String, b: String) -> PyResult<String> {
+    Ok(schema_patch_rs(&a, &b)?)
+}
+
+/// apply_schema_patch(schema: str(JSON), patch: str(JSON Patch)) -> str(JSON)
+#[pyfunction]
+fn apply_schema_patch(schema: String, patch: String) -> PyResult<String> {
+    Ok(apply_schema_patch_rs(&schema, &patch)?)
+}
+
+/// merge_schemas(a: str(JSON), b: str(JSON)) -> str(JSON)
+#[pyfunction]
+fn merge_schemas(a: String, b: String) -> PyResult<String> {
+    Ok(merge_schemas_rs(&a, &b)?)
+}
+
+/// schema_similarity(a: str(JSON), b: str(JSON), typed: bool = False) -> float
+/// Jaccard score of the path sets; `typed` halves paths whose type differs.
+#[pyfunction]
+#[pyo3(signature = (a, b, typed = false))]
+fn schema_similarity(a: String, b: String, typed: bool) -> PyResult<f64> {
+    if typed {
+        Ok(schema_similarity_typed_rs(&a, &b)?)
+    } else {
+        Ok(schema_similarity_rs(&a, &b)?)
+    }
+}
+
+/// schema_fingerprint(schema: str(JSON)) -> str (hex SHA-256)
+#[pyfunction]
+fn schema_fingerprint(schema: String) -> PyResult<String> {
+    Ok(schema_fingerprint_rs(&schema)?)
+}
+
+/// schema_coverage(schema: str(JSON), samples: List[str]) -> str(JSON) with
+/// `covered`, `uncovered` and `coverage`
+#[pyfunction]
+fn schema_coverage(schema: String, samples: Vec<PyBackedStr>) -> PyResult<String> {
+    Ok(schema_coverage_json_rs(&schema, &samples)?)
+}
+
+/// schema_stats(schema: str(JSON)) -> str(JSON) with path counts per type
+#[pyfunction]
+fn schema_stats(schema: String) -> PyResult<String> {
+    Ok(schema_stats_rs(&schema)?)
+}
+
+/// validate_sample(schema: str(JSON), sample: str(JSON)) -> str(JSON list of {path, message})
+#[pyfunction]
+fn validate_sample(schema: String, sample: String) -> PyResult<String> {
+    let errors = validate_sample_rs(&schema, &sample)?;
+    Ok(serde_json::to_string_pretty(&errors).map_err(InferError::SerializeError)?)
+}
+
+/// schema_to_typescript(schema: str(JSON)) -> str (TypeScript declarations)
+#[pyfunction]
+fn schema_to_typescript(schema: String) -> PyResult<String> {
+    Ok(schema_to_typescript_rs(&schema)?)
+}
+
+/// schema_to_sql_ddl(schema: str(JSON), table_name: str) -> str (CREATE TABLE)
+#[pyfunction]
+fn schema_to_sql_ddl(schema: String, table_name: String) -> PyResult<String> {
+    Ok(schema_to_sql_ddl_rs(&schema, &table_name)?)
+}
+
+/// SchemaInferrer(**options): feed(sample: str), finish() -> str(JSON),
+/// finish_with_errors(), save(path: str), SchemaInferrer.load(path: str)
+#[pyclass(name = "SchemaInferrer")]
+struct PySchemaInferrer {
+    inner: SchemaInferrer,
+}
+
+#[pymethods]
+impl PySchemaInferrer {
+    #[new]
+    #[pyo3(signature = (**options))]
+    fn new(options: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        Ok(Self {
+            inner: SchemaInferrer::new(infer_options_from_kwargs(options)?),
+        })
+    }
+
+    fn feed(&mut self, sample: &str) -> PyResult<()> {
+        Ok(self.inner.feed(sample)?)
+    }
+
+    fn finish(&self) -> PyResult<String> {
+        Ok(self.inner.finish()?)
+    }
+
+    /// finish_with_errors() -> (str(JSON), List[str] of skipped-sample errors)
+    fn finish_with_errors(&mut self) -> PyResult<(String, Vec<String>)> {
+        let result = self.inner.finish_with_errors()?;
+        let errors = result.errors.iter().map(ToString::to_string).collect();
+        Ok((result.schema, errors))
+    }
+
+    fn save(&self, path: &str) -> PyResult<()> {
+        Ok(self.inner.save(path)?)
+    }
+
+    #[staticmethod]
+    fn load(path: &str) -> PyResult<Self> {
+        Ok(Self {
+            inner: SchemaInferrer::load(path)?,
+        })
+    }
+
+    /// Snapshot of the accumulated root node.
+    fn root_node(&self) -> PyNode {
+        PyNode {
+            inner: self.inner.root().clone(),
+            options: self.inner.options().clone(),
+        }
+    }
+}
+
+/// Node: get(path: str) -> Optional[dict]; the accessors below take a path
+/// too (`""` is this node) and return None when it does not resolve.
+#[pyclass(name = "Node")]
+struct PyNode {
+    inner: Node,
+    options: InferOptions,
+}
+
+#[pymethods]
+impl PyNode {
+    fn get(&self, py: Python<'_>, path: &str) -> PyResult<Option<PyObject>> {
+        let Some(node) = self.inner.get_at_path(path) else {
+            return Ok(None);
+        };
+        let fragment = node.to_json_schema(&self.options).to_string();
+        let dict = py.import("json")?.call_method1("loads", (fragment,))?;
+        Ok(Some(dict.unbind()))
+    }
+
+    /// property_names(path: str = "") -> Optional[list[str]], sorted
+    #[pyo3(signature = (path = ""))]
+    fn property_names(&self, path: &str) -> Option<Vec<String>> {
+        let node = self.inner.get_at_path(path)?;
+        Some(
+            node.property_names()
+                .into_iter()
+                .map(String::from)
+                .collect(),
+        )
+    }
+
+    /// has_property(key: str, path: str = "") -> bool
+    #[pyo3(signature = (key, path = ""))]
+    fn has_property(&self, key: &str, path: &str) -> bool {
+        self.inner
+            .get_at_path(path)
+            .is_some_and(|node| node.has_property(key))
+    }
+
+    /// items_node(path: str = "") -> Optional[Node]
+    #[pyo3(signature = (path = ""))]
+    fn items_node(&self, path: &str) -> Option<PyNode> {
+        let items = self.inner.get_at_path(path)?.items_node()?;
+        Some(PyNode {
+            inner: items.clone(),
+            options: self.options.clone(),
+        })
+    }
+
+    /// observed_types(path: str = "") -> Optional[list[TypeTag]]
+    #[pyo3(signature = (path = ""))]
+    fn observed_types(&self, py: Python<'_>, path: &str) -> PyResult<Option<Vec<PyObject>>> {
+        let Some(node) = self.inner.get_at_path(path) else {
+            return Ok(None);
+        };
+        let type_tag = py.import("aif_core")?.getattr("TypeTag")?;
+        let tags = node
+            .observed_types()
+            .into_iter()
+            .map(|t| Ok(type_tag.call1((t as u8,))?.unbind()))
+            .collect::<PyResult<_>>()?;
+        Ok(Some(tags))
+    }
+}
+
+// TypeTag как enum.IntEnum: NULL = 0, ..., ARRAY = 6
+fn type_tag_enum<'py>(py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    let members: Vec<(String, u8)> = TypeTag::all()
+        .map(|t| (t.as_str().to_uppercase(), t as u8))
+        .collect();
+    py.import("enum")?
+        .getattr("IntEnum")?
+        .call1(("TypeTag", members))
+}
+
+#[pymodule]
+fn aif_core(py: Python, m: &Bound<pyo3::types::PyModule>) -> PyResult<()> {
+    let type_tag = type_tag_enum(py)?;
+    type_tag.setattr("__module__", "aif_core")?;
+    m.add("TypeTag", type_tag)?;
+    m.add_function(wrap_pyfunction!(infer_schema, m)?)?;
+    m.add_function(wrap_pyfunction!(infer_schema_annotated, m)?)?;
+    m.add_function(wrap_pyfunction!(infer_schema_from_jsonl, m)?)?;
+    m.add_function(wrap_pyfunction!(diff_schemas, m)?)?;
+    m.add_function(wrap_pyfunction!(schema_patch, m)?)?;
+    m.add_function(wrap_pyfunction!(apply_schema_patch, m)?)?;
+    m.add_function(wrap_pyfunction!(merge_schemas, m)?)?;
+    m.add_function(wrap_pyfunction!(strip_extensions, m)?)?;
+    m.add_function(wrap_pyfunction!(schema_similarity, m)?)?;
+    m.add_function(wrap_pyfunction!(schema_fingerprint, m)?)?;
+    m.add_function(wrap_pyfunction!(schema_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(schema_coverage, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_sample, m)?)?;
+    m.add_function(wrap_pyfunction!(schema_to_typescript, m)?)?;
+    m.add_function(wrap_pyfunction!(schema_to_sql_ddl, m)?)?;
+    m.add_class::<PySchemaInferrer>()?;
+    m.add_class::<PyNode>()?;
+    Ok(())
+}
@@ -0,0 +1,483 @@
+use ahash::{AHashMap, AHashSet};
+use serde_json::{Map, Value};
+use std::cmp::Ordering;
+
+use crate::content::{Base64, BooleanString, MediaType, NumericString};
+use crate::dependencies::CoOccurrence;
+use crate::examples::Reservoir;
+use crate::formats::FormatSet;
+use crate::patterns::{KeyPatterns, KeyShape};
+use crate::{cmp_numbers, is_multiple_of, InferError, Node, TypeTag, FLOAT_STEPS};
+
+// Схема — это убедительное свидетельство: каждый восстановленный узел считается
+// наблюдавшимся столько раз, чтобы пройти любые пороги (const, enum, форматы)
+pub(crate) const SCHEMA_WEIGHT: u64 = 1 << 32;
+
+impl Node {
+    /// Rebuilds an inference node from a JSON Schema, as if the documents it
+    /// describes had been observed. A schema saved earlier keeps learning by
+    /// loading it here and folding new samples in with `merge_nodes`.
+    pub fn from_json_schema(schema: &Value) -> Result<Node, InferError> {
+        Node::from_json_schema_at(schema, "")
+    }
+
+    fn from_json_schema_at(schema: &Value, path: &str) -> Result<Node, InferError> {
+        let unsupported = |reason: String| InferError::UnsupportedSchema {
+            path: if path.is_empty() {
+                "<root>".to_string()
+            } else {
+                path.to_string()
+            },
+            reason,
+        };
+        let obj = match schema {
+            Value::Object(obj) => obj,
+            // true принимает что угодно — типов не знаем
+            Value::Bool(true) => return Ok(Node::schema_default()),
+            other => return Err(unsupported(format!("expected an object, got {other}"))),
+        };
+
+        // Ветви anyOf без type — объединение альтернатив (nullable-стиль any_of);
+        // ветви из одних format — альтернативные форматы строки (ip_any_of)
+        let union = obj
+            .get("anyOf")
+            .filter(|b| obj.get("type").is_none() && format_alternatives(b).is_none());
+        if let Some(branches) = union {
+            let branches = branches
+                .as_array()
+                .ok_or_else(|| unsupported("anyOf must be an array".to_string()))?;
+            let mut node = Node::default();
+            for branch in branches {
+                node.merge_with(Node::from_json_schema_at(branch, path)?);
+            }
+            // альтернативы описывают одно значение, а не несколько наблюдений
+            node.seen_count = SCHEMA_WEIGHT;
+            node.doc_count = SCHEMA_WEIGHT;
+            node.restore_type_counts(obj);
+            return Ok(node);
+        }
+
+        let mut node = Node::schema_default();
+        match obj.get("type") {
+            None => {}
+            Some(Value::String(name)) => {
+                node.types.insert(
+                    name.parse::<TypeTag>()
+                        .map_err(|_| unsupported(format!("unknown type '{name}'")))?,
+                );
+            }
+            Some(Value::Array(names)) => {
+                for name in names {
+                    let tag = name
+                        .as_str()
+                        .and_then(|n| n.parse::<TypeTag>().ok())
+                        .ok_or_else(|| unsupported(format!("unknown type {name}")))?;
+                    node.types.insert(tag);
+                }
+            }
+            Some(other) => return Err(unsupported(format!("invalid type {other}"))),
+        }
+        if obj.get("nullable") == Some(&Value::Bool(true)) {
+            node.types.insert(TypeTag::Null);
+        }
+
+        if let Some(c) = obj.get("const") {
+            node.types.insert(TypeTag::of(c));
+            node.observe_scalars(std::slice::from_ref(c));
+            if matches!(c, Value::Bool(_) | Value::Number(_) | Value::String(_)) {
+                node.const_candidate = Some(c.clone());
+                node.is_const = true;
+            }
+        }
+        if let Some(values) = obj.get("enum") {
+            let values = values
+                .as_array()
+                .ok_or_else(|| unsupported("enum must be an array".to_string()))?;
+            if obj.get("type").is_none() {
+                node.types.extend(values.iter().map(TypeTag::of));
+            }
+            node.observe_scalars(values);
+        }
+        node.start_tracking();
+        node.read_keywords(obj);
+        if let Some(default) = obj.get("default") {
+            node.value_counts = Some(AHashMap::from_iter([(default.to_string(), SCHEMA_WEIGHT)]));
+        }
+        if let Some(Value::Array(examples)) = obj.get("examples") {
+            node.examples = Some(Reservoir::from_values(examples));
+        }
+
+        if let Some(props) = obj.get("properties") {
+            let props = props
+                .as_object()
+                .ok_or_else(|| unsupported("properties must be an object".to_string()))?;
+            let required: AHashSet<&str> = obj
+                .get("required")
+                .and_then(Value::as_array)
+                .map(|keys| keys.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            node.types.insert(TypeTag::Object);
+            for (k, v) in props {
+                let next = if path.is_empty() {
+                    k.to_string()
+                } else {
+                    format!("{path}.{k}")
+                };
+                let mut child = Node::from_json_schema_at(v, &next)?;
+                // необязательный ключ «пропущен» в одном из наблюдений объекта
+                if !required.contains(k.as_str()) {
+                    child.seen_count -= 1;
+                }
+                node.properties.insert(k.clone(), child);
+            }
+            node.key_patterns = schema
+                .pointer("/propertyNames/pattern")
+                .and_then(Value::as_str)
+                .and_then(KeyPatterns::from_pattern);
+            // в dependencies массив — зависимость ключей, объект — схема
+            let dependencies = obj
+                .get("dependentRequired")
+                .or_else(|| obj.get("dependencies"))
+                .and_then(Value::as_object);
+            if let Some(dependencies) = dependencies {
+                let mut co = CoOccurrence::default();
+                for (k, dependents) in dependencies {
+                    let (Some(child), Value::Array(dependents)) =
+                        (node.properties.get(k), dependents)
+                    else {
+                        continue;
+                    };
+                    let dependents: Vec<&str> =
+                        dependents.iter().filter_map(Value::as_str).collect();
+                    co.insert(k, &dependents, child.seen_count);
+                }
+                node.co_occurrence = Some(co);
+            }
+        }
+        // additionalProperties со схемой — словарь (false/true ничего о значениях не говорят)
+        if let Some(values @ Value::Object(_)) = obj.get("additionalProperties") {
+            node.types.insert(TypeTag::Object);
+            node.map_values = Some(Box::new(Node::from_json_schema_at(
+                values,
+                &format!("{path}.*"),
+            )?));
+            // из pattern форму ключей не восстановить, uuid — можно
+            if schema.pointer("/propertyNames/format") == Some(&Value::from("uuid")) {
+                node.map_keys = KeyShape::uuids();
+            }
+        }
+        if node.types.contains(&TypeTag::Object) {
+            node.sample_count = SCHEMA_WEIGHT;
+        }
+
+        // кортеж до 2020-12 — массив в items
+        let tuple = obj
+            .get("prefixItems")
+            .or_else(|| obj.get("items").filter(|i| i.is_array()));
+        if let Some(prefix) = tuple {
+            let prefix = prefix
+                .as_array()
+                .ok_or_else(|| unsupported("prefixItems must be an array".to_string()))?;
+            node.types.insert(TypeTag::Array);
+            let mut positions = Vec::with_capacity(prefix.len());
+            for (i, p) in prefix.iter().enumerate() {
+                positions.push(Node::from_json_schema_at(p, &format!("{path}[{i}]"))?);
+            }
+            // на случай отката к items — все позиции, сведённые в один узел
+            let mut items: Option<Box<Node>> = None;
+            for p in &positions {
+                match &mut items {
+                    Some(items) => items.merge_with(p.clone()),
+                    None => items = Some(Box::new(p.clone())),
+                }
+            }
+            node.items = items;
+            node.tuple = Some(positions);
+            node.arr_min_items = Some(prefix.len());
+            node.arr_max_items = Some(prefix.len());
+        } else if let Some(items) = obj.get("items") {
+            node.types.insert(TypeTag::Array);
+            node.items = Some(Box::new(Node::from_json_schema_at(
+                items,
+                &format!("{path}[]"),
+            )?));
+        }
+
+        node.restore_type_counts(obj);
+        Ok(node)
+    }
+
+    // Точные счётчики из x-aif-types, иначе — вес схемы для каждого типа
+    fn restore_type_counts(&mut self, obj: &Map<String, Value>) {
+        let saved = obj.get("x-aif-types").and_then(Value::as_object);
+        for tag in TypeTag::ALL.into_iter().filter(|t| self.types.contains(t)) {
+            let count = saved
+                .and_then(|s| s.get(tag.as_str()))
+                .and_then(Value::as_u64);
+            match count {
+                Some(n) => self.type_counts.set(tag, n),
+                None if self.type_counts.get(tag) == 0 => self.type_counts.add(tag, SCHEMA_WEIGHT),
+                None => {}
+            }
+        }
+    }
+
+    fn schema_default() -> Node {
+        Node {
+            seen_count: SCHEMA_WEIGHT,
+            doc_count: SCHEMA_WEIGHT,
+            ..Node::default()
+        }
+    }
+
+    // Значения из const/enum как будто наблюдались: наборы для enum, счётчики
+    fn observe_scalars(&mut self, values: &[Value]) {
+        let mut strings = AHashSet::default();
+        let mut ints = AHashSet::default();
+        let mut only_ints = true;
+        for v in values {
+            match v {
+                Value::String(s) => {
+                    let len = s.chars().count();
+                    self.str_min_len = Some(self.str_min_len.map_or(len, |m| m.min(len)));
+                    self.str_max_len = Some(self.str_max_len.map_or(len, |m| m.max(len)));
+                    strings.insert(s.clone());
+                }
+                Value::Number(n) => {
+                    if self
+                        .num_min
+                        .as_ref()
+                        .is_none_or(|m| cmp_numbers(n, m) == Ordering::Less)
+                    {
+                        self.num_min = Some(n.clone());
+                    }
+                    if self
+                        .num_max
+                        .as_ref()
+                        .is_none_or(|m| cmp_numbers(n, m) == Ordering::Greater)
+                    {
+                        self.num_max = Some(n.clone());
+                    }
+                    match n.as_i64() {
+                        Some(i) => {
+                            ints.insert(i);
+                        }
+                        None => only_ints = false,
+                    }
+                }
+                _ => {}
+            }
+        }
+        if !strings.is_empty() {
+            self.string_count = SCHEMA_WEIGHT;
+            self.string_values = Some(strings);
+        }
+        if !ints.is_empty() || !only_ints {
+            self.number_count = SCHEMA_WEIGHT;
+            self.int_values = Some(ints).filter(|_| only_ints);
+        }
+    }
+
+    // Ограничения, которые сам вывод ставит на узел
+    fn read_keywords(&mut self, obj: &Map<String, Value>) {
+        let size = |key: &str| {
+            obj.get(key)
+                .and_then(Value::as_u64)
+                .and_then(|n| usize::try_from(n).ok())
+        };
+        if let Some(n) = obj.get("minimum").and_then(Value::as_number) {
+            self.num_min = Some(n.clone());
+        }
+        if let Some(n) = obj.get("maximum").and_then(Value::as_number) {
+            self.num_max = Some(n.clone());
+        }
+        if let Some(step) = obj.get("multipleOf").and_then(Value::as_number) {
+            if let Some(k) = step.as_u64() {
+                self.int_gcd = k;
+            }
+            // шаг кратен всем более мелким шагам, которые его делят
+            if let Some(x) = step.as_f64() {
+                for (bit, s) in FLOAT_STEPS.iter().enumerate() {
+                    if is_multiple_of(x, *s) {
+                        self.float_steps |= 1 << bit;
+                    }
+                }
+                // шаг 10^-n — точность в n знаков
+                if let Some(n) = (1..=9).find(|n| (x * 10f64.powi(*n) - 1.0).abs() < 1e-9) {
+                    self.decimal_places = n as u32;
+                }
+            }
+        }
+
+        if let Some(n) = size("minLength") {
+            self.str_min_len = Some(n);
+        }
+        if let Some(n) = size("maxLength") {
+            self.str_max_len = Some(n);
+        }
+        if let Some(name) = obj.get("format").and_then(Value::as_str) {
+            self.formats = FormatSet::from_name(name);
+        } else if let Some(formats) = obj.get("anyOf").and_then(format_alternatives) {
+            self.formats = formats;
+        }
+
+        if obj.get("contentEncoding").and_then(Value::as_str) == Some("base64") {
+            let media_type = obj
+                .get("contentMediaType")
+                .and_then(Value::as_str)
+                .and_then(MediaType::from_name);
+            self.base64 = Some(Base64 { media_type });
+        }
+        self.numeric_strings = match obj.get("x-aif-suggested-type").and_then(Value::as_str) {
+            Some("integer") => Some(NumericString::Integer),
+            Some("number") => Some(NumericString::Number),
+            _ => None,
+        };
+        // раз подсказка выведена, обоим значениям истинности доверяем
+        if obj.get("x-aif-suggested-type").and_then(Value::as_str) == Some("boolean") {
+            let spellings = obj
+                .get("enum")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten();
+            self.boolean_strings = Some(BooleanString {
+                true_count: SCHEMA_WEIGHT,
+                false_count: SCHEMA_WEIGHT,
+                spellings: spellings
+                    .filter_map(Value::as_str)
+                    .map(String::from)
+                    .collect(),
+            });
+        }
+
+        if let Some(n) = size("minProperties") {
+            self.obj_min_props = Some(n);
+        }
+        if let Some(n) = size("maxProperties") {
+            self.obj_max_props = Some(n);
+        }
+        if obj.get("x-aif-empty") == Some(&Value::Bool(true)) {
+            self.obj_min_props = Some(0);
+            self.obj_max_props = Some(0);
+        }
+        if let Some(n) = size("minItems") {
+            self.arr_min_items = Some(n);
+        }
+        if let Some(n) = size("maxItems") {
+            self.arr_max_items = Some(n);
+        }
+        self.unique_items_candidate = obj.get("uniqueItems") == Some(&Value::Bool(true));
+    }
+
+    // Для типов без значений из схемы: значения были, но какие — неизвестно
+    fn start_tracking(&mut self) {
+        if self.types.contains(&TypeTag::String) && self.string_count == 0 {
+            self.string_count = SCHEMA_WEIGHT;
+        }
+        let numeric =
+            self.types.contains(&TypeTag::Integer) || self.types.contains(&TypeTag::Number);
+        if numeric && self.number_count == 0 {
+            self.number_count = SCHEMA_WEIGHT;
+        }
+        if numeric {
+            // НОД 1 — «кратность неизвестна», при объединении не даст ложный multipleOf
+            self.int_gcd = 1;
+        }
+    }
+}
+
+// Значение удовлетворяет одному из форматов — пересечение их множеств
+fn format_alternatives(branches: &Value) -> Option<FormatSet> {
+    branches
+        .as_array()?
+        .iter()
+        .map(|b| match b.as_object() {
+            Some(o) if o.len() == 1 => o.get("format")?.as_str().map(FormatSet::from_name),
+            _ => None,
+        })
+        .reduce(|a, b| Some(a?.intersect(b?)))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_samples, InferOptions, NullableStyle};
+
+    fn assert_round_trip(samples: &[&str], opts: InferOptions) {
+        let node = parse_samples(samples, &opts).unwrap();
+        let schema = node.to_json_schema(&opts);
+        let rebuilt = Node::from_json_schema(&schema).unwrap();
+        assert_eq!(rebuilt.to_json_schema(&opts), schema);
+    }
+
+    const SAMPLES: &[&str] = &[
+        r#"{"id":1,"kind":"a","v":1,"score":0.5,"email":"a@x.io","ip":"10.0.0.1","tags":["x","y"],"meta":{"n":null},"pt":["a",1]}"#,
+        r#"{"id":4,"kind":"b","v":1,"score":1.5,"email":"b@x.io","ip":"::1","tags":[],"meta":{"n":2},"pt":["b",2.5]}"#,
+        r#"{"id":8,"kind":"a","v":1,"score":-2,"email":"c@x.io","ip":"10.0.0.2","rows":[{"x":1},{"y":[[]]}],"m":{"a":1,"b":2,"c":3,"d":{"x":1}}}"#,
+    ];
+
+    #[test]
+    fn round_trip_with_default_options() {
+        assert_round_trip(SAMPLES, InferOptions::default());
+    }
+
+    #[test]
+    fn round_trip_with_every_keyword() {
+        let opts = InferOptions {
+            emit_numeric_bounds: true,
+            emit_string_bounds: true,
+            emit_array_bounds: true,
+            emit_property_counts: true,
+            detect_unique_items: true,
+            detect_multiple_of: true,
+            detect_formats: true,
+            ip_any_of: true,
+            enum_min_observations: 2,
+            include_examples: true,
+            examples_per_field: 2,
+            emit_default: true,
+            default_min_share: 0.6,
+            detect_base64: true,
+            base64_min_len: 4,
+            detect_tuples: true,
+            detect_maps: true,
+            map_key_threshold: 3,
+            ..InferOptions::default()
+        };
+        assert_round_trip(SAMPLES, opts);
+    }
+
+    #[test]
+    fn round_trip_with_each_nullable_style() {
+        for nullable_style in [NullableStyle::AnyOf, NullableStyle::Nullable] {
+            let opts = InferOptions {
+                nullable_style,
+                ..InferOptions::default()
+            };
+            assert_round_trip(SAMPLES, opts);
+        }
+    }
+
+    #[test]
+    fn round_trip_with_any_of_branches() {
+        let opts = InferOptions {
+            use_any_of: true,
+            emit_numeric_bounds: true,
+            ..InferOptions::default()
+        };
+        assert_round_trip(
+            &[r#"{"v":1,"o":{"a":1}}"#, r#"{"v":"x","o":[2.5],"z":null}"#],
+            opts,
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_type_with_path() {
+        let schema = serde_json::json!({"properties": {"a": {"items": {"type": "decimal"}}}});
+        let err = Node::from_json_schema(&schema).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unsupported schema at a[]: unknown type 'decimal'"
+        );
+    }
+}
@@ -0,0 +1,387 @@
+use std::collections::BTreeMap;
+
+use serde_json::{json, Map, Value};
+
+use crate::annotations::pointer_target;
+use crate::defs::{escape, sanitize_name, unique_name, DATA_KEYWORDS};
+use crate::diff::effective_type;
+use crate::merge::schema_options;
+use crate::{InferOptions, Node};
+
+// Имена свойств с их типами, по порядку имён
+type Shape = Vec<(String, Value)>;
+
+struct Enclosing {
+    pointer: String,
+    key: Option<String>,
+    shape: Shape,
+}
+
+// Объемлющая схема: её ключ и пути повторов относительно неё
+struct Target {
+    key: Option<String>,
+    repeats: Vec<String>,
+}
+
+struct Walk<'o> {
+    opts: &'o InferOptions,
+    enclosing: Vec<Enclosing>,
+    // (место повтора, указатель на объемлющую схему, её ключ)
+    found: Vec<(String, String, Option<String>)>,
+}
+
+/// Replaces every object sub-schema whose property names and types repeat
+/// those of one of the `recursion_max_depth` nearest enclosing objects with a
+/// `$ref` to the outermost such object: `#` for the root, otherwise a new
+/// `$defs` (draft-07 `definitions`) entry named after its property key.
+/// Objects with fewer than `recursion_min_properties` properties are ignored.
+///
+/// The target is rebuilt from the enclosing object merged with every repeat
+/// below it, so values deeper than the matching shape (a string leaf where
+/// an object used to be) still validate against the `$ref`. Keywords the
+/// reconstruction drops (`if`, `contains`, extensions) are lost inside the
+/// target; enclosing objects holding a `$ref` or `oneOf` stay inline, as do
+/// repeats inside another linked target.
+pub(crate) fn link_recursion(doc: &mut Map<String, Value>, opts: &InferOptions) {
+    let mut walk = Walk {
+        opts,
+        enclosing: Vec::new(),
+        found: Vec::new(),
+    };
+    walk.object(doc, "", None);
+    if walk.found.is_empty() {
+        return;
+    }
+
+    // повторы по объемлющим схемам, пути повторов — относительно своей схемы
+    let mut targets: BTreeMap<String, Target> = BTreeMap::new();
+    for (pointer, ancestor, key) in walk.found {
+        let relative = pointer[ancestor.len()..].to_string();
+        let target = targets.entry(ancestor).or_insert_with(|| Target {
+            key,
+            repeats: Vec::new(),
+        });
+        target.repeats.push(relative);
+    }
+
+    let keyword = opts.draft.defs_keyword();
+    let mut defs = match doc.remove(keyword) {
+        Some(Value::Object(defs)) => defs,
+        _ => Map::new(),
+    };
+    let render = schema_options(opts.clone());
+    // внешние схемы первыми; вложенная в уже связанную слита в неё
+    let mut ancestors: Vec<(String, Target)> = targets.into_iter().collect();
+    ancestors.sort_by_key(|(pointer, _)| pointer.len());
+    let mut linked: Vec<String> = Vec::new();
+    for (pointer, Target { key, repeats }) in ancestors {
+        if linked
+            .iter()
+            .any(|outer| outer.is_empty() || pointer.starts_with(&format!("{outer}/")))
+        {
+            continue;
+        }
+        let body = if pointer.is_empty() {
+            let mut body = doc.clone();
+            body.remove("$schema");
+            Value::Object(body)
+        } else {
+            match pointer_target(doc, &pointer) {
+                Some(body) => body.clone(),
+                None => continue,
+            }
+        };
+        let Some(mut target) = merged_target(&body, &repeats, &render) else {
+            continue;
+        };
+        let (reference, name) = if pointer.is_empty() {
+            (json!({ "$ref": "#" }), None)
+        } else {
+            let name = unique_name(&sanitize_name(key.as_deref().unwrap_or("def")), &defs);
+            let reference = json!({ "$ref": format!("#/{keyword}/{}", escape(&name)) });
+            (reference, Some(name))
+        };
+        for relative in &repeats {
+            if let Some(slot) = pointer_target(&mut target, relative) {
+                *slot = reference.clone();
+            }
+        }
+        match name {
+            None => {
+                doc.retain(|k, _| k == "$schema");
+                doc.extend(target);
+            }
+            Some(name) => {
+                defs.insert(name, Value::Object(target));
+                if let Some(slot) = pointer_target(doc, &pointer) {
+                    *slot = reference;
+                }
+            }
+        }
+        linked.push(pointer);
+    }
+    if !defs.is_empty() {
+        doc.insert(keyword.to_string(), Value::Object(defs));
+    }
+}
+
+// Объемлющая схема, слитая на уровне Node со всеми повторами под ней,
+// и повторами повторов, — ссылка на неё принимает любое из исходных значений
+fn merged_target(
+    body: &Value,
+    repeats: &[String],
+    render: &InferOptions,
+) -> Option<Map<String, Value>> {
+    if holds_keyword(body, &["$ref", "oneOf"]) {
+        return None;
+    }
+    let mut merged: Option<Node> = None;
+    let mut queue = vec![body];
+    while let Some(schema) = queue.pop() {
+        let node = Node::from_json_schema(schema).ok()?;
+        match &mut merged {
+            Some(merged) => merged.merge_with(node),
+            None => merged = Some(node),
+        }
+        queue.extend(repeats.iter().filter_map(|r| schema.pointer(r)));
+    }
+    let Value::Object(mut target) = merged?.to_json_schema(render) else {
+        return None;
+    };
+    copy_annotations(body, &mut target);
+    Some(target)
+}
+
+fn holds_keyword(v: &Value, keywords: &[&str]) -> bool {
+    match v {
+        Value::Object(m) => m.iter().any(|(k, child)| {
+            keywords.contains(&k.as_str())
+                || !DATA_KEYWORDS.contains(&k.as_str()) && holds_keyword(child, keywords)
+        }),
+        Value::Array(items) => items.iter().any(|item| holds_keyword(item, keywords)),
+        _ => false,
+    }
+}
+
+// title и description из аннотаций не переживают восстановление узла
+fn copy_annotations(from: &Value, to: &mut Map<String, Value>) {
+    let Some(from) = from.as_object() else {
+        return;
+    };
+    for key in ["title", "description"] {
+        if let (Some(text), false) = (from.get(key), to.contains_key(key)) {
+            to.insert(key.to_string(), text.clone());
+        }
+    }
+    if let (Some(Value::Object(props)), Some(Value::Object(target))) =
+        (from.get("properties"), to.get_mut("properties"))
+    {
+        for (k, sub) in props {
+            if let Some(Value::Object(t)) = target.get_mut(k) {
+                copy_annotations(sub, t);
+            }
+        }
+    }
+    if let (Some(items), Some(Value::Object(t))) = (from.get("items"), to.get_mut("items")) {
+        copy_annotations(items, t);
+    }
+}
+
+impl Walk<'_> {
+    fn object(&mut self, m: &Map<String, Value>, pointer: &str, key: Option<&str>) {
+        let shape = self.shape(m);
+        if let Some(shape) = &shape {
+            // ближайшие recursion_max_depth предков, из них берётся внешний
+            let window = self
+                .enclosing
+                .len()
+                .saturating_sub(self.opts.recursion_max_depth);
+            let ancestor = self.enclosing[window..].iter().find(|e| &e.shape == shape);
+            if let Some(ancestor) = ancestor {
+                let found = (
+                    pointer.to_string(),
+                    ancestor.pointer.clone(),
+                    ancestor.key.clone(),
+                );
+                self.found.push(found);
+                return;
+            }
+        }
+        let pushed = shape.is_some();
+        if let Some(shape) = shape {
+            self.enclosing.push(Enclosing {
+                pointer: pointer.to_string(),
+                key: key.map(String::from),
+                shape,
+            });
+        }
+        for (k, child) in m {
+            if DATA_KEYWORDS.contains(&k.as_str()) {
+                continue;
+            }
+            let child_pointer = format!("{pointer}/{}", escape(k));
+            match (k.as_str(), child) {
+                ("properties", Value::Object(props)) => {
+                    for (name, sub) in props {
+                        let sub_pointer = format!("{child_pointer}/{}", escape(name));
+                        self.value(sub, &sub_pointer, Some(name));
+                    }
+                }
+                _ => self.value(child, &child_pointer, key),
+            }
+        }
+        if pushed {
+            self.enclosing.pop();
+        }
+    }
+
+    fn value(&mut self, v: &Value, pointer: &str, key: Option<&str>) {
+        match v {
+            Value::Object(m) => self.object(m, pointer, key),
+            Value::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    self.value(item, &format!("{pointer}/{i}"), key);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn shape(&self, m: &Map<String, Value>) -> Option<Shape> {
+        let props = m.get("properties")?.as_object()?;
+        if props.len() < self.opts.recursion_min_properties.max(1) {
+            return None;
+        }
+        let mut shape: Shape = props
+            .iter()
+            .map(|(k, sub)| (k.clone(), effective_type(sub)))
+            .collect();
+        shape.sort_by(|a, b| a.0.cmp(&b.0));
+        Some(shape)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::infer_with;
+    use crate::{validate_sample_rs, InferOptions, SchemaDraft};
+    use serde_json::{json, Value};
+
+    fn recursive() -> InferOptions {
+        InferOptions {
+            detect_recursion: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn tree_at_the_root_refers_to_itself() {
+        let samples = [
+            r#"{"name":"a","children":[{"name":"b","children":[{"name":"c","children":[]}]}]}"#,
+            r#"{"name":"d","children":[]}"#,
+        ];
+        let v = infer_with(&samples, recursive());
+        assert_eq!(v["properties"]["children"]["items"], json!({"$ref": "#"}));
+        assert_eq!(v["required"], json!(["children", "name"]));
+        assert!(v.get("$defs").is_none());
+        accepts_own_samples(&v, &samples);
+
+        let without = infer_with(&samples, InferOptions::default());
+        assert_eq!(
+            without["properties"]["children"]["items"]["properties"]["name"],
+            json!({"type": "string"})
+        );
+    }
+
+    #[test]
+    fn nested_tree_moves_to_defs() {
+        let samples = [
+            r#"{"id":1,"menu":{"label":"File","items":[{"label":"Open","items":[]},{"label":"Recent","items":[{"label":"a.txt","items":[]}]}]}}"#,
+        ];
+        let v = infer_with(&samples, recursive());
+        assert_eq!(v["properties"]["menu"], json!({"$ref": "#/$defs/menu"}));
+        let menu = &v["$defs"]["menu"];
+        assert_eq!(
+            menu["properties"]["items"]["items"],
+            json!({"$ref": "#/$defs/menu"})
+        );
+        assert_eq!(menu["properties"]["label"], json!({"type": "string"}));
+        // вложенные данные по-прежнему проходят проверку, в том числе через $ref
+        accepts_own_samples(&v, &samples);
+        let schema = serde_json::to_string(&v).unwrap();
+        let errors = validate_sample_rs(
+            &schema,
+            r#"{"id":1,"menu":{"label":"File","items":[{"label":"Open","items":[{"label":2,"items":[]}]}]}}"#,
+        );
+        assert_eq!(errors.unwrap()[0].path, "menu.items[0].items[0].label");
+
+        let draft07 = InferOptions {
+            draft: SchemaDraft::Draft07,
+            ..recursive()
+        };
+        let v = infer_with(&samples, draft07);
+        assert_eq!(
+            v["properties"]["menu"],
+            json!({"$ref": "#/definitions/menu"})
+        );
+    }
+
+    fn accepts_own_samples(v: &Value, samples: &[&str]) {
+        let schema = serde_json::to_string(v).unwrap();
+        for sample in samples {
+            assert_eq!(validate_sample_rs(&schema, sample).unwrap(), [], "{sample}");
+        }
+    }
+
+    #[test]
+    fn deeper_leaves_are_merged_into_the_target() {
+        let samples = [r#"{"a":{"x":1,"y":{"x":2,"y":{"x":3,"y":"leaf"}}}}"#];
+        let v = infer_with(&samples, recursive());
+        let a = &v["$defs"]["a"];
+        assert_eq!(a["properties"]["y"], json!({"$ref": "#/$defs/a"}));
+        // цель ссылки допускает строку из самого глубокого y
+        assert_eq!(a["type"], json!(["object", "string"]));
+        accepts_own_samples(&v, &samples);
+        let schema = serde_json::to_string(&v).unwrap();
+        let errors = validate_sample_rs(&schema, r#"{"a":{"x":1,"y":{"x":"2","y":"leaf"}}}"#);
+        assert_eq!(errors.unwrap()[0].path, "a.y.x");
+
+        let samples = [r#"{"v":{"v":{"v":1}}}"#];
+        let loose = InferOptions {
+            recursion_min_properties: 1,
+            ..recursive()
+        };
+        let v = infer_with(&samples, loose);
+        assert_eq!(v["properties"]["v"], json!({"$ref": "#"}));
+        accepts_own_samples(&v, &samples);
+    }
+
+    #[test]
+    fn small_or_distant_shapes_stay_inline() {
+        // {"v": ...} — одно свойство, меньше порога
+        let samples = [r#"{"v":{"v":{"v":1}}}"#];
+        let v = infer_with(&samples, recursive());
+        assert_eq!(v["properties"]["v"]["properties"]["v"]["type"], "object");
+        let loose = InferOptions {
+            recursion_min_properties: 1,
+            ..recursive()
+        };
+        // у корня та же форма {v: object}
+        let v = infer_with(&samples, loose);
+        assert_eq!(v["properties"]["v"], json!({"$ref": "#"}));
+
+        // форма повторяется через два уровня — вне окна из одного предка
+        let samples = [r#"{"a":{"x":1,"y":{"p":1,"q":{"x":2,"y":{"p":3,"q":4}}}}}"#];
+        let near = InferOptions {
+            recursion_max_depth: 1,
+            ..recursive()
+        };
+        let v = infer_with(&samples, near);
+        assert!(v.get("$defs").is_none());
+        let v = infer_with(&samples, recursive());
+        assert_eq!(
+            v["$defs"]["a"]["properties"]["y"]["properties"]["q"],
+            json!({"$ref": "#/$defs/a"})
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use serde_json::Value;
+
+use crate::diff::{effective_type, parse_schema};
+use crate::InferError;
+
+/// ANSI `CREATE TABLE` for a flat object schema: one column per property,
+/// `NOT NULL` unless the property is optional or may be null. Object, array
+/// and mixed-type properties become comments instead of columns.
+pub fn schema_to_sql_ddl_rs(schema: &str, table_name: &str) -> Result<String, InferError> {
+    let schema = parse_schema(schema, "schema")?;
+    let unsupported = |reason: &str| InferError::UnsupportedSchema {
+        path: "<root>".to_string(),
+        reason: reason.to_string(),
+    };
+    let props = schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .filter(|p| !p.is_empty())
+        .ok_or_else(|| unsupported("a table needs an object schema with properties"))?;
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    let mut columns: Vec<String> = Vec::new();
+    // Комментарии ставятся перед следующей колонкой, чтобы не ломать запятые
+    let mut pending = String::new();
+    for (k, child) in props {
+        let name = quote(k);
+        let mut types: Vec<String> = match effective_type(child) {
+            Value::String(t) => vec![t],
+            Value::Array(ts) => ts
+                .into_iter()
+                .filter_map(|t| t.as_str().map(String::from))
+                .collect(),
+            _ => Vec::new(),
+        };
+        let nullable = types.iter().any(|t| t == "null")
+            || child.get("nullable") == Some(&Value::Bool(true))
+            || !required.contains(&k.as_str());
+        types.retain(|t| t != "null");
+
+        let sql_type = match types.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+            ["integer"] => Ok("BIGINT"),
+            ["number"] | ["integer", "number"] => Ok("DOUBLE PRECISION"),
+            ["string"] => Ok("TEXT"),
+            ["boolean"] => Ok("BOOLEAN"),
+            ["object"] => Err("object columns are not supported".to_string()),
+            ["array"] => Err("array columns are not supported".to_string()),
+            [] => Err("columns without a type are not supported".to_string()),
+            _ => Err(format!(
+                "mixed types ({}) are not supported",
+                types.join(", ")
+            )),
+        };
+        match sql_type {
+            Ok(sql_type) => {
+                let null = if nullable { "NULL" } else { "NOT NULL" };
+                columns.push(format!("{pending}  {name} {sql_type} {null}"));
+                pending.clear();
+            }
+            Err(reason) => pending.push_str(&format!("  -- {name}: {reason}\n")),
+        }
+    }
+    if columns.is_empty() {
+        return Err(unsupported("no property maps to a SQL column"));
+    }
+
+    let mut out = format!("CREATE TABLE {} (\n", quote(table_name));
+    out.push_str(&columns.join(",\n"));
+    out.push('\n');
+    out.push_str(&pending);
+    out.push_str(");\n");
+    Ok(out)
+}
+
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scalar_columns_with_nullability() {
+        let schema = r#"{"type":"object","properties":{
+            "active":{"type":"boolean"},
+            "id":{"type":"integer"},
+            "meta":{"type":"object","properties":{"a":{"type":"integer"}}},
+            "name":{"type":["null","string"]},
+            "note":{"type":"string","nullable":true},
+            "score":{"type":"number"},
+            "tags":{"type":"array","items":{"type":"string"}},
+            "x\"y":{"type":"string"}},
+            "required":["active","id","meta","name","note","score","tags"]}"#;
+        let expected = "\
+CREATE TABLE \"events\" (
+  \"active\" BOOLEAN NOT NULL,
+  \"id\" BIGINT NOT NULL,
+  -- \"meta\": object columns are not supported
+  \"name\" TEXT NULL,
+  \"note\" TEXT NULL,
+  \"score\" DOUBLE PRECISION NOT NULL,
+  -- \"tags\": array columns are not supported
+  \"x\"\"y\" TEXT NULL
+);
+";
+        assert_eq!(schema_to_sql_ddl_rs(schema, "events").unwrap(), expected);
+    }
+
+    #[test]
+    fn rejects_schemas_without_columns() {
+        let err = schema_to_sql_ddl_rs(r#"{"type":"array"}"#, "t").unwrap_err();
+        assert!(matches!(err, InferError::UnsupportedSchema { .. }));
+        let err = schema_to_sql_ddl_rs(
+            r#"{"type":"object","properties":{"o":{"type":"object"}}}"#,
+            "t",
+        )
+        .unwrap_err();
+        assert!(matches!(err, InferError::UnsupportedSchema { .. }));
+    }
+}
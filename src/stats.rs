@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+
+use serde_json::json;
+
+use crate::diff::parse_schema;
+use crate::{walk_node, InferError, Node, NodeVisitor, TypeTag};
+
+#[derive(Default)]
+struct StatsVisitor {
+    total_paths: u64,
+    types: BTreeMap<&'static str, u64>,
+    max_depth: usize,
+    object_count: u64,
+    array_count: u64,
+}
+
+impl NodeVisitor for StatsVisitor {
+    fn visit_node(&mut self, path: &str, node: &Node) {
+        // корень описывает документ целиком и в статистику путей не входит
+        if path.is_empty() {
+            return;
+        }
+        self.total_paths += 1;
+        for tag in &node.types {
+            *self.types.entry(tag.as_str()).or_default() += 1;
+        }
+        self.object_count += u64::from(node.types.contains(&TypeTag::Object));
+        self.array_count += u64::from(node.types.contains(&TypeTag::Array));
+        self.max_depth = self.max_depth.max(depth(path));
+    }
+}
+
+// Каждое свойство и каждый шаг в элементы массива ([]) — уровень
+fn depth(path: &str) -> usize {
+    path.split('.')
+        .map(|segment| {
+            let items = segment.matches("[]").count();
+            items + usize::from(segment.len() > 2 * items)
+        })
+        .sum()
+}
+
+/// Size of `schema` as JSON: `total_paths` (properties and array items at any
+/// depth), `type_distribution` (paths per JSON type), `max_depth`, and how
+/// many paths are objects (`object_count`) or arrays (`array_count`).
+pub fn schema_stats_rs(schema: &str) -> Result<String, InferError> {
+    let node = Node::from_json_schema(&parse_schema(schema, "schema")?)?;
+    let mut stats = StatsVisitor::default();
+    walk_node(&node, "", &mut stats);
+    let out = json!({
+        "total_paths": stats.total_paths,
+        "type_distribution": stats.types,
+        "max_depth": stats.max_depth,
+        "object_count": stats.object_count,
+        "array_count": stats.array_count,
+    });
+    serde_json::to_string_pretty(&out).map_err(InferError::SerializeError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn nested_schema_distribution() {
+        let schema = r#"{"type":"object","properties":{
+            "id":{"type":"integer"},
+            "name":{"type":["string","null"]},
+            "meta":{"type":"object","properties":{"score":{"type":"number"},"ok":{"type":"boolean"}}},
+            "tags":{"type":"array","items":{"type":"string"}}}}"#;
+        let v: Value = serde_json::from_str(&schema_stats_rs(schema).unwrap()).unwrap();
+        assert_eq!(
+            v,
+            json!({
+                "total_paths": 7,
+                "type_distribution": {
+                    "array": 1, "boolean": 1, "integer": 1, "null": 1,
+                    "number": 1, "object": 1, "string": 2
+                },
+                "max_depth": 2,
+                "object_count": 1,
+                "array_count": 1,
+            })
+        );
+        assert_eq!(depth("rows[].id"), 3);
+        assert_eq!(depth("grid[][]"), 3);
+    }
+}
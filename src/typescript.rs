@@ -0,0 +1,350 @@
+use ahash::{AHashMap, AHashSet};
+use serde_json::{Map, Value};
+
+use crate::defs::escape;
+use crate::diff::parse_schema;
+use crate::InferError;
+
+/// TypeScript declarations for `schema`: an `interface Root` for an object
+/// root (`type Root = ...` otherwise) plus one interface per nested object
+/// with `properties`, named after its path (`RootMeta`, `RootRowsItem`), and
+/// one declaration per `$defs` / `definitions` entry. A name already taken
+/// gets a `_2`, `_3`, ... suffix. Properties missing from `required` become
+/// optional; `anyOf` and `oneOf` become unions, and `$ref` to `#` or to a
+/// definition names its declaration. Other `$ref` become `unknown`.
+pub fn schema_to_typescript_rs(schema: &str) -> Result<String, InferError> {
+    let schema = parse_schema(schema, "schema")?;
+    let mut emitter = Emitter::default();
+    emitter.reserve("Root");
+    emitter.refs.insert("#".to_string(), "Root".to_string());
+    let mut defs = Vec::new();
+    for keyword in ["$defs", "definitions"] {
+        for (k, def) in schema
+            .get(keyword)
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+        {
+            let name = emitter.reserve(&pascal_case(k));
+            let pointer = format!("#/{keyword}/{}", escape(k));
+            emitter.refs.insert(pointer, name.clone());
+            defs.push((name, def));
+        }
+    }
+
+    if has_properties(&schema) {
+        emitter.interface("Root", &schema);
+    } else {
+        let ty = emitter.type_expr(&schema, "Root");
+        emitter.decls.push(format!("export type Root = {ty};\n"));
+    }
+    for (name, def) in defs {
+        if has_properties(def) {
+            emitter.interface(&name, def);
+        } else {
+            let ty = emitter.type_expr(def, &name);
+            emitter.decls.push(format!("export type {name} = {ty};\n"));
+        }
+    }
+    Ok(emitter.decls.join("\n"))
+}
+
+#[derive(Default)]
+struct Emitter {
+    decls: Vec<String>,
+    // занятые имена объявлений
+    names: AHashSet<String>,
+    // $ref → имя объявления
+    refs: AHashMap<String, String>,
+}
+
+impl Emitter {
+    // Свободное имя на основе base, как defs::unique_name
+    fn reserve(&mut self, base: &str) -> String {
+        let base = if base.is_empty() { "Def" } else { base };
+        let name = match self.names.contains(base) {
+            false => base.to_string(),
+            true => (2..)
+                .map(|n| format!("{base}_{n}"))
+                .find(|name| !self.names.contains(name))
+                .unwrap_or_default(),
+        };
+        self.names.insert(name.clone());
+        name
+    }
+
+    // Родительский интерфейс идёт раньше вложенных
+    fn interface(&mut self, name: &str, schema: &Value) {
+        let slot = self.decls.len();
+        self.decls.push(String::new());
+
+        let empty = Map::new();
+        let props = schema
+            .get("properties")
+            .and_then(Value::as_object)
+            .unwrap_or(&empty);
+        let required: Vec<&str> = schema
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|r| r.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+
+        let mut body = format!("export interface {name} {{\n");
+        for (k, child) in props {
+            let ty = self.type_expr(child, &format!("{name}{}", pascal_case(k)));
+            let optional = if required.contains(&k.as_str()) {
+                ""
+            } else {
+                "?"
+            };
+            body.push_str(&format!("  {}{optional}: {ty};\n", property_key(k)));
+        }
+        if let Some(extra) = schema.get("additionalProperties").filter(|v| v.is_object()) {
+            let ty = self.type_expr(extra, &format!("{name}Value"));
+            body.push_str(&format!("  [key: string]: {ty};\n"));
+        }
+        body.push_str("}\n");
+        self.decls[slot] = body;
+    }
+
+    fn type_expr(&mut self, schema: &Value, name: &str) -> String {
+        let Value::Object(s) = schema else {
+            return if schema == &Value::Bool(false) {
+                "never".to_string()
+            } else {
+                "unknown".to_string()
+            };
+        };
+        if let Some(reference) = s.get("$ref").and_then(Value::as_str) {
+            return match self.refs.get(reference) {
+                Some(name) => name.clone(),
+                None => "unknown".to_string(),
+            };
+        }
+        if let Some(c) = s.get("const") {
+            return literal(c);
+        }
+        if let Some(Value::Array(values)) = s.get("enum") {
+            return union(values.iter().map(literal).collect());
+        }
+        let branches = s.get("anyOf").or_else(|| s.get("oneOf"));
+        if let Some(Value::Array(branches)) = branches.filter(|_| s.get("type").is_none()) {
+            let types = branches.iter().map(|b| self.type_expr(b, name)).collect();
+            return union(types);
+        }
+
+        let names: Vec<&str> = match s.get("type") {
+            Some(Value::String(t)) => vec![t.as_str()],
+            Some(Value::Array(ts)) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => return "unknown".to_string(),
+        };
+        let mut types = Vec::new();
+        for t in names {
+            let ty = match t {
+                "integer" | "number" => "number".to_string(),
+                "string" => "string".to_string(),
+                "boolean" => "boolean".to_string(),
+                "null" => "null".to_string(),
+                "array" if tuple_positions(s).is_some() => {
+                    let prefix = tuple_positions(s).into_iter().flatten();
+                    let positions: Vec<String> = prefix
+                        .enumerate()
+                        .map(|(i, p)| self.type_expr(p, &format!("{name}{i}")))
+                        .collect();
+                    format!("[{}]", positions.join(", "))
+                }
+                "array" => {
+                    let item = match s.get("items") {
+                        Some(items) => self.type_expr(items, &format!("{name}Item")),
+                        None => "unknown".to_string(),
+                    };
+                    if item.contains(' ') {
+                        format!("({item})[]")
+                    } else {
+                        format!("{item}[]")
+                    }
+                }
+                "object" if has_properties(schema) => {
+                    let name = self.reserve(name);
+                    self.interface(&name, schema);
+                    name
+                }
+                "object" => match s.get("additionalProperties").filter(|v| v.is_object()) {
+                    Some(values) => {
+                        format!(
+                            "Record<string, {}>",
+                            self.type_expr(values, &format!("{name}Value"))
+                        )
+                    }
+                    None => "Record<string, unknown>".to_string(),
+                },
+                _ => "unknown".to_string(),
+            };
+            types.push(ty);
+        }
+        if s.get("nullable") == Some(&Value::Bool(true)) {
+            types.push("null".to_string());
+        }
+        union(types)
+    }
+}
+
+// prefixItems, а до 2020-12 — массив в items
+fn tuple_positions(s: &Map<String, Value>) -> Option<&Vec<Value>> {
+    s.get("prefixItems")
+        .or_else(|| s.get("items"))
+        .and_then(Value::as_array)
+}
+
+fn has_properties(schema: &Value) -> bool {
+    schema.get("properties").is_some_and(Value::is_object)
+}
+
+// number и integer дают один и тот же тип — повторы убираем
+fn union(types: Vec<String>) -> String {
+    let mut unique: Vec<String> = Vec::new();
+    for t in types {
+        if !unique.contains(&t) {
+            unique.push(t);
+        }
+    }
+    match unique.len() {
+        0 => "never".to_string(),
+        _ => unique.join(" | "),
+    }
+}
+
+fn literal(v: &Value) -> String {
+    match v {
+        Value::Null | Value::Bool(_) | Value::Number(_) | Value::String(_) => v.to_string(),
+        _ => "unknown".to_string(),
+    }
+}
+
+fn property_key(k: &str) -> String {
+    let mut chars = k.chars();
+    let identifier = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if identifier {
+        k.to_string()
+    } else {
+        Value::String(k.to_string()).to_string()
+    }
+}
+
+fn pascal_case(k: &str) -> String {
+    k.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            let first = chars.next().map(|c| c.to_ascii_uppercase());
+            first.into_iter().chain(chars).collect::<String>()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{infer_schema_from_strs, InferOptions};
+
+    #[test]
+    fn nested_objects_become_named_interfaces() {
+        let samples = [
+            r#"{"id":1,"name":"a","meta":{"created_at":"x","score":1.5,"owner":null},"tags":["t"],"rows":[{"n":1,"v":"x"}]}"#,
+            r#"{"id":2,"meta":{"created_at":"y","score":2,"owner":"o"},"tags":[],"rows":[{"n":2,"v":3}],"x-y":true}"#,
+        ];
+        let schema = infer_schema_from_strs(&samples, InferOptions::default()).unwrap();
+        let expected = "\
+export interface Root {
+  id: number;
+  meta: RootMeta;
+  name?: string;
+  rows: RootRowsItem[];
+  tags: string[];
+  \"x-y\"?: boolean;
+}
+
+export interface RootMeta {
+  created_at: string;
+  owner: null | string;
+  score: number;
+}
+
+export interface RootRowsItem {
+  n: number;
+  v: number | string;
+}
+";
+        assert_eq!(schema_to_typescript_rs(&schema).unwrap(), expected);
+    }
+
+    #[test]
+    fn colliding_names_get_suffixes() {
+        let schema = r#"{"type":"object","properties":{
+            "a_b":{"type":"object","properties":{"x":{"type":"string"}}},
+            "aB":{"type":"object","properties":{"x":{"type":"number"}}},
+            "rowsItem":{"type":"object","properties":{"n":{"type":"integer"}}},
+            "rows":{"type":"array","items":{"type":"object","properties":{"v":{"type":"string"}}}}}}"#;
+        let ts = schema_to_typescript_rs(schema).unwrap();
+        assert!(ts.contains("  a_b?: RootAB;\n  aB?: RootAB_2;\n"));
+        assert!(ts.contains("  rowsItem?: RootRowsItem;\n  rows?: RootRowsItem_2[];\n"));
+        for name in ["RootAB", "RootAB_2", "RootRowsItem", "RootRowsItem_2"] {
+            let decl = format!("export interface {name} {{");
+            assert_eq!(ts.matches(&decl).count(), 1, "{name}");
+        }
+    }
+
+    #[test]
+    fn refs_and_one_of_name_declarations() {
+        let samples = [r#"{"menu":{"label":"a","items":[{"label":"b","items":[]}]}}"#];
+        let opts = InferOptions {
+            detect_recursion: true,
+            ..Default::default()
+        };
+        let schema = infer_schema_from_strs(&samples, opts).unwrap();
+        let expected = "\
+export interface Root {
+  menu: Menu;
+}
+
+export interface Menu {
+  items: Menu[];
+  label: string;
+}
+";
+        assert_eq!(schema_to_typescript_rs(&schema).unwrap(), expected);
+
+        let schema = r##"{"oneOf":[{"$ref":"#/definitions/a"},{"type":"string"}],
+            "definitions":{"a":{"type":"object","properties":{"next":{"$ref":"#"}}}}}"##;
+        assert_eq!(
+            schema_to_typescript_rs(schema).unwrap(),
+            "export type Root = A | string;\n\nexport interface A {\n  next?: Root;\n}\n"
+        );
+    }
+
+    #[test]
+    fn non_object_roots_and_literals() {
+        let ts = |s: &str| schema_to_typescript_rs(s).unwrap();
+        assert_eq!(
+            ts(r#"{"type":"array","items":{"type":["integer","string"]}}"#),
+            "export type Root = (number | string)[];\n"
+        );
+        assert_eq!(
+            ts(
+                r#"{"type":"array","prefixItems":[{"type":"string"},{"type":"integer"}],"items":false}"#
+            ),
+            "export type Root = [string, number];\n"
+        );
+        assert_eq!(
+            ts(r#"{"enum":["a","b"]}"#),
+            "export type Root = \"a\" | \"b\";\n"
+        );
+        assert_eq!(
+            ts(r#"{"type":"object","additionalProperties":{"type":"integer"}}"#),
+            "export type Root = Record<string, number>;\n"
+        );
+    }
+}
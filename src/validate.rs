@@ -0,0 +1,564 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::cmp::Ordering;
+
+use crate::diff::parse_schema;
+use crate::{all_distinct, cmp_numbers, is_multiple_of, InferError, TypeTag};
+
+/// One constraint a sample breaks. `path` uses dot notation with array
+/// indices (`rows[2].id`); the root is `<root>`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidationError {
+    pub path: String,
+    pub message: String,
+}
+
+/// Checks `sample` against `schema`, covering the keywords the inferrer emits
+/// (`type`, `nullable`, `const`, `enum`, `anyOf`, `oneOf`, `not`, `allOf`,
+/// `if` / `then` / `else`, numeric, length, item and property-count bounds,
+/// `multipleOf`, `uniqueItems`, `contains` with `minContains`, `properties`,
+/// `required`, `additionalProperties`, `dependentRequired`,
+/// `dependentSchemas`, `dependencies`, `items`, `prefixItems`, the
+/// pre-2020-12 tuple form `items: [...]` with `additionalItems`, and `$ref` to
+/// `#` or a pointer into the same document such as `#/$defs/...`). `format`,
+/// `pattern` and `propertyNames` are not checked. An empty result means the
+/// sample is valid.
+pub fn validate_sample_rs(schema: &str, sample: &str) -> Result<Vec<ValidationError>, InferError> {
+    let schema = parse_schema(schema, "schema")?;
+    let sample: Value = serde_json::from_str(sample)
+        .map_err(|source| InferError::InvalidJson { index: 0, source })?;
+    let mut errors = Vec::new();
+    validate(&schema, &schema, &sample, "", &mut errors);
+    Ok(errors)
+}
+
+/// Whether `v` satisfies `schema`, with the keyword coverage of `validate_sample_rs`.
+pub(crate) fn is_valid(schema: &Value, v: &Value) -> bool {
+    matches(schema, schema, v)
+}
+
+// Как is_valid, но $ref разрешается от корня root
+fn matches(root: &Value, schema: &Value, v: &Value) -> bool {
+    let mut errors = Vec::new();
+    validate(root, schema, v, "", &mut errors);
+    errors.is_empty()
+}
+
+// Больше переходов $ref подряд, не спускаясь в значение, — цикл
+const MAX_REF_HOPS: usize = 64;
+
+fn resolve_ref<'r>(root: &'r Value, reference: &str) -> Result<&'r Value, String> {
+    let mut reference = reference;
+    for _ in 0..MAX_REF_HOPS {
+        let target = reference
+            .strip_prefix('#')
+            .and_then(|pointer| root.pointer(pointer))
+            .ok_or_else(|| format!("cannot resolve $ref \"{reference}\""))?;
+        match target.get("$ref").and_then(Value::as_str) {
+            Some(next) => reference = next,
+            None => return Ok(target),
+        }
+    }
+    Err(format!("$ref \"{reference}\" does not lead to a schema"))
+}
+
+fn validate(
+    root: &Value,
+    schema: &Value,
+    v: &Value,
+    path: &str,
+    errors: &mut Vec<ValidationError>,
+) {
+    let Value::Object(s) = schema else {
+        if schema == &Value::Bool(false) {
+            push(errors, path, "no value is allowed here".to_string());
+        }
+        return;
+    };
+
+    // цепочка из одних $ref проходится сразу, ключевые слова рядом проверяются ниже
+    if let Some(reference) = s.get("$ref").and_then(Value::as_str) {
+        match resolve_ref(root, reference) {
+            Ok(target) => validate(root, target, v, path, errors),
+            Err(message) => push(errors, path, message),
+        }
+    }
+
+    if let Some(c) = s.get("const") {
+        if c != v {
+            push(errors, path, format!("expected {c}, got {v}"));
+        }
+    }
+    if let Some(Value::Array(values)) = s.get("enum") {
+        if !values.contains(v) {
+            push(
+                errors,
+                path,
+                format!("{v} is not one of {}", Value::Array(values.clone())),
+            );
+        }
+    }
+    if let Some(Value::Array(branches)) = s.get("anyOf") {
+        if !branches.iter().any(|b| matches(root, b, v)) {
+            push(errors, path, "does not match any anyOf branch".to_string());
+        }
+    }
+    if let Some(Value::Array(branches)) = s.get("oneOf") {
+        match branches.iter().filter(|b| matches(root, b, v)).count() {
+            0 => push(errors, path, "does not match any oneOf branch".to_string()),
+            1 => {}
+            n => push(
+                errors,
+                path,
+                format!("matches {n} oneOf branches, expected exactly one"),
+            ),
+        }
+    }
+    if let Some(negated) = s.get("not") {
+        if matches(root, negated, v) {
+            push(errors, path, "matches the schema under not".to_string());
+        }
+    }
+    for sub in s
+        .get("allOf")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        validate(root, sub, v, path, errors);
+    }
+    if let Some(condition) = s.get("if") {
+        let branch = if matches(root, condition, v) {
+            s.get("then")
+        } else {
+            s.get("else")
+        };
+        if let Some(branch) = branch {
+            validate(root, branch, v, path, errors);
+        }
+    }
+    if let Some(expected) = s.get("type") {
+        let nullable = s.get("nullable") == Some(&Value::Bool(true));
+        let allowed = match expected {
+            Value::String(t) => has_type(v, t),
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).any(|t| has_type(v, t)),
+            _ => true,
+        };
+        if !(allowed || nullable && v.is_null()) {
+            push(
+                errors,
+                path,
+                format!("expected type {expected}, got {}", TypeTag::of(v)),
+            );
+            // остальные ключевые слова относятся к другому типу
+            return;
+        }
+    }
+
+    match v {
+        Value::Number(n) => {
+            let bound = |key: &str| s.get(key).and_then(Value::as_number);
+            let cmp = |key: &str| bound(key).map(|b| (cmp_numbers(n, b), b));
+            // draft-04: exclusiveMinimum / exclusiveMaximum — флаг строгости minimum / maximum
+            let strict = |key: &str| s.get(key) == Some(&Value::Bool(true));
+            match cmp("minimum") {
+                Some((Ordering::Less, b)) => {
+                    push(errors, path, format!("{n} is less than minimum {b}"));
+                }
+                Some((Ordering::Equal, b)) if strict("exclusiveMinimum") => {
+                    push(
+                        errors,
+                        path,
+                        format!("{n} is not above exclusive minimum {b}"),
+                    );
+                }
+                _ => {}
+            }
+            if let Some((Ordering::Less | Ordering::Equal, b)) = cmp("exclusiveMinimum") {
+                push(
+                    errors,
+                    path,
+                    format!("{n} is not above exclusiveMinimum {b}"),
+                );
+            }
+            match cmp("maximum") {
+                Some((Ordering::Greater, b)) => {
+                    push(errors, path, format!("{n} is greater than maximum {b}"));
+                }
+                Some((Ordering::Equal, b)) if strict("exclusiveMaximum") => {
+                    push(
+                        errors,
+                        path,
+                        format!("{n} is not below exclusive maximum {b}"),
+                    );
+                }
+                _ => {}
+            }
+            if let Some((Ordering::Greater | Ordering::Equal, b)) = cmp("exclusiveMaximum") {
+                push(
+                    errors,
+                    path,
+                    format!("{n} is not below exclusiveMaximum {b}"),
+                );
+            }
+            if let (Some(step), Some(x)) =
+                (bound("multipleOf").and_then(|b| b.as_f64()), n.as_f64())
+            {
+                if step > 0.0 && !is_multiple_of(x, step) {
+                    push(errors, path, format!("{n} is not a multiple of {step}"));
+                }
+            }
+        }
+        Value::String(text) => {
+            let len = text.chars().count();
+            check_size(s, "minLength", "maxLength", len, "characters", path, errors);
+        }
+        Value::Array(items) => {
+            check_size(
+                s,
+                "minItems",
+                "maxItems",
+                items.len(),
+                "items",
+                path,
+                errors,
+            );
+            if s.get("uniqueItems") == Some(&Value::Bool(true)) && !all_distinct(items) {
+                push(errors, path, "items are not unique".to_string());
+            }
+            if let Some(candidate) = s.get("contains") {
+                let found = items
+                    .iter()
+                    .filter(|item| matches(root, candidate, item))
+                    .count();
+                let min = s.get("minContains").and_then(Value::as_u64).unwrap_or(1);
+                if (found as u64) < min {
+                    push(
+                        errors,
+                        path,
+                        format!("{found} items match contains, expected at least {min}"),
+                    );
+                }
+            }
+            let (prefix, rest) = match (s.get("prefixItems"), s.get("items")) {
+                (Some(prefix), rest) => (prefix.as_array(), rest),
+                (None, Some(Value::Array(prefix))) => (Some(prefix), s.get("additionalItems")),
+                (None, rest) => (None, rest),
+            };
+            for (i, (p, item)) in prefix.into_iter().flatten().zip(items).enumerate() {
+                validate(root, p, item, &format!("{path}[{i}]"), errors);
+            }
+            if let Some(item_schema) = rest {
+                let skip = prefix.map_or(0, Vec::len);
+                for (i, item) in items.iter().enumerate().skip(skip) {
+                    validate(root, item_schema, item, &format!("{path}[{i}]"), errors);
+                }
+            }
+        }
+        Value::Object(obj) => {
+            validate_object(root, s, obj, path, errors);
+            validate_dependencies(root, s, obj, v, path, errors);
+        }
+        _ => {}
+    }
+}
+
+fn validate_object(
+    root: &Value,
+    s: &Map<String, Value>,
+    obj: &Map<String, Value>,
+    path: &str,
+    errors: &mut Vec<ValidationError>,
+) {
+    check_size(
+        s,
+        "minProperties",
+        "maxProperties",
+        obj.len(),
+        "properties",
+        path,
+        errors,
+    );
+    if let Some(Value::Array(required)) = s.get("required") {
+        for key in required.iter().filter_map(Value::as_str) {
+            if !obj.contains_key(key) {
+                push(errors, path, format!("missing required property \"{key}\""));
+            }
+        }
+    }
+    let properties = s.get("properties").and_then(Value::as_object);
+    for (k, child) in obj {
+        let child_path = if path.is_empty() {
+            k.clone()
+        } else {
+            format!("{path}.{k}")
+        };
+        match properties.and_then(|p| p.get(k)) {
+            Some(child_schema) => validate(root, child_schema, child, &child_path, errors),
+            None => match s.get("additionalProperties") {
+                Some(Value::Bool(false)) => {
+                    push(errors, path, format!("unexpected property \"{k}\""))
+                }
+                Some(extra) => validate(root, extra, child, &child_path, errors),
+                None => {}
+            },
+        }
+    }
+}
+
+// dependentRequired и dependentSchemas, а также draft-07 dependencies с обоими видами
+fn validate_dependencies(
+    root: &Value,
+    s: &Map<String, Value>,
+    obj: &Map<String, Value>,
+    v: &Value,
+    path: &str,
+    errors: &mut Vec<ValidationError>,
+) {
+    for key in ["dependentRequired", "dependentSchemas", "dependencies"] {
+        let Some(Value::Object(deps)) = s.get(key) else {
+            continue;
+        };
+        for (k, dependency) in deps.iter().filter(|(k, _)| obj.contains_key(*k)) {
+            match dependency {
+                Value::Array(required) => {
+                    for other in required.iter().filter_map(Value::as_str) {
+                        if !obj.contains_key(other) {
+                            push(
+                                errors,
+                                path,
+                                format!("property \"{k}\" requires property \"{other}\""),
+                            );
+                        }
+                    }
+                }
+                schema => validate(root, schema, v, path, errors),
+            }
+        }
+    }
+}
+
+fn check_size(
+    s: &Map<String, Value>,
+    min_key: &str,
+    max_key: &str,
+    len: usize,
+    unit: &str,
+    path: &str,
+    errors: &mut Vec<ValidationError>,
+) {
+    let limit = |key: &str| s.get(key).and_then(Value::as_u64);
+    let len = len as u64;
+    if let Some(min) = limit(min_key).filter(|min| len < *min) {
+        push(errors, path, format!("{len} {unit}, {min_key} is {min}"));
+    }
+    if let Some(max) = limit(max_key).filter(|max| len > *max) {
+        push(errors, path, format!("{len} {unit}, {max_key} is {max}"));
+    }
+}
+
+fn has_type(v: &Value, t: &str) -> bool {
+    match (t, v) {
+        ("null", Value::Null)
+        | ("boolean", Value::Bool(_))
+        | ("number", Value::Number(_))
+        | ("string", Value::String(_))
+        | ("array", Value::Array(_))
+        | ("object", Value::Object(_)) => true,
+        // 1.0 — тоже целое
+        ("integer", Value::Number(n)) => {
+            n.is_i64() || n.is_u64() || n.as_f64().is_some_and(|x| x.fract() == 0.0)
+        }
+        _ => false,
+    }
+}
+
+fn push(errors: &mut Vec<ValidationError>, path: &str, message: String) {
+    let path = if path.is_empty() { "<root>" } else { path };
+    errors.push(ValidationError {
+        path: path.to_string(),
+        message,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{infer_schema_from_strs, InferOptions};
+
+    fn errors(schema: &str, sample: &str) -> Vec<(String, String)> {
+        validate_sample_rs(schema, sample)
+            .unwrap()
+            .into_iter()
+            .map(|e| (e.path, e.message))
+            .collect()
+    }
+
+    #[test]
+    fn inferred_schema_accepts_its_samples_and_flags_violations() {
+        let samples = [
+            r#"{"id":1,"name":"ab","tags":["x"],"meta":{"ok":true}}"#,
+            r#"{"id":7,"name":"abcd","tags":["y","z"],"meta":{"ok":false}}"#,
+        ];
+        let opts = InferOptions {
+            emit_numeric_bounds: true,
+            emit_string_bounds: true,
+            emit_array_bounds: true,
+            ..Default::default()
+        };
+        let schema = infer_schema_from_strs(&samples, opts).unwrap();
+        for s in samples {
+            assert_eq!(errors(&schema, s), vec![]);
+        }
+
+        let found = errors(
+            &schema,
+            r#"{"id":"7","name":"abcdefg","tags":[],"meta":{"ok":1}}"#,
+        );
+        let paths: Vec<&str> = found.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(paths, vec!["id", "name", "tags", "meta.ok"]);
+        assert_eq!(found[0].1, "expected type \"integer\", got string");
+
+        let found = errors(&schema, r#"{"id":9,"name":"abc","tags":["a"]}"#);
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "<root>".to_string(),
+                    "missing required property \"meta\"".to_string()
+                ),
+                ("id".to_string(), "9 is greater than maximum 7".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn const_enum_any_of_and_extra_properties() {
+        let schema = r#"{"type":"object","additionalProperties":false,"properties":{
+            "k":{"const":"a"},"e":{"enum":[1,2]},
+            "n":{"anyOf":[{"type":"string"},{"type":"null"}]},
+            "rows":{"type":"array","items":{"type":"integer","nullable":true}},
+            "pt":{"type":"array","prefixItems":[{"type":"string"},{"type":"integer"}],"items":false}}}"#;
+        assert_eq!(
+            errors(
+                schema,
+                r#"{"k":"a","e":2,"n":null,"rows":[1,null],"pt":["a",1]}"#
+            ),
+            vec![]
+        );
+
+        let found = errors(
+            schema,
+            r#"{"k":"b","e":3,"n":1,"pt":[1,1,2],"rows":[1.5],"x":0}"#,
+        );
+        let paths: Vec<&str> = found.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["k", "e", "n", "pt[0]", "pt[2]", "rows[0]", "<root>"]
+        );
+        assert_eq!(found[4].1, "no value is allowed here");
+        assert_eq!(found[6].1, "unexpected property \"x\"");
+    }
+
+    #[test]
+    fn refs_resolve_against_the_root() {
+        let schema = r##"{"type":"object","required":["name"],"properties":{
+            "name":{"type":"string"},
+            "children":{"type":"array","items":{"$ref":"#"}},
+            "owner":{"$ref":"#/$defs/user"},
+            "team":{"$ref":"#/definitions/team"},
+            "lost":{"$ref":"#/$defs/missing"}},
+            "$defs":{"user":{"type":"object","properties":{"id":{"type":"integer"}},"required":["id"]}},
+            "definitions":{"team":{"$ref":"#/$defs/user"}}}"##;
+        let valid = r#"{"name":"a","children":[{"name":"b","children":[]}],"owner":{"id":1},"team":{"id":2}}"#;
+        assert_eq!(errors(schema, valid), vec![]);
+
+        let found = errors(
+            schema,
+            r#"{"name":"a","children":[{"children":[{"name":1}]}],"owner":{},"team":{"id":"x"},"lost":1}"#,
+        );
+        let paths: Vec<&str> = found.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "children[0]",
+                "children[0].children[0].name",
+                "owner",
+                "team.id",
+                "lost"
+            ]
+        );
+        assert_eq!(found[4].1, "cannot resolve $ref \"#/$defs/missing\"");
+
+        let looping =
+            r##"{"$defs":{"a":{"$ref":"#/$defs/b"},"b":{"$ref":"#/$defs/a"}},"$ref":"#/$defs/a"}"##;
+        assert_eq!(errors(looping, "1")[0].0, "<root>");
+    }
+
+    #[test]
+    fn conditions_all_of_and_dependencies() {
+        let samples = [
+            r#"{"kind":"legacy","account":"A1"}"#,
+            r#"{"kind":"card","card_number":"4111","card_expiry":"12/30"}"#,
+            r#"{"card_number":"5500","card_expiry":"01/29"}"#,
+        ];
+        let opts = InferOptions {
+            conditional_rules: vec![crate::ConditionalRule {
+                field: "kind".to_string(),
+                value: serde_json::json!("legacy"),
+            }],
+            detect_dependent_required: true,
+            dependent_required_min_support: 1,
+            ..Default::default()
+        };
+        let schema = infer_schema_from_strs(&samples, opts).unwrap();
+        for s in samples {
+            assert_eq!(errors(&schema, s), vec![]);
+        }
+        let found = errors(&schema, r#"{"kind":"legacy","card_number":"1"}"#);
+        let messages: Vec<&str> = found.iter().map(|(_, m)| m.as_str()).collect();
+        assert!(messages.contains(&"missing required property \"account\""));
+        assert!(messages.contains(&"property \"card_number\" requires property \"card_expiry\""));
+
+        let schema = r#"{"allOf":[{"minimum":0},{"maximum":9}],
+            "dependentSchemas":{"a":{"required":["b"]}}}"#;
+        assert_eq!(errors(schema, "10")[0].1, "10 is greater than maximum 9");
+        assert_eq!(
+            errors(schema, r#"{"a":1}"#)[0].1,
+            "missing required property \"b\""
+        );
+    }
+
+    #[test]
+    fn one_of_needs_exactly_one_branch() {
+        let samples = [
+            r#"{"type":"click","id":"e1","x":1}"#,
+            r#"{"type":"purchase","id":"e2","amount":9.5}"#,
+            r#"{"id":"e4","note":"legacy"}"#,
+        ];
+        let opts = InferOptions {
+            discriminator: Some("type".to_string()),
+            ..Default::default()
+        };
+        let schema = infer_schema_from_strs(&samples, opts).unwrap();
+        for s in samples {
+            assert_eq!(errors(&schema, s), vec![]);
+        }
+        let found = errors(&schema, r#"{"type":"click","id":"e3","x":"oops"}"#);
+        assert_eq!(
+            found,
+            vec![(
+                "<root>".to_string(),
+                "does not match any oneOf branch".to_string()
+            )]
+        );
+
+        let both = r#"{"oneOf":[{"type":"integer"},{"minimum":0}],"not":{"const":5}}"#;
+        assert_eq!(errors(both, "-1"), vec![]);
+        assert_eq!(
+            errors(both, "1")[0].1,
+            "matches 2 oneOf branches, expected exactly one"
+        );
+        assert_eq!(errors(both, "5")[1].1, "matches the schema under not");
+    }
+}
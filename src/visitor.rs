@@ -0,0 +1,97 @@
+use crate::Node;
+
+/// Callback for `walk_node`; `path` uses the `get_at_path` spelling (`a.b`,
+/// `rows[].id`, `""` for the root).
+pub trait NodeVisitor {
+    fn visit_node(&mut self, path: &str, node: &Node);
+}
+
+/// Depth-first traversal: `node` itself, then each property in insertion
+/// order and the array items node, each followed by its own descendants.
+pub fn walk_node(node: &Node, path: &str, visitor: &mut impl NodeVisitor) {
+    visitor.visit_node(path, node);
+    for (k, child) in &node.properties {
+        walk_node(child, &property_path(path, k), visitor);
+    }
+    if let Some(items) = &node.items {
+        walk_node(items, &items_path(path), visitor);
+    }
+}
+
+// Запись путей get_at_path — общая для узлов и для схем (diff, coverage)
+pub(crate) fn property_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+pub(crate) fn items_path(path: &str) -> String {
+    format!("{path}[]")
+}
+
+/// Records every visited path in traversal order.
+#[derive(Debug, Clone, Default)]
+pub struct PathCollectingVisitor {
+    pub paths: Vec<String>,
+}
+
+impl NodeVisitor for PathCollectingVisitor {
+    fn visit_node(&mut self, path: &str, _node: &Node) {
+        self.paths.push(path.to_string());
+    }
+}
+
+impl Node {
+    /// Every path below this node (the node itself excluded) that
+    /// `get_at_path` resolves, in traversal order.
+    pub fn collect_paths(&self) -> Vec<String> {
+        let mut visitor = PathCollectingVisitor::default();
+        walk_node(self, "", &mut visitor);
+        visitor.paths.remove(0);
+        visitor.paths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_samples, InferOptions, TypeTag};
+
+    struct TypeCounter(Vec<(String, usize)>);
+
+    impl NodeVisitor for TypeCounter {
+        fn visit_node(&mut self, path: &str, node: &Node) {
+            self.0.push((path.to_string(), node.types.len()));
+        }
+    }
+
+    #[test]
+    fn walk_visits_root_properties_and_items() {
+        let samples = [
+            r#"{"id":1,"rows":[{"n":1}],"grid":[[true]],"meta":{"x":"a"}}"#,
+            r#"{"id":"2","rows":[],"meta":null}"#,
+        ];
+        let root = parse_samples(&samples, &InferOptions::default()).unwrap();
+        let paths = root.collect_paths();
+        assert_eq!(
+            paths,
+            ["id", "rows", "rows[]", "rows[].n", "grid", "grid[]", "grid[][]", "meta", "meta.x"]
+        );
+        for p in &paths {
+            assert!(root.get_at_path(p).is_some(), "{p}");
+        }
+
+        let mut counter = TypeCounter(Vec::new());
+        walk_node(&root, "", &mut counter);
+        assert_eq!(counter.0[0], (String::new(), 1));
+        assert!(counter.0.contains(&("id".to_string(), 2)));
+        assert!(counter.0.contains(&("meta".to_string(), 2)));
+        assert!(root
+            .get_at_path("meta")
+            .unwrap()
+            .types
+            .contains(&TypeTag::Null));
+    }
+}
@@ -0,0 +1,51 @@
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    diff_schemas_json_rs, infer_schema_rs, merge_schemas_rs, DiffOptions, InferError, InferOptions,
+};
+
+impl From<InferError> for JsValue {
+    fn from(e: InferError) -> Self {
+        JsValue::from_str(&e.to_string())
+    }
+}
+
+// Поля InferOptions поверх значений по умолчанию; неизвестный ключ — ошибка, как в kwargs Python
+fn options_from_json(options: Option<String>) -> Result<InferOptions, JsValue> {
+    let invalid = |e: serde_json::Error| JsValue::from_str(&format!("invalid options: {e}"));
+    let mut merged = match serde_json::to_value(InferOptions::default()).map_err(invalid)? {
+        Value::Object(m) => m,
+        _ => Map::new(),
+    };
+    if let Some(text) = options {
+        let overrides: Map<String, Value> = serde_json::from_str(&text).map_err(invalid)?;
+        for (key, value) in overrides {
+            if !merged.contains_key(&key) {
+                return Err(JsValue::from_str(&format!("unknown option: {key}")));
+            }
+            merged.insert(key, value);
+        }
+    }
+    serde_json::from_value(Value::Object(merged)).map_err(invalid)
+}
+
+/// infer_schema(samples: string[], options?: string(JSON object of
+/// InferOptions fields)) -> string(JSON)
+#[wasm_bindgen]
+pub fn infer_schema(samples: Vec<String>, options: Option<String>) -> Result<String, JsValue> {
+    let options = options_from_json(options)?;
+    Ok(infer_schema_rs(&samples, options)?)
+}
+
+/// diff_schemas(a: string(JSON), b: string(JSON)) -> string(JSON)
+#[wasm_bindgen]
+pub fn diff_schemas(a: String, b: String) -> Result<String, JsValue> {
+    Ok(diff_schemas_json_rs(&a, &b, DiffOptions::default())?)
+}
+
+/// merge_schemas(a: string(JSON), b: string(JSON)) -> string(JSON)
+#[wasm_bindgen]
+pub fn merge_schemas(a: String, b: String) -> Result<String, JsValue> {
+    Ok(merge_schemas_rs(&a, &b)?)
+}
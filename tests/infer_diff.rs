@@ -1,11 +1,151 @@
-use aif_core::{diff_schemas_rs, infer_schema_rs};
+use aif_core::{
+    diff_schemas_rs, infer_schema_from_jsonl_rs, infer_schema_from_reader, infer_schema_from_strs,
+    infer_schema_rs, merge_nodes, ConflictStrategy, DiffOptions, InferError, InferOptions, Node,
+    SchemaInferrer,
+};
+use serde_json::{json, Value};
+use std::io::Cursor;
 
 #[test]
 fn infer_then_diff_works() {
     let s1 = vec![r#"{"id":1,"name":"Alice"}"#.to_string()];
     let s2 = vec![r#"{"id":2,"name":"Bob","tags":["x"]}"#.to_string()];
-    let a = infer_schema_rs(&s1).unwrap();
-    let b = infer_schema_rs(&s2).unwrap();
-    let d = diff_schemas_rs(&a, &b).unwrap();
-    assert!(d.contains("added"));
+    let a = infer_schema_rs(&s1, InferOptions::default()).unwrap();
+    let b = infer_schema_rs(&s2, InferOptions::default()).unwrap();
+    let d = diff_schemas_rs(&a, &b, DiffOptions::default()).unwrap();
+    assert_eq!(d.added, vec!["tags", "tags[]"]);
+    assert!(d.removed.is_empty());
+    assert_eq!(d.common, vec!["id", "name"]);
+}
+
+#[test]
+fn infer_from_jsonl_file() {
+    let path = std::env::temp_dir().join(format!("aif-core-{}.jsonl", std::process::id()));
+    std::fs::write(
+        &path,
+        "{\"id\":1,\"name\":\"a\"}\n\n{\"id\":2}\n{\"id\":3,\"tags\":[]}\n",
+    )
+    .unwrap();
+    let out = infer_schema_from_jsonl_rs(path.to_str().unwrap(), InferOptions::default());
+    std::fs::remove_file(&path).unwrap();
+
+    let v: serde_json::Value = serde_json::from_str(&out.unwrap()).unwrap();
+    assert_eq!(v["required"], serde_json::json!(["id"]));
+    assert!(v["properties"].get("name").is_some());
+    assert!(v["properties"].get("tags").is_some());
+}
+
+#[test]
+fn infer_from_reader_reports_bad_line() {
+    let err = infer_schema_from_reader(Cursor::new("{}\n  \n{oops}\n"), InferOptions::default())
+        .unwrap_err();
+    assert!(matches!(err, InferError::InvalidJson { index: 1, .. }));
+
+    let err =
+        infer_schema_from_jsonl_rs("/nonexistent/aif.jsonl", InferOptions::default()).unwrap_err();
+    assert!(matches!(err, InferError::Io(_)));
+}
+
+#[test]
+fn diff_rejects_invalid_schema() {
+    let err = diff_schemas_rs("{}", "{", DiffOptions::default()).unwrap_err();
+    assert!(matches!(
+        err,
+        InferError::InvalidSchema {
+            name: "schema B",
+            ..
+        }
+    ));
+}
+
+#[test]
+fn borrowed_samples_match_owned() {
+    let borrowed = [r#"{"id":1}"#, r#"{"id":2,"name":"x"}"#];
+    let owned: Vec<String> = borrowed.iter().map(|s| s.to_string()).collect();
+
+    assert_eq!(
+        infer_schema_from_strs(&borrowed, InferOptions::default()).unwrap(),
+        infer_schema_rs(&owned, InferOptions::default()).unwrap()
+    );
+}
+
+fn infer(samples: &[&str], options: InferOptions) -> Value {
+    serde_json::from_str(&infer_schema_from_strs(samples, options).unwrap()).unwrap()
+}
+
+#[test]
+fn array_root_keeps_items() {
+    let v = infer(&["[1,2,3]", "[4]"], InferOptions::default());
+    assert_eq!(v["$schema"], "https://json-schema.org/draft/2020-12/schema");
+    assert_eq!(v["type"], "array");
+    assert_eq!(v["items"]["type"], "integer");
+    assert!(v.get("properties").is_none());
+
+    let v = infer(
+        &[r#"[{"id":1}]"#, r#"[{"id":2,"name":"x"}]"#],
+        InferOptions::default(),
+    );
+    assert_eq!(v["items"]["required"], json!(["id"]));
+
+    let legacy = InferOptions {
+        legacy_object_root: true,
+        ..Default::default()
+    };
+    let v = infer(&["[1,2,3]"], legacy);
+    assert_eq!(v["type"], "object");
+    assert_eq!(v["properties"], json!({}));
+}
+
+#[test]
+fn scalar_and_mixed_roots() {
+    let v = infer(&[r#""a""#, r#""b""#], InferOptions::default());
+    assert_eq!(v["type"], "string");
+    assert!(v.get("properties").is_none());
+
+    let mixed = [r#"{"id":1}"#, r#""x""#];
+    let v = infer(&mixed, InferOptions::default());
+    assert_eq!(v["type"], json!(["object", "string"]));
+    assert!(v["properties"].get("id").is_some());
+
+    let any_of = InferOptions {
+        conflict_strategy: ConflictStrategy::AnyOf,
+        ..Default::default()
+    };
+    let v = infer(&mixed, any_of);
+    assert!(v.get("type").is_none());
+    let branches = v["anyOf"].as_array().unwrap();
+    assert_eq!(branches.len(), 2);
+
+    // схема корня-объекта не меняется
+    let object = [r#"{"id":1}"#, r#"{"id":2}"#];
+    let legacy = InferOptions {
+        legacy_object_root: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        infer(&object, InferOptions::default()),
+        infer(&object, legacy)
+    );
+}
+
+#[test]
+fn saved_schema_keeps_learning_as_a_node() {
+    let saved = infer_schema_from_strs(
+        &[r#"{"id":1,"name":"a"}"#, r#"{"id":2,"name":"b"}"#],
+        InferOptions::default(),
+    )
+    .unwrap();
+    let loaded = Node::from_json_schema(&serde_json::from_str(&saved).unwrap()).unwrap();
+    assert_eq!(loaded.property_names(), ["id", "name"]);
+
+    let mut inferrer = SchemaInferrer::new(InferOptions::default());
+    inferrer.feed(r#"{"id":"x3","tags":["t"]}"#).unwrap();
+    let merged = merge_nodes(loaded, inferrer.root().clone());
+    let v = merged.to_json_schema(&InferOptions::default());
+    assert_eq!(v["required"], json!(["id"]));
+    assert_eq!(v["properties"]["id"]["type"], json!(["integer", "string"]));
+    assert_eq!(v["properties"]["tags"]["items"]["type"], "string");
+
+    let err = Node::from_json_schema(&json!({"type": "nope"})).unwrap_err();
+    assert!(matches!(err, InferError::UnsupportedSchema { .. }));
 }
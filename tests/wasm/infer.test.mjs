@@ -0,0 +1,29 @@
+// Smoke test for the wasm-pack build:
+//   wasm-pack build --target web --features wasm && node --test tests/wasm/
+import { test } from "node:test";
+import assert from "node:assert/strict";
+import { readFile } from "node:fs/promises";
+
+import init, { infer_schema, diff_schemas, merge_schemas } from "../../pkg/aif_core.js";
+
+const wasm = await readFile(new URL("../../pkg/aif_core_bg.wasm", import.meta.url));
+await init({ module_or_path: wasm });
+
+test("infer, diff and merge", () => {
+  const a = infer_schema(['{"id":1,"name":"x"}', '{"id":2}']);
+  assert.deepEqual(JSON.parse(a).required, ["id"]);
+
+  const b = infer_schema(['{"id":"1"}', '{"id":"2"}'], JSON.stringify({ emit_property_counts: true }));
+  assert.equal(JSON.parse(b).minProperties, 1);
+
+  const diff = JSON.parse(diff_schemas(a, b));
+  assert.ok(diff.removed.includes("name"));
+
+  const merged = JSON.parse(merge_schemas(a, b));
+  assert.deepEqual(merged.properties.id.type, ["integer", "string"]);
+});
+
+test("errors surface as exceptions", () => {
+  assert.throws(() => infer_schema(["{"]), /sample 0/);
+  assert.throws(() => infer_schema([], '{"no_such_option":1}'), /unknown option/);
+});